@@ -0,0 +1,338 @@
+//! Per-application rules.
+//!
+//! An [`AppRule`] such as "while `mpv.exe` is in the foreground, suspend
+//! the overlays" changes protection while a matching window has focus and
+//! undoes the change once it loses it, so users no longer have to toggle
+//! protection by hand around games and video playback.
+//!
+//! Rules match on the foreground window's process name or window class
+//! ([`WindowInfo`]).  [`evaluate`] is a pure function of the rules and that
+//! information; the [`AppWatcher`] samples a [`ForegroundSource`] and reports
+//! only changes, so tests can drive it with synthetic windows.
+
+use serde::{Deserialize, Serialize};
+
+// ── Foreground window ────────────────────────────────────────────────────────
+
+/// What the rules can see of the foreground window.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WindowInfo {
+    /// File name of the owning process's executable, e.g. `"mpv.exe"`.
+    pub process: String,
+    /// Win32 window class, e.g. `"UnityWndClass"`.
+    pub class: String,
+}
+
+/// Source of the current foreground window.
+pub trait ForegroundSource {
+    /// `None` if there is no foreground window or it cannot be inspected.
+    fn foreground(&self) -> Option<WindowInfo>;
+}
+
+/// The interactive session's foreground window.
+pub struct SystemForeground;
+
+impl ForegroundSource for SystemForeground {
+    #[cfg(windows)]
+    fn foreground(&self) -> Option<WindowInfo> {
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Threading::{
+            OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+            QueryFullProcessImageNameW,
+        };
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GetClassNameW, GetForegroundWindow, GetWindowThreadProcessId,
+        };
+        use windows::core::PWSTR;
+
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0.is_null() {
+                return None;
+            }
+
+            let mut class = [0u16; 256];
+            let len = GetClassNameW(hwnd, &mut class).max(0) as usize;
+            let class = String::from_utf16_lossy(&class[..len]);
+
+            let mut pid = 0u32;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+            // Elevated processes cannot be opened; their class still matches.
+            let mut process = String::new();
+            if let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+                let mut path = [0u16; 1024];
+                let mut len = path.len() as u32;
+                if QueryFullProcessImageNameW(
+                    handle,
+                    PROCESS_NAME_WIN32,
+                    PWSTR(path.as_mut_ptr()),
+                    &mut len,
+                )
+                .is_ok()
+                {
+                    let path = String::from_utf16_lossy(&path[..len as usize]);
+                    process = path.rsplit('\\').next().unwrap_or_default().to_string();
+                }
+                let _ = CloseHandle(handle);
+            }
+
+            Some(WindowInfo { process, class })
+        }
+    }
+
+    /// No portable notion of a foreground window; rules never match.
+    #[cfg(not(windows))]
+    fn foreground(&self) -> Option<WindowInfo> {
+        None
+    }
+}
+
+// ── Rules ────────────────────────────────────────────────────────────────────
+
+/// What a rule matches on.  Comparisons ignore ASCII case.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppMatch {
+    /// Executable file name; the `.exe` suffix is optional.
+    Process(String),
+    /// Exact window class.
+    WindowClass(String),
+}
+
+impl AppMatch {
+    fn matches(&self, window: &WindowInfo) -> bool {
+        match self {
+            AppMatch::Process(name) => {
+                let stem = |s: &str| {
+                    let s = s.to_ascii_lowercase();
+                    s.strip_suffix(".exe").map(str::to_string).unwrap_or(s)
+                };
+                !window.process.is_empty() && stem(name) == stem(&window.process)
+            }
+            AppMatch::WindowClass(class) => class.eq_ignore_ascii_case(&window.class),
+        }
+    }
+}
+
+/// What happens while a rule matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppAction {
+    /// Hide the overlays, even if protection is on.
+    Suspend,
+    /// Use this opacity on every overlay.
+    Opacity(u8),
+    /// Show the overlays on the selected monitors, even if protection is off.
+    ForceOn,
+}
+
+/// "While the foreground window matches `matcher`, do `action`."
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppRule {
+    /// Assigned by the daemon when the rule is added.
+    #[serde(default)]
+    pub id: u32,
+    pub matcher: AppMatch,
+    pub action: AppAction,
+}
+
+impl AppRule {
+    /// Why this rule cannot be used, if anything.
+    pub fn validate(&self) -> Result<(), String> {
+        let name = match &self.matcher {
+            AppMatch::Process(name) | AppMatch::WindowClass(name) => name,
+        };
+        if name.trim().is_empty() {
+            return Err("an application rule needs a process name or window class".into());
+        }
+        Ok(())
+    }
+
+    /// Whether this rule applies to `window`.
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        self.matcher.matches(window)
+    }
+}
+
+/// Action the rules call for with `window` in the foreground.  The first
+/// matching rule in `rules` wins; `None` means protection behaves normally.
+pub fn evaluate(rules: &[AppRule], window: Option<&WindowInfo>) -> Option<AppAction> {
+    let window = window?;
+    rules.iter().find(|r| r.matches(window)).map(|r| r.action)
+}
+
+// ── Watcher ──────────────────────────────────────────────────────────────────
+
+/// Edge-triggered rule evaluation.
+pub struct AppWatcher<F: ForegroundSource> {
+    source: F,
+    /// Action of the previous tick.
+    last: Option<AppAction>,
+}
+
+impl<F: ForegroundSource> AppWatcher<F> {
+    pub fn new(source: F) -> Self {
+        Self { source, last: None }
+    }
+
+    /// Sample the foreground window and report a change of action, if any:
+    /// `Some(Some(action))` when a rule starts applying (or a different one
+    /// takes over), `Some(None)` when the last one stops.
+    pub fn tick(&mut self, rules: &[AppRule]) -> Option<Option<AppAction>> {
+        let action = evaluate(rules, self.source.foreground().as_ref());
+        if action == self.last {
+            return None;
+        }
+        self.last = action;
+        Some(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake::Fake;
+
+    fn window(process: &str, class: &str) -> WindowInfo {
+        WindowInfo {
+            process: process.into(),
+            class: class.into(),
+        }
+    }
+
+    fn rule(matcher: AppMatch, action: AppAction) -> AppRule {
+        AppRule {
+            id: 0,
+            matcher,
+            action,
+        }
+    }
+
+    impl ForegroundSource for Fake<Option<WindowInfo>> {
+        fn foreground(&self) -> Option<WindowInfo> {
+            self.get()
+        }
+    }
+
+    #[test]
+    fn process_match_ignores_case_and_exe_suffix() {
+        let mpv = rule(AppMatch::Process("MPV".into()), AppAction::Suspend);
+        assert!(mpv.matches(&window("mpv.exe", "mpv")));
+        assert!(mpv.matches(&window("Mpv.EXE", "")));
+        assert!(!mpv.matches(&window("mpv-helper.exe", "mpv")));
+
+        let with_suffix = rule(AppMatch::Process("mpv.exe".into()), AppAction::Suspend);
+        assert!(with_suffix.matches(&window("mpv.exe", "")));
+    }
+
+    #[test]
+    fn unknown_process_never_matches_by_name() {
+        // Elevated windows report no process name.
+        let rule = rule(AppMatch::Process(".exe".into()), AppAction::Suspend);
+        assert!(!rule.matches(&window("", "SomeClass")));
+    }
+
+    #[test]
+    fn window_class_match_is_exact_but_ignores_case() {
+        let unity = rule(
+            AppMatch::WindowClass("UnityWndClass".into()),
+            AppAction::ForceOn,
+        );
+        assert!(unity.matches(&window("game.exe", "unitywndclass")));
+        assert!(!unity.matches(&window("game.exe", "UnityWndClass2")));
+    }
+
+    #[test]
+    fn evaluate_without_a_foreground_window_is_none() {
+        let rules = [rule(AppMatch::Process("mpv".into()), AppAction::Suspend)];
+        assert_eq!(evaluate(&rules, None), None);
+    }
+
+    #[test]
+    fn evaluate_without_a_matching_rule_is_none() {
+        let rules = [rule(AppMatch::Process("mpv".into()), AppAction::Suspend)];
+        assert_eq!(
+            evaluate(&rules, Some(&window("notepad.exe", "Notepad"))),
+            None
+        );
+        assert_eq!(evaluate(&[], Some(&window("mpv.exe", "mpv"))), None);
+    }
+
+    #[test]
+    fn evaluate_picks_the_first_matching_rule() {
+        let rules = [
+            rule(AppMatch::Process("notepad".into()), AppAction::ForceOn),
+            rule(AppMatch::WindowClass("mpv".into()), AppAction::Opacity(80)),
+            rule(AppMatch::Process("mpv".into()), AppAction::Suspend),
+        ];
+        assert_eq!(
+            evaluate(&rules, Some(&window("mpv.exe", "mpv"))),
+            Some(AppAction::Opacity(80))
+        );
+        assert_eq!(
+            evaluate(&rules, Some(&window("notepad.exe", "Notepad"))),
+            Some(AppAction::ForceOn)
+        );
+    }
+
+    #[test]
+    fn rules_need_a_name() {
+        assert!(
+            rule(AppMatch::Process("  ".into()), AppAction::Suspend)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            rule(AppMatch::WindowClass(String::new()), AppAction::Suspend)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            rule(AppMatch::Process("mpv".into()), AppAction::Suspend)
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn watcher_reports_only_changes() {
+        let fg = Fake::default();
+        let mut watcher = AppWatcher::new(fg.clone());
+        let rules = [rule(AppMatch::Process("mpv".into()), AppAction::Suspend)];
+
+        assert_eq!(watcher.tick(&rules), None);
+
+        fg.set(Some(window("mpv.exe", "mpv")));
+        assert_eq!(watcher.tick(&rules), Some(Some(AppAction::Suspend)));
+        assert_eq!(watcher.tick(&rules), None);
+
+        fg.set(Some(window("explorer.exe", "CabinetWClass")));
+        assert_eq!(watcher.tick(&rules), Some(None));
+        assert_eq!(watcher.tick(&rules), None);
+    }
+
+    #[test]
+    fn watcher_reports_a_different_rule_taking_over() {
+        let fg = Fake::default();
+        let mut watcher = AppWatcher::new(fg.clone());
+        let rules = [
+            rule(AppMatch::Process("mpv".into()), AppAction::Suspend),
+            rule(AppMatch::Process("game".into()), AppAction::Opacity(60)),
+        ];
+
+        fg.set(Some(window("mpv.exe", "mpv")));
+        watcher.tick(&rules);
+        fg.set(Some(window("game.exe", "UnityWndClass")));
+        assert_eq!(watcher.tick(&rules), Some(Some(AppAction::Opacity(60))));
+    }
+
+    #[test]
+    fn watcher_notices_rules_changing_under_a_steady_window() {
+        let fg = Fake::default();
+        fg.set(Some(window("mpv.exe", "mpv")));
+        let mut watcher = AppWatcher::new(fg);
+
+        assert_eq!(watcher.tick(&[]), None);
+        let rules = [rule(AppMatch::Process("mpv".into()), AppAction::Suspend)];
+        assert_eq!(watcher.tick(&rules), Some(Some(AppAction::Suspend)));
+        assert_eq!(watcher.tick(&[]), Some(None));
+    }
+}
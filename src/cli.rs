@@ -0,0 +1,438 @@
+//! Command-line control of a running daemon.
+//!
+//! ```text
+//! oled-care status [--json]
+//! oled-care enable [--monitor NAME]... [--opacity PERCENT]
+//! oled-care disable
+//! oled-care quit
+//! ```
+//!
+//! `NAME` may be a GDI name (`DISPLAY2` or `\\.\DISPLAY2`), a friendly model
+//! name, or a monitor id as printed by `status`.  Every command prints the
+//! resulting daemon state.  Commands the daemon refuses (e.g. enabling with
+//! no monitor selected) print its reason and exit with [`EXIT_REJECTED`].
+
+use std::collections::BTreeSet;
+use std::io::{self, BufReader, BufWriter};
+
+use crate::ipc::{self, ClientKind, Connection, DaemonMsg, DaemonState, UiMsg, connect_to_daemon};
+use crate::monitor::{MonitorId, MonitorInfo};
+
+/// Exit code: the command succeeded.
+pub const EXIT_OK: i32 = 0;
+/// Exit code: the daemon is not running or the connection failed.
+pub const EXIT_NO_DAEMON: i32 = 1;
+/// Exit code: bad command-line arguments.
+pub const EXIT_USAGE: i32 = 2;
+/// Exit code: the daemon refused the command, did not end up in the
+/// requested state, or speaks an incompatible protocol.
+pub const EXIT_REJECTED: i32 = 3;
+
+const USAGE: &str = "\
+Usage:
+  oled-care                      start OLED Care (or open its window)
+  oled-care status [--json]      print the current state
+  oled-care enable [--monitor NAME]... [--opacity PERCENT]
+                                 enable protection, optionally on exactly
+                                 the given monitors and at a new opacity
+  oled-care disable              disable protection
+  oled-care quit                 shut down OLED Care";
+
+/// Subcommands understood by [`run`].
+pub const COMMANDS: &[&str] = &["status", "enable", "disable", "quit", "help", "--help"];
+
+// ── Argument parsing ─────────────────────────────────────────────────────────
+
+/// A parsed command line.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Status {
+        json: bool,
+    },
+    Enable {
+        monitors: Vec<String>,
+        opacity_pct: Option<u8>,
+    },
+    Disable,
+    Quit,
+    Help,
+}
+
+fn parse(args: &[String]) -> Result<Command, String> {
+    let (cmd, rest) = args.split_first().ok_or("missing command")?;
+    match cmd.as_str() {
+        "status" => match rest {
+            [] => Ok(Command::Status { json: false }),
+            [flag] if flag == "--json" => Ok(Command::Status { json: true }),
+            _ => Err(format!(
+                "unexpected arguments to status: {}",
+                rest.join(" ")
+            )),
+        },
+        "enable" => {
+            let mut monitors = Vec::new();
+            let mut opacity_pct = None;
+            let mut it = rest.iter();
+            while let Some(arg) = it.next() {
+                match arg.as_str() {
+                    "--monitor" | "-m" => {
+                        monitors.push(it.next().ok_or("--monitor needs a value")?.clone());
+                    }
+                    "--opacity" | "-o" => {
+                        let v = it.next().ok_or("--opacity needs a value")?;
+                        let pct: u8 = v
+                            .trim_end_matches('%')
+                            .parse()
+                            .ok()
+                            .filter(|p| *p <= 100)
+                            .ok_or_else(|| format!("invalid opacity '{}' (expected 0-100)", v))?;
+                        opacity_pct = Some(pct);
+                    }
+                    other => return Err(format!("unknown option '{}'", other)),
+                }
+            }
+            Ok(Command::Enable {
+                monitors,
+                opacity_pct,
+            })
+        }
+        "disable" if rest.is_empty() => Ok(Command::Disable),
+        "quit" if rest.is_empty() => Ok(Command::Quit),
+        "help" | "--help" => Ok(Command::Help),
+        other => Err(format!("unknown command or arguments: {}", other)),
+    }
+}
+
+// ── Entry point ───────────────────────────────────────────────────────────────
+
+/// Run a CLI command (`args` excludes the program name) and return the
+/// process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let cmd = match parse(args) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("oled-care: {}\n\n{}", e, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+    if let Command::Help = cmd {
+        println!("{}", USAGE);
+        return EXIT_OK;
+    }
+
+    let mut client = match Client::connect() {
+        Ok(c) => c,
+        Err(ClientError::Io(e)) => {
+            eprintln!("oled-care: OLED Care is not running ({})", e);
+            return EXIT_NO_DAEMON;
+        }
+        Err(ClientError::Rejected(e)) => {
+            eprintln!("oled-care: {}", e);
+            return EXIT_REJECTED;
+        }
+    };
+
+    match execute(&mut client, cmd) {
+        Ok(code) => code,
+        Err(ClientError::Io(e)) => {
+            eprintln!("oled-care: lost connection to daemon ({})", e);
+            EXIT_NO_DAEMON
+        }
+        Err(ClientError::Rejected(e)) => {
+            eprintln!("oled-care: {}", e);
+            EXIT_REJECTED
+        }
+    }
+}
+
+fn execute(client: &mut Client, cmd: Command) -> Result<i32, ClientError> {
+    match cmd {
+        Command::Help => Ok(EXIT_OK),
+
+        Command::Status { json } => {
+            let state = client.send(UiMsg::GetState)?;
+            if json {
+                match serde_json::to_string_pretty(&state) {
+                    Ok(s) => println!("{}", s),
+                    Err(e) => {
+                        eprintln!("oled-care: cannot encode state: {}", e);
+                        return Ok(EXIT_REJECTED);
+                    }
+                }
+            } else {
+                print_state(&state);
+            }
+            Ok(EXIT_OK)
+        }
+
+        Command::Enable {
+            monitors,
+            opacity_pct,
+        } => {
+            let state = client.send(UiMsg::GetState)?;
+
+            if !monitors.is_empty() {
+                let mut wanted = BTreeSet::new();
+                for name in &monitors {
+                    match resolve_monitor(&state.monitors, name) {
+                        Ok(id) => {
+                            wanted.insert(id);
+                        }
+                        Err(e) => {
+                            eprintln!("oled-care: {}", e);
+                            return Ok(EXIT_REJECTED);
+                        }
+                    }
+                }
+
+                // Make the selection exactly the requested set.  The daemon
+                // swaps it under active protection without a gap.
+                client.send(UiMsg::SetSelection(wanted))?;
+            }
+
+            if let Some(pct) = opacity_pct {
+                let opacity = ((pct as f32 / 100.0) * 255.0).round() as u8;
+                client.send(UiMsg::SetOpacity(opacity))?;
+            }
+
+            let state = client.send(UiMsg::SetActive(true))?;
+            print_state(&state);
+            Ok(if state.overlays_active {
+                EXIT_OK
+            } else {
+                EXIT_REJECTED
+            })
+        }
+
+        Command::Disable => {
+            let state = client.send(UiMsg::SetActive(false))?;
+            print_state(&state);
+            Ok(if state.overlays_active {
+                EXIT_REJECTED
+            } else {
+                EXIT_OK
+            })
+        }
+
+        Command::Quit => {
+            // The daemon exits without replying; a closed connection is the
+            // expected outcome.
+            let _ = client.send(UiMsg::Quit);
+            Ok(EXIT_OK)
+        }
+    }
+}
+
+// ── Connection ────────────────────────────────────────────────────────────────
+
+/// Why a daemon request failed.
+enum ClientError {
+    /// Connecting, reading or writing failed.
+    Io(io::Error),
+    /// The daemon answered, but refused the command or the handshake.
+    Rejected(String),
+}
+
+impl From<io::Error> for ClientError {
+    fn from(e: io::Error) -> Self {
+        ClientError::Io(e)
+    }
+}
+
+/// A synchronous request/reply connection to the daemon.
+struct Client {
+    reader: BufReader<Connection>,
+    writer: BufWriter<Connection>,
+    next_id: u64,
+}
+
+impl Client {
+    fn connect() -> Result<Self, ClientError> {
+        let stream = connect_to_daemon(500)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = BufWriter::new(stream);
+        ipc::handshake(&mut reader, &mut writer, 0, ClientKind::Other)
+            .map_err(ClientError::Rejected)?;
+        Ok(Self {
+            reader,
+            writer,
+            next_id: 1,
+        })
+    }
+
+    /// Send one command and return the state the daemon replies with.
+    fn send(&mut self, msg: UiMsg) -> Result<DaemonState, ClientError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        match ipc::request(&mut self.reader, &mut self.writer, id, msg)? {
+            DaemonMsg::State(s) => Ok(s),
+            DaemonMsg::Error { message, .. } => Err(ClientError::Rejected(message)),
+            other => Err(ClientError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unexpected reply: {:?}", other),
+            ))),
+        }
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// GDI device name without the `\\.\` prefix (e.g. `DISPLAY2`).
+fn short_name(mon: &MonitorInfo) -> &str {
+    mon.name.trim_start_matches("\\\\.\\")
+}
+
+/// Find the monitor a user-supplied name refers to.
+fn resolve_monitor(monitors: &[MonitorInfo], name: &str) -> Result<MonitorId, String> {
+    let matches: Vec<&MonitorInfo> = monitors
+        .iter()
+        .filter(|m| {
+            m.id.0 == name
+                || m.name.eq_ignore_ascii_case(name)
+                || short_name(m).eq_ignore_ascii_case(name)
+                || (!m.friendly_name.is_empty() && m.friendly_name.eq_ignore_ascii_case(name))
+        })
+        .collect();
+
+    match matches.as_slice() {
+        [one] => Ok(one.id.clone()),
+        [] => Err(format!("no connected monitor matches '{}'", name)),
+        _ => Err(format!(
+            "'{}' matches {} monitors; use the GDI name or id instead",
+            name,
+            matches.len()
+        )),
+    }
+}
+
+fn pct(opacity: u8) -> u8 {
+    ((opacity as f32 / 255.0) * 100.0).round() as u8
+}
+
+/// Print a human-readable summary of `state`.
+fn print_state(state: &DaemonState) {
+    println!(
+        "Protection: {}",
+        if state.overlays_active { "on" } else { "off" }
+    );
+    println!("Opacity:    {}%", pct(state.opacity));
+    println!("Monitors:");
+    for mon in &state.monitors {
+        let selected = if state.selected.contains(&mon.id) {
+            "x"
+        } else {
+            " "
+        };
+        let overlay = if state.overlay_alive.contains(&mon.id) {
+            "  overlay"
+        } else {
+            ""
+        };
+        let custom = state
+            .monitor_settings
+            .get(&mon.id)
+            .and_then(|ms| ms.opacity)
+            .map(|o| format!("  {}%", pct(o)))
+            .unwrap_or_default();
+        println!(
+            "  [{}] {:<10} {:<24} {}x{} @ ({}, {}){}{}",
+            selected,
+            short_name(mon),
+            mon.friendly_name,
+            mon.width,
+            mon.height,
+            mon.x,
+            mon.y,
+            custom,
+            overlay
+        );
+        println!("      id: {}", mon.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn monitor(id: &str, name: &str, friendly: &str) -> MonitorInfo {
+        MonitorInfo {
+            name: name.into(),
+            friendly_name: friendly.into(),
+            ..MonitorInfo::at(id, (0, 0, 1920, 1080))
+        }
+    }
+
+    #[test]
+    fn parses_status() {
+        assert_eq!(parse(&args("status")), Ok(Command::Status { json: false }));
+        assert_eq!(
+            parse(&args("status --json")),
+            Ok(Command::Status { json: true })
+        );
+        assert!(parse(&args("status --yaml")).is_err());
+    }
+
+    #[test]
+    fn parses_enable_options() {
+        assert_eq!(
+            parse(&args("enable -m DISPLAY1 --monitor DISPLAY2 --opacity 40%")),
+            Ok(Command::Enable {
+                monitors: vec!["DISPLAY1".into(), "DISPLAY2".into()],
+                opacity_pct: Some(40),
+            })
+        );
+        assert_eq!(
+            parse(&args("enable")),
+            Ok(Command::Enable {
+                monitors: vec![],
+                opacity_pct: None,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_bad_enable_options() {
+        assert!(parse(&args("enable --opacity 101")).is_err());
+        assert!(parse(&args("enable --opacity half")).is_err());
+        assert!(parse(&args("enable --monitor")).is_err());
+        assert!(parse(&args("enable --force")).is_err());
+    }
+
+    #[test]
+    fn parses_argumentless_commands() {
+        assert_eq!(parse(&args("disable")), Ok(Command::Disable));
+        assert_eq!(parse(&args("quit")), Ok(Command::Quit));
+        assert_eq!(parse(&args("--help")), Ok(Command::Help));
+        assert!(parse(&args("disable now")).is_err());
+        assert!(parse(&args("restart")).is_err());
+        assert!(parse(&[]).is_err());
+    }
+
+    #[test]
+    fn every_parsed_command_is_listed() {
+        for cmd in COMMANDS {
+            assert!(parse(&[cmd.to_string()]).is_ok(), "{}", cmd);
+        }
+    }
+
+    #[test]
+    fn resolves_monitor_names() {
+        let monitors = [
+            monitor("GSM5B7F-1", r"\\.\DISPLAY1", "LG ULTRAGEAR"),
+            monitor("GSM5B7F-2", r"\\.\DISPLAY2", "LG ULTRAGEAR"),
+            monitor("DEL4242@c", r"\\.\DISPLAY3", "DELL U2720Q"),
+        ];
+        let id = |s: &str| Ok(MonitorId(s.into()));
+        assert_eq!(resolve_monitor(&monitors, "display2"), id("GSM5B7F-2"));
+        assert_eq!(resolve_monitor(&monitors, r"\\.\DISPLAY1"), id("GSM5B7F-1"));
+        assert_eq!(resolve_monitor(&monitors, "dell u2720q"), id("DEL4242@c"));
+        assert_eq!(resolve_monitor(&monitors, "GSM5B7F-2"), id("GSM5B7F-2"));
+        // Two panels of the same model need a more specific name.
+        assert!(resolve_monitor(&monitors, "LG ULTRAGEAR").is_err());
+        assert!(resolve_monitor(&monitors, "DISPLAY9").is_err());
+    }
+}
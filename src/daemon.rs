@@ -3,21 +3,49 @@
 //! The daemon:
 //! - Owns the [`OverlayManager`] (manages overlay windows)
 //! - Runs the system tray icon
-//! - Listens on TCP 127.0.0.1:17432 for UI connections
+//! - Listens for UI / CLI connections on a per-user IPC endpoint
+//!   (see [`crate::ipc::transport`])
 //! - Processes commands from the UI (SetOpacity, ToggleMonitor, SetActive, …)
+//! - Pushes state / topology / overlay events to subscribed clients
 //! - Spawns `oled-care.exe --ui` when the user clicks "Open" in the tray
+//! - Re-enumerates monitors on display changes and moves / closes overlays
+//! - Applies time-of-day [`crate::schedule`] rules
+//! - Dims the overlays while the user is [`crate::idle`]
+//! - Suspends, re-tints or forces on the overlays per foreground
+//!   application ([`crate::apps`])
+//! - Pauses overlays under [`crate::fullscreen`] applications
+//! - Accounts each monitor's protected and unprotected time in the
+//!   [`crate::stats`] ledger
+//! - Optionally samples the screen to suggest regions over static content
+//!   ([`crate::heatmap`])
+//! - Persists opacity, selection, per-monitor settings and the active flag via
+//!   [`crate::settings`]
 //! - Never uses GPUI
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::io::{BufReader, BufWriter};
-use std::net::{TcpListener, TcpStream};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::ipc::{self, DAEMON_PORT, DaemonMsg, DaemonState, UiMsg};
-use crate::monitor::{MonitorInfo, enumerate_monitors};
-use crate::overlay::{OverlayManager, register_overlay_class};
+use crate::apps::{AppAction, AppRule, AppWatcher, SystemForeground};
+use crate::fullscreen::{FullscreenDetector, SystemFullscreen};
+use crate::heatmap::{self, ContentAnalyzer, SAMPLE_INTERVAL, SystemCapture};
+use crate::idle::{IdleDetector, IdleSettings, SystemInput};
+use crate::ipc::{
+    self, CAPABILITIES, ClientKind, Connection, DaemonFrame, DaemonMsg, DaemonState, ErrorCode,
+    Listener, PROTOCOL_VERSION, Request, UiMsg,
+};
+use crate::monitor::{
+    MonitorId, MonitorInfo, diff_monitors, enumerate_monitors, spawn_display_watcher,
+};
+use crate::overlay::animator::MAX_FADE_MS;
+use crate::overlay::region::reserved_strips;
+use crate::overlay::{MonitorSettings, OverlayManager, register_overlay_class};
+use crate::schedule::{ScheduleAction, ScheduleRule, Scheduler, SystemClock};
+use crate::settings::{self, Settings};
+use crate::stats::{self, Exposure, ExposureTracker};
 use crate::tray::{TrayEvent, spawn_tray};
 
 // ── Internal state ────────────────────────────────────────────────────────────
@@ -25,9 +53,44 @@ use crate::tray::{TrayEvent, spawn_tray};
 /// Internal daemon state (not serialized — used inside the daemon process only).
 struct CoreState {
     monitors: Vec<MonitorInfo>,
-    selected: Vec<bool>,
+    /// Ids of the selected monitors.  May contain monitors that are not
+    /// currently connected, so their selection survives a re-plug.
+    selected: BTreeSet<MonitorId>,
+    /// Global opacity, used by every monitor without its own override.
     opacity: u8,
+    /// Per-monitor overlay behaviour.  Monitors without an entry use
+    /// [`MonitorSettings::default`].
+    monitor_settings: BTreeMap<MonitorId, MonitorSettings>,
     overlays_active: bool,
+    /// Time-of-day rules, in priority order.
+    schedules: Vec<ScheduleRule>,
+    /// Monitors the open schedule windows protect, with their opacity.
+    /// Like `app_action`, an override that leaves `selected`,
+    /// `overlays_active` and the opacities untouched.
+    scheduled: BTreeMap<MonitorId, u8>,
+    /// Idle dimming thresholds.
+    idle: IdleSettings,
+    /// Whether the overlays are currently dimmed for idleness.
+    user_idle: bool,
+    /// Per-application rules, in priority order.
+    app_rules: Vec<AppRule>,
+    /// Action of the rule matching the foreground window.  Overrides
+    /// `overlays_active` and the opacities without changing them.
+    app_action: Option<AppAction>,
+    /// Pause a monitor's overlays while a fullscreen application covers it.
+    pause_on_fullscreen: bool,
+    /// Monitors currently covered by a fullscreen application.  They keep
+    /// their selection but get no overlays.
+    paused: BTreeSet<MonitorId>,
+    /// Sample the screen for static content.
+    analyze_content: bool,
+    /// Set whenever a persisted field changes; the main loop flushes the
+    /// settings file and clears it.  Debounces slider drags, which send many
+    /// `SetOpacity` commands per second.
+    dirty: bool,
+    /// The settings file was written by a newer version.  Changes then stay
+    /// in memory and the file is never saved over.
+    read_only: bool,
 }
 
 impl CoreState {
@@ -37,12 +100,529 @@ impl CoreState {
             monitors: self.monitors.clone(),
             selected: self.selected.clone(),
             opacity: self.opacity,
+            monitor_settings: self.monitor_settings.clone(),
+            overlays_active: self.protection_on(),
+            overlay_alive: mgr.alive_monitors(),
+            effective_opacity: self
+                .monitors
+                .iter()
+                .map(|m| (m.id.clone(), self.effective_opacity(&m.id)))
+                .collect(),
+            schedules: self.schedules.clone(),
+            scheduled: self.scheduled.clone(),
+            idle: self.idle.clone(),
+            user_idle: self.user_idle,
+            app_rules: self.app_rules.clone(),
+            app_action: self.app_action,
+            pause_on_fullscreen: self.pause_on_fullscreen,
+            paused: self.paused.clone(),
+            analyze_content: self.analyze_content,
+        }
+    }
+
+    /// Whether at least one *connected* monitor is selected.
+    fn any_selected(&self) -> bool {
+        self.monitors.iter().any(|m| self.selected.contains(&m.id))
+    }
+
+    /// Whether protection is on, switched on by hand or by a schedule.
+    fn protection_on(&self) -> bool {
+        self.overlays_active || !self.scheduled.is_empty()
+    }
+
+    /// Whether overlays should be on screen: protection is on, unless an
+    /// application rule suspends or forces them.
+    fn overlays_wanted(&self) -> bool {
+        match self.app_action {
+            Some(AppAction::Suspend) => false,
+            Some(AppAction::ForceOn) => self.any_selected() || !self.scheduled.is_empty(),
+            _ => self.protection_on(),
+        }
+    }
+
+    /// Monitors that should have overlays: the selection while protection
+    /// is on by hand, plus those of open schedule windows, minus paused
+    /// ones.
+    fn covered(&self) -> BTreeSet<MonitorId> {
+        if !self.overlays_wanted() {
+            return BTreeSet::new();
+        }
+        let manual = self.overlays_active || self.app_action == Some(AppAction::ForceOn);
+        let mut ids = if manual {
+            self.selected.clone()
+        } else {
+            BTreeSet::new()
+        };
+        ids.extend(self.scheduled.keys().cloned());
+        ids.difference(&self.paused).cloned().collect()
+    }
+
+    /// Opacity the overlays on `id` should show, honouring an application
+    /// rule's opacity and then a schedule's.
+    fn opacity_for(&self, id: &MonitorId) -> u8 {
+        match (self.app_action, self.scheduled.get(id)) {
+            (Some(AppAction::Opacity(opacity)), _) => opacity,
+            (_, Some(&opacity)) => opacity,
+            _ => self.settings_for(id).opacity_or(self.opacity),
+        }
+    }
+
+    /// Opacity the overlays on `id` show while not hovered: the idle
+    /// opacity while the user is idle, else [`opacity_for`](Self::opacity_for).
+    fn effective_opacity(&self, id: &MonitorId) -> u8 {
+        if self.user_idle {
+            self.idle.opacity
+        } else {
+            self.opacity_for(id)
+        }
+    }
+
+    /// Whether `id` refers to a currently connected monitor.
+    fn is_connected(&self, id: &MonitorId) -> bool {
+        self.monitors.iter().any(|m| &m.id == id)
+    }
+
+    /// What each connected monitor shows right now, for the exposure
+    /// ledger.  `shown` is the opacity the overlay windows of each monitor
+    /// that has any currently show (see
+    /// [`OverlayManager::shown_opacities`]), so hover fades, idle dimming
+    /// and fades in and out are counted as the panel saw them.
+    ///
+    /// A monitor counts as protected even if its overlays only cover some
+    /// regions of it.
+    fn exposures(&self, shown: &BTreeMap<MonitorId, u8>) -> Vec<(MonitorId, Exposure)> {
+        let wanted = self.overlays_wanted();
+        self.monitors
+            .iter()
+            .map(|mon| {
+                let shown = shown.get(&mon.id).copied().filter(|_| wanted);
+                (mon.id.clone(), Exposure::from_shown(shown))
+            })
+            .collect()
+    }
+
+    /// Effective settings for one monitor.
+    fn settings_for(&self, id: &MonitorId) -> MonitorSettings {
+        self.monitor_settings.get(id).cloned().unwrap_or_default()
+    }
+
+    /// Build the document written to the settings file.
+    fn to_settings(&self) -> Settings {
+        Settings {
+            opacity: self.opacity,
+            selected: self.selected.clone(),
+            monitor_settings: self.monitor_settings.clone(),
             overlays_active: self.overlays_active,
-            overlay_alive: mgr.states.iter().map(|s| s.hwnd.is_some()).collect(),
+            schedules: self.schedules.clone(),
+            idle: self.idle.clone(),
+            app_rules: self.app_rules.clone(),
+            pause_on_fullscreen: self.pause_on_fullscreen,
+            analyze_content: self.analyze_content,
+            ..Settings::default()
+        }
+    }
+}
+
+// ── Shared handles ────────────────────────────────────────────────────────────
+
+/// Everything the daemon's threads share.  Cheap to clone (all `Arc`s).
+#[derive(Clone)]
+struct Shared {
+    state: Arc<Mutex<CoreState>>,
+    mgr: Arc<Mutex<OverlayManager>>,
+    /// True when overlays are currently active.  Passed to the tray so the
+    /// menu label stays in sync.
+    active_flag: Arc<AtomicBool>,
+    hub: Arc<Hub>,
+    /// Exposure ledger; never locked together with `state` or `mgr`.
+    stats: Arc<Mutex<ExposureTracker<SystemClock>>>,
+    /// Static-content heatmaps; never locked together with `state` or `mgr`.
+    analyzer: Arc<Mutex<ContentAnalyzer>>,
+}
+
+impl Shared {
+    /// Snapshot the current state for a reply or event.
+    fn snapshot(&self) -> DaemonState {
+        let s = self.state.lock().unwrap();
+        let m = self.mgr.lock().unwrap();
+        s.to_daemon_state(&m)
+    }
+
+    /// Push the current state to every subscribed client.
+    fn publish_state(&self) {
+        self.publish_state_except(None);
+    }
+
+    /// Push the current state to every subscribed client but `skip`, which
+    /// already has it in the reply to its command.
+    fn publish_state_except(&self, skip: Option<u64>) {
+        if self.hub.has_subscribers() {
+            self.hub
+                .publish_except(DaemonMsg::StateChanged(self.snapshot()), skip);
+        }
+    }
+}
+
+// ── Event hub ─────────────────────────────────────────────────────────────────
+
+/// Connections that asked for push events via [`UiMsg::Subscribe`], and the
+/// ones that introduced themselves as UI windows.
+///
+/// Each subscriber entry is the sending half of that connection's outgoing
+/// frame queue; the connection's writer thread is the only one touching its
+/// socket.
+#[derive(Default)]
+struct Hub {
+    subscribers: Mutex<Vec<(u64, mpsc::Sender<DaemonFrame>)>>,
+    /// Connections whose [`UiMsg::Hello`] declared [`ClientKind::Ui`].
+    ui_sessions: Mutex<BTreeSet<u64>>,
+}
+
+impl Hub {
+    fn subscribe(&self, conn: u64, tx: mpsc::Sender<DaemonFrame>) {
+        let mut subs = self.subscribers.lock().unwrap();
+        if !subs.iter().any(|(c, _)| *c == conn) {
+            subs.push((conn, tx));
+        }
+    }
+
+    fn open_ui(&self, conn: u64) {
+        self.ui_sessions.lock().unwrap().insert(conn);
+    }
+
+    /// Forget a connection that went away.
+    fn disconnect(&self, conn: u64) {
+        self.subscribers.lock().unwrap().retain(|(c, _)| *c != conn);
+        self.ui_sessions.lock().unwrap().remove(&conn);
+    }
+
+    /// Whether any client is subscribed.
+    fn has_subscribers(&self) -> bool {
+        !self.subscribers.lock().unwrap().is_empty()
+    }
+
+    /// Whether a UI window is connected.
+    fn has_ui(&self) -> bool {
+        !self.ui_sessions.lock().unwrap().is_empty()
+    }
+
+    /// Queue `msg` as an event on every subscribed connection, dropping
+    /// connections whose writer has gone away.
+    fn publish(&self, msg: DaemonMsg) {
+        self.publish_except(msg, None);
+    }
+
+    /// [`publish`](Self::publish) to every subscribed connection but `skip`.
+    fn publish_except(&self, msg: DaemonMsg, skip: Option<u64>) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|(c, tx)| Some(*c) == skip || tx.send(DaemonFrame::Event(msg.clone())).is_ok());
+    }
+}
+
+// ── Settings & overlays ───────────────────────────────────────────────────────
+
+/// Write the settings file if anything changed since the last flush.
+fn flush_settings(state: &Mutex<CoreState>) {
+    let snapshot = {
+        let mut s = state.lock().unwrap();
+        if !s.dirty || s.read_only {
+            return;
         }
+        s.dirty = false;
+        s.to_settings()
+    };
+    if let Err(e) = settings::save(&snapshot) {
+        eprintln!("[daemon] Failed to save settings: {:?}", e);
     }
 }
 
+/// Charge the time since the last tick to what each monitor showed, and
+/// write the ledger out when a flush is due.
+fn record_exposure(sh: &Shared) {
+    let samples = {
+        let s = sh.state.lock().unwrap();
+        let shown = sh.mgr.lock().unwrap().shown_opacities();
+        s.exposures(&shown)
+    };
+    let due = {
+        let mut tracker = sh.stats.lock().unwrap();
+        tracker.tick(samples);
+        tracker.due()
+    };
+    let Some(ledger) = due else {
+        return;
+    };
+    if let Err(e) = stats::save(&ledger) {
+        eprintln!("[daemon] Failed to save statistics: {:?}", e);
+    }
+}
+
+/// Sample the screen for static content every [`SAMPLE_INTERVAL`] while
+/// content analysis is on, and write the heatmaps out when they are due.
+///
+/// Capturing blocks for a while, so it runs on a thread of its own and
+/// without the analyzer locked; only the finished frames are recorded.
+fn spawn_content_analysis(sh: Shared) {
+    thread::spawn(move || {
+        loop {
+            thread::sleep(SAMPLE_INTERVAL);
+            let (monitors, analyze) = {
+                let s = sh.state.lock().unwrap();
+                (s.monitors.clone(), s.analyze_content)
+            };
+            if analyze {
+                let frames = heatmap::capture_all(&SystemCapture, &monitors);
+                sh.analyzer.lock().unwrap().record(frames, Instant::now());
+            }
+            let due = sh.analyzer.lock().unwrap().due(Instant::now());
+            if let Some(heatmaps) = due
+                && let Err(e) = heatmap::save(&heatmaps)
+            {
+                eprintln!("[daemon] Failed to save heatmaps: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Spawn overlays on every [covered](CoreState::covered), connected monitor
+/// that lacks one.
+///
+/// The state lock is held only long enough to snapshot what
+/// [`OverlayManager::activate`] needs, since window creation blocks.
+fn spawn_overlays(state: &Mutex<CoreState>, mgr: &Mutex<OverlayManager>) {
+    let (monitors, selected, opacity, monitor_settings, idle_opacity) = {
+        let s = state.lock().unwrap();
+        let mut monitor_settings = s.monitor_settings.clone();
+        // An application rule's or a schedule's opacity stands in for the
+        // monitor's own.
+        for mon in &s.monitors {
+            let opacity = s.opacity_for(&mon.id);
+            if opacity != s.settings_for(&mon.id).opacity_or(s.opacity) {
+                monitor_settings.entry(mon.id.clone()).or_default().opacity = Some(opacity);
+            }
+        }
+        (
+            s.monitors.clone(),
+            s.covered(),
+            s.opacity,
+            monitor_settings,
+            s.user_idle.then_some(s.idle.opacity),
+        )
+    };
+
+    // Dummy hwnd channel — activate() blocks until all HWNDs are registered
+    // into mgr.states directly.
+    let (dummy_tx, _dummy_rx) = mpsc::channel::<(usize, usize)>();
+    let mut m = mgr.lock().unwrap();
+    m.activate(&monitors, &selected, opacity, &monitor_settings, &dummy_tx);
+    // New overlays join an ongoing idle period.
+    if idle_opacity.is_some() {
+        m.set_idle(idle_opacity);
+    }
+}
+
+/// Enable or disable overlay protection.
+///
+/// Enabling is a no-op unless at least one connected monitor is selected.
+/// Disabling also lifts the override of any open schedule window until its
+/// next boundary.  Returns `true` if protection actually changed.
+fn set_active(sh: &Shared, active: bool) -> bool {
+    let mut s = sh.state.lock().unwrap();
+    if s.protection_on() == active && s.overlays_active == active {
+        return false;
+    }
+    if active && !s.any_selected() {
+        return false;
+    }
+
+    if !active {
+        s.scheduled.clear();
+    }
+    s.overlays_active = active;
+    s.dirty = true;
+    drop(s); // release before locking mgr
+    // An application rule may keep the overlays suspended or forced on.
+    sync_overlays(sh);
+    true
+}
+
+/// Make `ids` the selection among the connected monitors, keeping the
+/// selection of disconnected ones.
+///
+/// Works while protection is on: overlays are spawned on newly selected
+/// monitors before those on deselected monitors close, so no monitor that
+/// stays selected is ever left uncovered.
+fn set_selection(sh: &Shared, ids: &BTreeSet<MonitorId>) -> Result<(), String> {
+    let deselected: Vec<MonitorId> = {
+        let mut s = sh.state.lock().unwrap();
+        if let Some(id) = ids.iter().find(|id| !s.is_connected(id)) {
+            return Err(format!("unknown monitor '{}'", id));
+        }
+        if ids.is_empty() && s.overlays_active {
+            return Err("protection is active; select at least one monitor".into());
+        }
+        let deselected = s
+            .selected
+            .iter()
+            .filter(|id| s.is_connected(id) && !ids.contains(*id))
+            .cloned()
+            .collect();
+        let disconnected: Vec<MonitorId> = s
+            .selected
+            .iter()
+            .filter(|id| !s.is_connected(id))
+            .cloned()
+            .collect();
+        s.selected = ids.iter().cloned().chain(disconnected).collect();
+        s.dirty = true;
+        deselected
+    };
+    if sh.state.lock().unwrap().overlays_wanted() {
+        spawn_overlays(&sh.state, &sh.mgr);
+    }
+    // A monitor a schedule still protects keeps its overlay.
+    let covered = sh.state.lock().unwrap().covered();
+    let mut m = sh.mgr.lock().unwrap();
+    for id in deselected.iter().filter(|id| !covered.contains(*id)) {
+        m.close(id);
+    }
+    Ok(())
+}
+
+/// Spawn or close overlays so they match [`CoreState::covered`], and
+/// publish whether protection is on to the tray.
+fn sync_overlays(sh: &Shared) {
+    let (covered, on) = {
+        let s = sh.state.lock().unwrap();
+        (s.covered(), s.protection_on())
+    };
+    sh.active_flag.store(on, Ordering::Relaxed);
+    if covered.is_empty() {
+        sh.mgr.lock().unwrap().deactivate();
+        return;
+    }
+    // New overlays first, so no monitor that stays covered is left bare.
+    spawn_overlays(&sh.state, &sh.mgr);
+    let mut m = sh.mgr.lock().unwrap();
+    for id in m.alive_monitors().difference(&covered) {
+        m.close(id);
+    }
+}
+
+/// Push the opacity each monitor should show to its overlays, after an
+/// override began or ended.
+fn refresh_opacities(sh: &Shared) {
+    let s = sh.state.lock().unwrap();
+    let m = sh.mgr.lock().unwrap();
+    for mon in &s.monitors {
+        m.update_opacity(&mon.id, s.opacity_for(&mon.id));
+    }
+}
+
+/// Carry out a scheduler decision as a transient override, the way an
+/// application rule's action is: the rule's monitors are protected at its
+/// opacity without touching the selection, the per-monitor settings or the
+/// active flag, so everything reverts when the last window closes and
+/// protection switched on by hand stays on.
+fn apply_schedule(sh: &Shared, action: ScheduleAction) {
+    let target = match action {
+        ScheduleAction::Deactivate => {
+            println!("[daemon] Schedule ended");
+            BTreeMap::new()
+        }
+        ScheduleAction::Apply(target) => {
+            println!("[daemon] Schedule applies to {} monitor(s)", target.len());
+            target
+        }
+    };
+    sh.state.lock().unwrap().scheduled = target;
+    sync_overlays(sh);
+    refresh_opacities(sh);
+    sh.publish_state();
+}
+
+/// Ramp the overlays to the idle opacity when the user goes idle, and back
+/// when they return.
+fn set_user_idle(sh: &Shared, idle: bool) {
+    let mut s = sh.state.lock().unwrap();
+    s.user_idle = idle;
+    let opacity = idle.then_some(s.idle.opacity);
+    sh.mgr.lock().unwrap().set_idle(opacity);
+    drop(s);
+    sh.publish_state();
+}
+
+/// Carry out an application rule's action (`None` when no rule matches any
+/// more): suspend, force or re-tint the overlays without touching the
+/// persisted state, so everything reverts once the rule stops matching.
+fn apply_app_action(sh: &Shared, action: Option<AppAction>) {
+    match action {
+        Some(action) => println!("[daemon] Application rule applies: {:?}", action),
+        None => println!("[daemon] Application rule ended"),
+    }
+    sh.state.lock().unwrap().app_action = action;
+    sync_overlays(sh);
+    // Push the opacity the rule calls for, or restore the normal one.
+    refresh_opacities(sh);
+    sh.publish_state();
+}
+
+/// Pause the overlays on monitors a fullscreen application just covered and
+/// restore them on monitors it just left.
+fn set_paused(sh: &Shared, paused: BTreeSet<MonitorId>) {
+    let mut s = sh.state.lock().unwrap();
+    let covered: Vec<MonitorId> = paused.difference(&s.paused).cloned().collect();
+    let uncovered = s.paused.difference(&paused).count();
+    for id in &covered {
+        println!(
+            "[daemon] Fullscreen application on {}; pausing its overlay",
+            id
+        );
+    }
+    s.paused = paused;
+    let wanted = s.overlays_wanted();
+    drop(s); // release before locking mgr
+
+    {
+        let mut m = sh.mgr.lock().unwrap();
+        for id in &covered {
+            m.close(id);
+        }
+    }
+    if wanted && uncovered > 0 {
+        spawn_overlays(&sh.state, &sh.mgr);
+    }
+    sh.publish_state();
+}
+
+/// Replace a monitor's overlays after its region layout, pixel orbit or fill
+/// changed.
+fn rebuild_overlays(sh: &Shared, id: &MonitorId) {
+    let active = sh.state.lock().unwrap().overlays_wanted();
+    if !active {
+        return;
+    }
+    sh.mgr.lock().unwrap().close(id);
+    spawn_overlays(&sh.state, &sh.mgr);
+}
+
+/// Close all overlays, save settings and exit the process.
+fn shutdown(sh: &Shared) -> ! {
+    flush_settings(&sh.state);
+    let ledger = sh.stats.lock().unwrap().flush();
+    if let Err(e) = stats::save(&ledger) {
+        eprintln!("[daemon] Failed to save statistics: {:?}", e);
+    }
+    let heatmaps = sh.analyzer.lock().unwrap().flush(Instant::now());
+    if let Err(e) = heatmap::save(&heatmaps) {
+        eprintln!("[daemon] Failed to save heatmaps: {:?}", e);
+    }
+    sh.mgr.lock().unwrap().deactivate();
+    std::process::exit(0);
+}
+
 // ── Entry point ───────────────────────────────────────────────────────────────
 
 /// Entry point for daemon mode.
@@ -55,81 +635,179 @@ pub fn run_daemon() {
         }
     }
 
+    // Claim the IPC endpoint before anything else: a second launcher that
+    // lost the race for it exits here, before it touches the token, the
+    // overlays or the tray.  The endpoint admits the current user only, and
+    // clients that connect before the server thread starts wait in its
+    // backlog.
+    let (listener, endpoint) = match ipc::bind_daemon() {
+        Ok((listener, endpoint)) => {
+            println!("[daemon] Listening on {}", endpoint);
+            (listener, endpoint)
+        }
+        Err(e) => {
+            eprintln!("[daemon] Cannot open IPC endpoint: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+    // Only clients that can read the per-session token may connect.  The
+    // endpoint is recorded with it, so clients present the token nowhere
+    // else.
+    let token: Arc<str> = match ipc::auth::create_token(&endpoint) {
+        Ok(t) => t.into(),
+        Err(e) => {
+            eprintln!("[daemon] Cannot write IPC token: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+
     let monitors = enumerate_monitors();
     println!("[daemon] {} monitor(s) found", monitors.len());
-    let n = monitors.len();
 
-    let state = Arc::new(Mutex::new(CoreState {
-        monitors,
-        selected: vec![false; n],
-        opacity: 50,
-        overlays_active: false,
-    }));
+    // Restore the previous session's settings.
+    let saved = settings::load();
+    let read_only = saved.is_from_newer_version();
+    if read_only {
+        println!("[daemon] Settings changes will not be saved this session");
+    }
+    let mut selected = saved.selected;
 
-    let overlay_mgr = Arc::new(Mutex::new(OverlayManager::new(n)));
+    // A settings file from before monitor ids existed stores the selection
+    // by enumeration index; resolve it against today's order once and save
+    // it back in id form.
+    let upgraded = selected.is_empty() && !saved.legacy_selected.is_empty();
+    if upgraded {
+        selected = monitors
+            .iter()
+            .zip(&saved.legacy_selected)
+            .filter(|(_, sel)| **sel)
+            .map(|(m, _)| m.id.clone())
+            .collect();
+    }
 
-    // Track whether a UI window is currently open (to avoid spawning duplicates).
-    let ui_open = Arc::new(AtomicBool::new(false));
+    let sh = Shared {
+        state: Arc::new(Mutex::new(CoreState {
+            monitors,
+            selected,
+            opacity: saved.opacity,
+            monitor_settings: saved.monitor_settings,
+            overlays_active: false,
+            schedules: saved.schedules,
+            scheduled: BTreeMap::new(),
+            idle: saved.idle,
+            user_idle: false,
+            app_rules: saved.app_rules,
+            app_action: None,
+            pause_on_fullscreen: saved.pause_on_fullscreen,
+            paused: BTreeSet::new(),
+            analyze_content: saved.analyze_content,
+            dirty: upgraded,
+            read_only,
+        })),
+        mgr: Arc::new(Mutex::new(OverlayManager::new())),
+        active_flag: Arc::new(AtomicBool::new(false)),
+        hub: Arc::new(Hub::default()),
+        stats: Arc::new(Mutex::new(ExposureTracker::new(SystemClock, stats::load()))),
+        analyzer: Arc::new(Mutex::new(ContentAnalyzer::new(heatmap::load()))),
+    };
 
-    // Shared flag: true when overlays are currently active.
-    // Passed to the tray so the menu label stays in sync.
-    let active_flag = Arc::new(AtomicBool::new(false));
+    // Re-enable protection if it was on when the daemon last exited.
+    if saved.overlays_active {
+        set_active(&sh, true);
+    }
 
     // Start the tray icon thread.
     let (tray_tx, tray_rx) = mpsc::channel::<TrayEvent>();
-    spawn_tray(tray_tx, active_flag.clone());
+    spawn_tray(tray_tx, sh.active_flag.clone());
+
+    // Sample the screen for static content while analysis is on.
+    spawn_content_analysis(sh.clone());
 
-    // Start the TCP IPC server on a background thread.
+    // Watch for monitors being attached, detached or rearranged.
+    let (display_tx, display_rx) = mpsc::channel::<()>();
+    spawn_display_watcher(display_tx);
+
+    // Serve the endpoint bound above on a background thread.
     {
-        let state = state.clone();
-        let mgr = overlay_mgr.clone();
-        let ui_open = ui_open.clone();
-        let active_flag = active_flag.clone();
-        thread::spawn(move || run_tcp_server(state, mgr, ui_open, active_flag));
+        let sh = sh.clone();
+        thread::spawn(move || run_ipc_server(listener, token, sh));
     }
 
     // Spawn the initial UI window so the user sees it on first launch.
     spawn_ui_process();
 
-    // Main daemon loop: poll tray events every 100 ms.
+    // Main daemon loop: poll tray / display events, reap dead overlays,
+    // account exposure and flush settings every 100 ms.
+    let mut scheduler = Scheduler::new(SystemClock);
+    let mut idle = IdleDetector::new(SystemInput);
+    let mut apps = AppWatcher::new(SystemForeground);
+    let mut fullscreen = FullscreenDetector::new(SystemFullscreen);
     loop {
         thread::sleep(Duration::from_millis(100));
+        flush_settings(&sh.state);
+        record_exposure(&sh);
+
+        let rules = sh.state.lock().unwrap().schedules.clone();
+        if let Some(action) = scheduler.tick(&rules) {
+            apply_schedule(&sh, action);
+        }
+
+        let idle_settings = sh.state.lock().unwrap().idle.clone();
+        if let Some(now_idle) = idle.tick(&idle_settings) {
+            set_user_idle(&sh, now_idle);
+        }
+
+        let app_rules = sh.state.lock().unwrap().app_rules.clone();
+        if let Some(action) = apps.tick(&app_rules) {
+            apply_app_action(&sh, action);
+        }
+
+        let (monitors, pause) = {
+            let s = sh.state.lock().unwrap();
+            (s.monitors.clone(), s.pause_on_fullscreen)
+        };
+        if let Some(paused) = fullscreen.tick(&monitors, pause) {
+            set_paused(&sh, paused);
+        }
+
+        // Display changes arrive in bursts; re-enumerate once per tick.
+        if display_rx.try_iter().count() > 0 {
+            refresh_topology(&sh);
+        }
+
+        let dead = sh.mgr.lock().unwrap().reap_dead();
+        if !dead.is_empty() {
+            for key in dead {
+                match key.region {
+                    None => {
+                        eprintln!("[daemon] Overlay on {} exited unexpectedly", key.monitor);
+                        sh.hub.publish(DaemonMsg::OverlayDied(key.monitor));
+                    }
+                    Some(region) => {
+                        eprintln!(
+                            "[daemon] Overlay '{}' on {} exited unexpectedly",
+                            region, key.monitor
+                        );
+                        sh.hub.publish(DaemonMsg::RegionDied(key.monitor, region));
+                    }
+                }
+            }
+            sh.publish_state();
+        }
 
         while let Ok(ev) = tray_rx.try_recv() {
             match ev {
                 TrayEvent::Open => {
                     // Only spawn a new UI if one isn't already open.
-                    if !ui_open.load(Ordering::Relaxed) {
+                    if !sh.hub.has_ui() {
                         spawn_ui_process();
                     }
                 }
-                TrayEvent::Quit => {
-                    overlay_mgr.lock().unwrap().deactivate();
-                    std::process::exit(0);
-                }
+                TrayEvent::Quit => shutdown(&sh),
                 TrayEvent::Toggle => {
-                    let mut s = state.lock().unwrap();
-                    if s.overlays_active {
-                        // ── Disable ──────────────────────────────────────
-                        s.overlays_active = false;
-                        drop(s);
-                        overlay_mgr.lock().unwrap().deactivate();
-                        active_flag.store(false, Ordering::Relaxed);
-                    } else {
-                        // ── Enable (only if at least one monitor selected) ─
-                        if s.selected.iter().any(|&sel| sel) {
-                            let monitors = s.monitors.clone();
-                            let selected = s.selected.clone();
-                            let opacity = s.opacity;
-                            drop(s);
-                            let (dummy_tx, _dummy_rx) = mpsc::channel::<(usize, usize)>();
-                            overlay_mgr
-                                .lock()
-                                .unwrap()
-                                .activate(&monitors, &selected, opacity, &dummy_tx);
-                            state.lock().unwrap().overlays_active = true;
-                            active_flag.store(true, Ordering::Relaxed);
-                        }
+                    let active = sh.state.lock().unwrap().protection_on();
+                    if set_active(&sh, !active) {
+                        sh.publish_state();
                     }
                 }
             }
@@ -139,6 +817,77 @@ pub fn run_daemon() {
 
 // ── Helpers ───────────────────────────────────────────────────────────────────
 
+/// Whether `mon` reserves any area for the taskbar or app bars.  An
+/// auto-hiding taskbar reserves none, leaving "reserved areas only" mode
+/// nothing to cover.
+fn has_reserved_area(mon: &MonitorInfo) -> bool {
+    let monitor = (mon.x, mon.y, mon.width, mon.height);
+    let work = (mon.work_x, mon.work_y, mon.work_width, mon.work_height);
+    !reserved_strips(monitor, work).is_empty()
+}
+
+/// Re-enumerate monitors after a display change and bring the overlays in
+/// line with the new layout: overlays on vanished monitors are closed, those
+/// on moved / resized monitors follow them, and newly attached monitors that
+/// were previously selected get an overlay if protection is on.
+///
+/// Subscribed clients receive a `TopologyChanged` event followed by the new
+/// state.
+fn refresh_topology(sh: &Shared) {
+    let monitors = enumerate_monitors();
+
+    let mut s = sh.state.lock().unwrap();
+    let diff = diff_monitors(&s.monitors, &monitors);
+    s.monitors = monitors;
+    if diff.is_empty() {
+        return;
+    }
+    println!(
+        "[daemon] Display topology changed: {} added, {} removed, {} moved",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.moved.len()
+    );
+    let active = s.overlays_wanted();
+    let monitors = s.monitors.clone();
+    let moved: Vec<_> = diff
+        .moved
+        .iter()
+        .map(|mon| (mon, s.settings_for(&mon.id)))
+        .collect();
+    for (mon, ms) in &moved {
+        if ms.reserved_only && !has_reserved_area(mon) {
+            eprintln!(
+                "[daemon] {} no longer reserves a taskbar area (auto-hide?); \
+                 its reserved-areas-only overlay covers nothing",
+                mon.id
+            );
+        }
+    }
+    drop(s); // release before locking mgr
+
+    let mut rebuild = false;
+    {
+        let mut m = sh.mgr.lock().unwrap();
+        for id in &diff.removed {
+            m.close(id);
+        }
+        for (mon, ms) in &moved {
+            // E.g. the taskbar moved to another edge: different strips.
+            if !m.reposition(mon, ms) {
+                m.close(&mon.id);
+                rebuild = true;
+            }
+        }
+    }
+    if active && (rebuild || !diff.added.is_empty()) {
+        spawn_overlays(&sh.state, &sh.mgr);
+    }
+
+    sh.hub.publish(DaemonMsg::TopologyChanged(monitors));
+    sh.publish_state();
+}
+
 /// Spawn a new UI process (`oled-care.exe --ui`).
 fn spawn_ui_process() {
     if let Ok(exe) = std::env::current_exe() {
@@ -149,146 +898,532 @@ fn spawn_ui_process() {
     }
 }
 
-// ── TCP server ────────────────────────────────────────────────────────────────
+// ── IPC server ────────────────────────────────────────────────────────────────
 
-/// TCP server loop: accept one connection at a time from the UI.
-fn run_tcp_server(
-    state: Arc<Mutex<CoreState>>,
-    mgr: Arc<Mutex<OverlayManager>>,
-    ui_open: Arc<AtomicBool>,
-    active_flag: Arc<AtomicBool>,
-) {
-    let addr = format!("127.0.0.1:{}", DAEMON_PORT);
-    let listener = match TcpListener::bind(&addr) {
-        Ok(l) => l,
-        Err(e) => {
-            eprintln!("[daemon] Cannot bind IPC port {}: {:?}", DAEMON_PORT, e);
-            std::process::exit(1);
-        }
-    };
-    println!("[daemon] Listening on {}", addr);
-
-    for stream in listener.incoming() {
-        match stream {
+/// Server loop: accept connections and handle each on its own thread.
+fn run_ipc_server(listener: Box<dyn Listener>, token: Arc<str>, sh: Shared) {
+    let next_conn = AtomicU64::new(1);
+    loop {
+        match listener.accept() {
             Ok(s) => {
-                let state = state.clone();
-                let mgr = mgr.clone();
-                let ui_open = ui_open.clone();
-                let active_flag = active_flag.clone();
-                thread::spawn(move || {
-                    ui_open.store(true, Ordering::Relaxed);
-                    handle_client(s, state, mgr, active_flag);
-                    ui_open.store(false, Ordering::Relaxed);
-                });
+                let sh = sh.clone();
+                let conn = next_conn.fetch_add(1, Ordering::Relaxed);
+                let token = token.clone();
+                thread::spawn(move || handle_client(s, conn, &token, sh));
+            }
+            Err(e) => {
+                eprintln!("[daemon] Accept error: {:?}", e);
+                // Avoid spinning if the endpoint is persistently broken.
+                thread::sleep(Duration::from_millis(100));
             }
-            Err(e) => eprintln!("[daemon] Accept error: {:?}", e),
         }
     }
 }
 
 // ── Client handler ────────────────────────────────────────────────────────────
 
-/// Handle one UI client connection in a loop until it disconnects.
-fn handle_client(
-    stream: TcpStream,
-    state: Arc<Mutex<CoreState>>,
-    mgr: Arc<Mutex<OverlayManager>>,
-    active_flag: Arc<AtomicBool>,
-) {
-    let _ = stream.set_nodelay(true);
-    let mut reader = BufReader::new(stream.try_clone().unwrap());
-    let mut writer = BufWriter::new(stream);
+/// Handle one client connection in a loop until it disconnects.
+///
+/// Outgoing frames (replies and, once subscribed, events) go through a
+/// per-connection queue drained by a dedicated writer thread, so a reply and
+/// an event can never be interleaved mid-frame.
+///
+/// The first request must be a [`UiMsg::Hello`] presenting `token`; anything
+/// else is answered with [`ErrorCode::Unauthorized`] and the connection is
+/// closed.
+fn handle_client(stream: Connection, conn: u64, token: &str, sh: Shared) {
+    let mut reader = match stream.try_clone() {
+        Ok(s) => BufReader::new(s),
+        Err(e) => {
+            eprintln!("[daemon] Cannot split connection {}: {:?}", conn, e);
+            return;
+        }
+    };
+
+    let (frame_tx, frame_rx) = mpsc::channel::<DaemonFrame>();
+    let writer_thread = {
+        let mut writer = BufWriter::new(stream);
+        thread::spawn(move || {
+            for frame in frame_rx {
+                if ipc::write_msg(&mut writer, &frame).is_err() {
+                    break; // client disconnected while we were writing
+                }
+            }
+        })
+    };
 
+    let mut authenticated = false;
     loop {
-        let msg: UiMsg = match ipc::read_msg(&mut reader) {
-            Ok(m) => m,
-            Err(_) => break, // UI disconnected
+        let body = match ipc::read_frame(&mut reader) {
+            Ok(b) => b,
+            Err(_) => break, // client disconnected
         };
 
-        match msg {
-            UiMsg::GetState => {
-                // Fall through to the reply-with-state at the bottom of the loop.
+        if !authenticated {
+            match ipc::decode_request(&body) {
+                Ok(Request {
+                    msg:
+                        UiMsg::Hello {
+                            token: ref t,
+                            client,
+                            ..
+                        },
+                    ..
+                }) if ipc::auth::verify(token, t) => {
+                    authenticated = true;
+                    if client == ClientKind::Ui {
+                        sh.hub.open_ui(conn);
+                    }
+                }
+                other => {
+                    let id = match other {
+                        Ok(r) => r.id,
+                        Err((id, _)) => id,
+                    };
+                    eprintln!("[daemon] Rejected unauthenticated connection {}", conn);
+                    let _ = frame_tx.send(DaemonFrame::Reply {
+                        id,
+                        msg: DaemonMsg::Error {
+                            code: ErrorCode::Unauthorized,
+                            message: "the first message must be Hello with the session token"
+                                .into(),
+                        },
+                    });
+                    break;
+                }
             }
+        }
 
-            UiMsg::ShowUi => {
-                // A second instance sent this — reply with current state and
-                // close the connection.  The main daemon loop will open a fresh
-                // UI window if none is currently alive.
-                let reply = {
-                    let s = state.lock().unwrap();
-                    let m = mgr.lock().unwrap();
-                    DaemonMsg::State(s.to_daemon_state(&m))
+        // A request we cannot decode (e.g. from a newer client) gets an error
+        // reply; the connection stays usable.
+        let (id, msg) = match ipc::decode_request(&body) {
+            Ok(Request { id, msg }) => (id, msg),
+            Err((id, message)) => {
+                let reply = DaemonFrame::Reply {
+                    id,
+                    msg: DaemonMsg::Error {
+                        code: ErrorCode::UnknownCommand,
+                        message,
+                    },
                 };
-                let _ = ipc::write_msg(&mut writer, &reply);
-                break;
+                if frame_tx.send(reply).is_err() {
+                    break;
+                }
+                continue;
             }
+        };
+
+        // ShowUi ends the connection after its reply.
+        let mut last = matches!(msg, UiMsg::ShowUi);
+        let mut changed = false;
 
-            UiMsg::SetOpacity(opacity) => {
-                let mut s = state.lock().unwrap();
-                s.opacity = opacity;
-                if s.overlays_active {
-                    mgr.lock().unwrap().update_opacity(opacity);
+        let reply = match msg {
+            UiMsg::Hello { protocol, .. } => {
+                // A client speaking another protocol learns our version and
+                // is then disconnected.
+                if protocol != PROTOCOL_VERSION {
+                    eprintln!(
+                        "[daemon] Client speaks protocol {}, expected {}",
+                        protocol, PROTOCOL_VERSION
+                    );
+                    last = true;
                 }
+                DaemonMsg::Hello {
+                    protocol: PROTOCOL_VERSION,
+                    capabilities: CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                }
+            }
+            UiMsg::ListSchedules => {
+                DaemonMsg::Schedules(sh.state.lock().unwrap().schedules.clone())
+            }
+            UiMsg::ListAppRules => DaemonMsg::AppRules(sh.state.lock().unwrap().app_rules.clone()),
+            UiMsg::GetStats => DaemonMsg::Stats(sh.stats.lock().unwrap().report()),
+            UiMsg::GetRegionSuggestions => {
+                let monitors = sh.state.lock().unwrap().monitors.clone();
+                DaemonMsg::RegionSuggestions(sh.analyzer.lock().unwrap().suggestions(&monitors))
+            }
+            msg => match handle_command(&sh, conn, msg, &frame_tx) {
+                // Reply with the full current state.
+                Ok(c) => {
+                    changed = c;
+                    DaemonMsg::State(sh.snapshot())
+                }
+                Err((code, message)) => DaemonMsg::Error { code, message },
+            },
+        };
+
+        if frame_tx
+            .send(DaemonFrame::Reply { id, msg: reply })
+            .is_err()
+        {
+            break;
+        }
+        // The sender has the new state in its reply already.
+        if changed {
+            sh.publish_state_except(Some(conn));
+        }
+        if last {
+            break;
+        }
+    }
+
+    // Dropping every sender lets the writer flush what is queued and exit.
+    sh.hub.disconnect(conn);
+    drop(frame_tx);
+    let _ = writer_thread.join();
+}
+
+/// Outcome of [`handle_command`]: whether subscribers should hear about a
+/// state change, or why the command was refused.
+type CommandResult = Result<bool, (ErrorCode, String)>;
+
+/// Refuse a command whose arguments do not fit the current state.
+fn invalid(message: impl Into<String>) -> CommandResult {
+    Err((ErrorCode::InvalidArgument, message.into()))
+}
+
+/// Change the settings of connected monitor `id` with `change`, which
+/// refuses values that do not fit by returning why, and mark them for
+/// saving.  A refused change leaves the settings untouched.
+fn update_monitor(
+    sh: &Shared,
+    id: &MonitorId,
+    change: impl FnOnce(&mut MonitorSettings) -> Result<(), String>,
+) -> Result<(), (ErrorCode, String)> {
+    let mut s = sh.state.lock().unwrap();
+    if !s.is_connected(id) {
+        return Err((
+            ErrorCode::InvalidArgument,
+            format!("unknown monitor '{}'", id),
+        ));
+    }
+    let mut ms = s.settings_for(id);
+    change(&mut ms).map_err(|e| (ErrorCode::InvalidArgument, e))?;
+    s.monitor_settings.insert(id.clone(), ms);
+    s.dirty = true;
+    Ok(())
+}
+
+/// [`update_monitor`] for a setting the overlays pick up in place, such as
+/// the hover fade.
+fn update_fade(
+    sh: &Shared,
+    id: &MonitorId,
+    change: impl FnOnce(&mut MonitorSettings) -> Result<(), String>,
+) -> CommandResult {
+    update_monitor(sh, id, change)?;
+    let s = sh.state.lock().unwrap();
+    sh.mgr.lock().unwrap().update_fade(id, &s.settings_for(id));
+    Ok(true)
+}
+
+/// [`update_monitor`] for a setting that changes which overlays a monitor
+/// has or how they are drawn, so they are rebuilt.
+fn update_layout(
+    sh: &Shared,
+    id: &MonitorId,
+    change: impl FnOnce(&mut MonitorSettings) -> Result<(), String>,
+) -> CommandResult {
+    update_monitor(sh, id, change)?;
+    rebuild_overlays(sh, id);
+    Ok(true)
+}
+
+/// Apply one client command.  Returns `Ok(true)` if the daemon state changed
+/// in a way subscribers should hear about.
+fn handle_command(
+    sh: &Shared,
+    conn: u64,
+    msg: UiMsg,
+    frame_tx: &mpsc::Sender<DaemonFrame>,
+) -> CommandResult {
+    match msg {
+        // Answered directly by handle_client.
+        UiMsg::Hello { .. } => Ok(false),
+
+        UiMsg::GetState => Ok(false),
+
+        UiMsg::Subscribe => {
+            sh.hub.subscribe(conn, frame_tx.clone());
+            Ok(false)
+        }
+
+        UiMsg::ShowUi => {
+            // A second instance sent this — open a fresh UI window unless one
+            // is already alive.
+            if !sh.hub.has_ui() {
+                spawn_ui_process();
             }
+            Ok(false)
+        }
 
-            UiMsg::ToggleMonitor(idx) => {
-                let mut s = state.lock().unwrap();
-                // Only allow toggling when overlays are not active.
-                if !s.overlays_active {
-                    if let Some(sel) = s.selected.get_mut(idx) {
-                        *sel = !*sel;
+        UiMsg::SetOpacity(opacity) => {
+            let mut s = sh.state.lock().unwrap();
+            s.opacity = opacity;
+            s.dirty = true;
+            if s.overlays_wanted() {
+                // Only monitors that follow the global opacity change.
+                let m = sh.mgr.lock().unwrap();
+                for mon in &s.monitors {
+                    if s.settings_for(&mon.id).opacity.is_none() {
+                        m.update_opacity(&mon.id, s.opacity_for(&mon.id));
                     }
                 }
             }
+            Ok(true)
+        }
 
-            UiMsg::SetActive(active) => {
-                if active {
-                    // ── Enable ──────────────────────────────────────────
-                    let s = state.lock().unwrap();
-                    if !s.overlays_active && s.selected.iter().any(|&sel| sel) {
-                        let monitors = s.monitors.clone();
-                        let selected = s.selected.clone();
-                        let opacity = s.opacity;
-                        drop(s); // release before locking mgr
-
-                        // Dummy hwnd channel — activate() blocks until all
-                        // HWNDs are registered into mgr.states directly.
-                        let (dummy_tx, _dummy_rx) = mpsc::channel::<(usize, usize)>();
-                        mgr.lock()
-                            .unwrap()
-                            .activate(&monitors, &selected, opacity, &dummy_tx);
-
-                        state.lock().unwrap().overlays_active = true;
-                        active_flag.store(true, Ordering::Relaxed);
-                    }
-                    // if already active: lock guard drops here, nothing to do
-                } else {
-                    // ── Disable ─────────────────────────────────────────
-                    // Take a fresh lock (no outer guard in scope, no deadlock).
-                    let mut s = state.lock().unwrap();
-                    if s.overlays_active {
-                        s.overlays_active = false;
-                        drop(s); // release before locking mgr
-                        mgr.lock().unwrap().deactivate();
-                        active_flag.store(false, Ordering::Relaxed);
-                    }
+        UiMsg::SetMonitorOpacity(id, opacity) => {
+            update_monitor(sh, &id, |ms| {
+                ms.opacity = opacity;
+                Ok(())
+            })?;
+            let s = sh.state.lock().unwrap();
+            sh.mgr
+                .lock()
+                .unwrap()
+                .update_opacity(&id, s.opacity_for(&id));
+            Ok(true)
+        }
+
+        UiMsg::SetFadeOnHover(id, enabled) => update_fade(sh, &id, |ms| {
+            ms.fade_on_hover = enabled;
+            Ok(())
+        }),
+
+        UiMsg::SetFadeDuration(id, fade_ms) => update_fade(sh, &id, |ms| {
+            if fade_ms > MAX_FADE_MS {
+                return Err(format!("fade duration must be at most {} ms", MAX_FADE_MS));
+            }
+            ms.fade_ms = fade_ms;
+            Ok(())
+        }),
+
+        UiMsg::SetFadeCurve(id, curve) => update_fade(sh, &id, |ms| {
+            ms.fade_curve = curve;
+            Ok(())
+        }),
+
+        UiMsg::SetHoverOpacity(id, opacity) => update_fade(sh, &id, |ms| {
+            ms.hover_opacity = opacity;
+            Ok(())
+        }),
+
+        UiMsg::SetReveal(id, reveal) => update_fade(sh, &id, |ms| {
+            if let Some(reveal) = &reveal {
+                reveal.validate()?;
+            }
+            ms.reveal = reveal;
+            Ok(())
+        }),
+
+        UiMsg::SetFocusFollow(id, enabled) => update_fade(sh, &id, |ms| {
+            ms.focus_follow = enabled;
+            Ok(())
+        }),
+
+        UiMsg::ToggleMonitor(id) => {
+            let mut s = sh.state.lock().unwrap();
+            // Only allow toggling when overlays are not active, and only
+            // for monitors that are actually connected.
+            if !s.is_connected(&id) {
+                return invalid(format!("unknown monitor '{}'", id));
+            }
+            if s.overlays_active || s.overlays_wanted() {
+                return invalid("selection is locked while protection is active");
+            }
+            if !s.selected.remove(&id) {
+                s.selected.insert(id);
+            }
+            s.dirty = true;
+            Ok(true)
+        }
+
+        UiMsg::SetSelection(ids) => {
+            if let Err(e) = set_selection(sh, &ids) {
+                return invalid(e);
+            }
+            Ok(true)
+        }
+
+        UiMsg::SetRegion(id, region) => update_layout(sh, &id, |ms| {
+            region.validate()?;
+            match ms.regions.iter_mut().find(|r| r.name == region.name) {
+                Some(existing) => *existing = region,
+                None => ms.regions.push(region),
+            }
+            Ok(())
+        }),
+
+        UiMsg::SetReservedOnly(id, enabled) => {
+            let reserved = {
+                let s = sh.state.lock().unwrap();
+                s.monitors
+                    .iter()
+                    .find(|m| m.id == id)
+                    .map(has_reserved_area)
+            };
+            if enabled && reserved == Some(false) {
+                return invalid(format!(
+                    "'{}' reserves no area for a taskbar or app bar (an auto-hiding \
+                     taskbar reserves none), so there would be nothing to cover",
+                    id
+                ));
+            }
+            update_layout(sh, &id, |ms| {
+                ms.reserved_only = enabled;
+                Ok(())
+            })
+        }
+
+        UiMsg::SetOrbit(id, orbit) => update_layout(sh, &id, |ms| {
+            if let Some(orbit) = &orbit {
+                orbit.validate()?;
+            }
+            ms.orbit = orbit;
+            Ok(())
+        }),
+
+        UiMsg::SetFill(id, fill) => update_layout(sh, &id, |ms| {
+            fill.validate()?;
+            ms.fill = fill;
+            Ok(())
+        }),
+
+        UiMsg::RemoveRegion(id, name) => {
+            {
+                let mut s = sh.state.lock().unwrap();
+                let Some(ms) = s.monitor_settings.get_mut(&id) else {
+                    return invalid(format!("monitor '{}' has no regions", id));
+                };
+                let before = ms.regions.len();
+                ms.regions.retain(|r| r.name != name);
+                if ms.regions.len() == before {
+                    return invalid(format!("monitor '{}' has no region '{}'", id, name));
                 }
+                s.dirty = true;
             }
+            rebuild_overlays(sh, &id);
+            Ok(true)
+        }
 
-            UiMsg::Quit => {
-                mgr.lock().unwrap().deactivate();
-                std::process::exit(0);
+        UiMsg::SetIdleEnabled(enabled) => {
+            let mut s = sh.state.lock().unwrap();
+            s.idle.enabled = enabled;
+            s.dirty = true;
+            // Disabling while idle is undone by the detector's next tick.
+            Ok(true)
+        }
+
+        UiMsg::SetIdleTimeout(secs) => {
+            if secs == 0 {
+                return invalid("idle timeout must be at least 1 second");
             }
+            let mut s = sh.state.lock().unwrap();
+            s.idle.timeout_secs = secs;
+            s.dirty = true;
+            Ok(true)
         }
 
-        // After every command, reply with the full current state.
-        let reply = {
-            let s = state.lock().unwrap();
-            let m = mgr.lock().unwrap();
-            DaemonMsg::State(s.to_daemon_state(&m))
-        };
-        if ipc::write_msg(&mut writer, &reply).is_err() {
-            break; // UI disconnected while we were writing
+        UiMsg::SetIdleOpacity(opacity) => {
+            let mut s = sh.state.lock().unwrap();
+            s.idle.opacity = opacity;
+            s.dirty = true;
+            if s.user_idle {
+                sh.mgr.lock().unwrap().set_idle(Some(opacity));
+            }
+            Ok(true)
+        }
+
+        UiMsg::SetPauseOnFullscreen(enabled) => {
+            let mut s = sh.state.lock().unwrap();
+            s.pause_on_fullscreen = enabled;
+            s.dirty = true;
+            // Paused monitors resume on the detector's next tick.
+            Ok(true)
         }
+
+        UiMsg::SetContentAnalysis(enabled) => {
+            {
+                let mut s = sh.state.lock().unwrap();
+                s.analyze_content = enabled;
+                s.dirty = true;
+            }
+            // Sampling starts or stops on the analysis thread's next round.
+            if !enabled {
+                sh.analyzer.lock().unwrap().reset();
+            }
+            Ok(true)
+        }
+
+        // Answered directly by handle_client.
+        UiMsg::ListSchedules => Ok(false),
+
+        // The main loop applies the change on its next scheduler tick;
+        // subscribers get the new rules in the state now.
+        UiMsg::AddSchedule(mut rule) => {
+            if let Err(e) = rule.validate() {
+                return invalid(e);
+            }
+            let mut s = sh.state.lock().unwrap();
+            rule.id = s.schedules.iter().map(|r| r.id).max().unwrap_or(0) + 1;
+            s.schedules.push(rule);
+            s.dirty = true;
+            Ok(true)
+        }
+
+        UiMsg::RemoveSchedule(id) => {
+            let mut s = sh.state.lock().unwrap();
+            let before = s.schedules.len();
+            s.schedules.retain(|r| r.id != id);
+            if s.schedules.len() == before {
+                return invalid(format!("no schedule with id {}", id));
+            }
+            s.dirty = true;
+            Ok(true)
+        }
+
+        // Answered directly by handle_client.
+        UiMsg::ListAppRules => Ok(false),
+
+        // Answered directly by handle_client.
+        UiMsg::GetStats => Ok(false),
+
+        // Answered directly by handle_client.
+        UiMsg::GetRegionSuggestions => Ok(false),
+
+        // Like schedules: the main loop re-evaluates on its next tick, and
+        // subscribers get the new rules in the state now.
+        UiMsg::AddAppRule(mut rule) => {
+            if let Err(e) = rule.validate() {
+                return invalid(e);
+            }
+            let mut s = sh.state.lock().unwrap();
+            rule.id = s.app_rules.iter().map(|r| r.id).max().unwrap_or(0) + 1;
+            s.app_rules.push(rule);
+            s.dirty = true;
+            Ok(true)
+        }
+
+        UiMsg::RemoveAppRule(id) => {
+            let mut s = sh.state.lock().unwrap();
+            let before = s.app_rules.len();
+            s.app_rules.retain(|r| r.id != id);
+            if s.app_rules.len() == before {
+                return invalid(format!("no application rule with id {}", id));
+            }
+            s.dirty = true;
+            Ok(true)
+        }
+
+        UiMsg::SetActive(active) => {
+            if active && !sh.state.lock().unwrap().any_selected() {
+                return invalid("no connected monitor is selected");
+            }
+            Ok(set_active(sh, active))
+        }
+
+        UiMsg::Quit => shutdown(sh),
     }
 }
@@ -0,0 +1,32 @@
+//! Test stand-in for the system sources the detectors and trackers sample.
+//!
+//! [`IdleDetector`](crate::idle::IdleDetector),
+//! [`AppWatcher`](crate::apps::AppWatcher), the
+//! [`Scheduler`](crate::schedule::Scheduler) and the
+//! [`ExposureTracker`](crate::stats::ExposureTracker) each read the system
+//! through a small trait.  Their tests implement that trait for [`Fake`]
+//! over the value the source reports and move it by hand.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A value the test sets by hand, shared by every clone: the test keeps one
+/// handle and gives the code under test another.
+#[derive(Clone, Default)]
+pub struct Fake<T>(Rc<RefCell<T>>);
+
+impl<T: Clone> Fake<T> {
+    pub fn new(value: T) -> Self {
+        Self(Rc::new(RefCell::new(value)))
+    }
+
+    /// The current value.
+    pub fn get(&self) -> T {
+        self.0.borrow().clone()
+    }
+
+    /// Replace the value every clone reads.
+    pub fn set(&self, value: T) {
+        *self.0.borrow_mut() = value;
+    }
+}
@@ -0,0 +1,112 @@
+//! The foreground window, as several features see it.
+//!
+//! Focus-follow overlays, fullscreen detection and application rules all
+//! look at the foreground window.  The shell's desktop windows, which get
+//! focus when the user clicks the wallpaper, are never worth reacting to,
+//! and a window's visible frame differs from its `GetWindowRect` by the
+//! invisible resize borders; both are handled here once.
+//!
+//! Focus-follow overlays run one timer each, so [`shared_focus`] samples
+//! the foreground window once per timer interval for all of them.
+
+use std::ffi::c_void;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use windows::Win32::Foundation::{HWND, RECT};
+use windows::Win32::Graphics::Dwm::{DWMWA_EXTENDED_FRAME_BOUNDS, DwmGetWindowAttribute};
+use windows::Win32::Graphics::Gdi::{
+    GetMonitorInfoW, MONITOR_DEFAULTTONULL, MONITORINFO, MonitorFromWindow,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetClassNameW, GetForegroundWindow, GetWindowRect, IsIconic,
+};
+
+use crate::overlay::focus::Foreground;
+use crate::overlay::region::Rect;
+
+/// Whether `hwnd` is one of the shell's desktop windows (`Progman` /
+/// `WorkerW`), which span their monitor and take focus when the wallpaper
+/// is clicked.
+pub unsafe fn is_desktop(hwnd: HWND) -> bool {
+    let mut class = [0u16; 32];
+    let len = unsafe { GetClassNameW(hwnd, &mut class) }.max(0) as usize;
+    let class = String::from_utf16_lossy(&class[..len]);
+    class == "Progman" || class == "WorkerW"
+}
+
+/// The visible frame of `hwnd` in virtual-screen coordinates, without the
+/// invisible resize borders `GetWindowRect` includes.  Falls back to the
+/// window rectangle when DWM cannot tell.
+pub unsafe fn frame_bounds(hwnd: HWND) -> Option<Rect> {
+    let mut rect = RECT::default();
+    unsafe {
+        if DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_EXTENDED_FRAME_BOUNDS,
+            &mut rect as *mut RECT as *mut c_void,
+            std::mem::size_of::<RECT>() as u32,
+        )
+        .is_err()
+        {
+            GetWindowRect(hwnd, &mut rect).ok()?;
+        }
+    }
+    Some((
+        rect.left,
+        rect.top,
+        rect.right - rect.left,
+        rect.bottom - rect.top,
+    ))
+}
+
+/// Sample the foreground window for focus-follow.  `None` when there is
+/// nothing worth cutting out: no foreground window, a minimised one or the
+/// desktop itself.
+unsafe fn sample_focus() -> Option<Foreground> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() || IsIconic(hwnd).as_bool() || is_desktop(hwnd) {
+            return None;
+        }
+        let bounds = frame_bounds(hwnd)?;
+
+        let hmonitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL);
+        if hmonitor.is_invalid() {
+            return None;
+        }
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(hmonitor, &mut info).as_bool() {
+            return None;
+        }
+        let m = info.rcMonitor;
+
+        Some(Foreground {
+            id: hwnd.0 as usize,
+            bounds,
+            monitor: (m.left, m.top, m.right - m.left, m.bottom - m.top),
+        })
+    }
+}
+
+/// Last focus sample and when it was taken, shared by every overlay thread.
+static LAST_FOCUS: Mutex<Option<(Instant, Option<Foreground>)>> = Mutex::new(None);
+
+/// The foreground window for focus-follow, sampled afresh only if the last
+/// sample is older than `max_age`.  With `max_age` set to the overlays'
+/// timer interval, all of them share one sample per tick.
+pub fn shared_focus(max_age: Duration) -> Option<Foreground> {
+    let mut last = LAST_FOCUS.lock().unwrap();
+    let now = Instant::now();
+    match *last {
+        Some((at, focus)) if now.duration_since(at) < max_age => focus,
+        _ => {
+            let focus = unsafe { sample_focus() };
+            *last = Some((now, focus));
+            focus
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! Fullscreen detection.
+//!
+//! Layered topmost overlays fight with fullscreen games and video players:
+//! they can knock exclusive-mode games out of fullscreen and stack above
+//! borderless ones.  While a fullscreen application covers a protected
+//! monitor the daemon therefore pauses that monitor's overlays and restores
+//! them afterwards.
+//!
+//! A monitor counts as covered when the foreground window's visible frame
+//! spans all of it and the window has neither a caption nor the maximised
+//! style (borderless fullscreen), or when the shell reports a
+//! fullscreen / presentation state (exclusive fullscreen, where the window
+//! rectangle may not match) for the monitor the foreground window is on.
+//!
+//! [`paused_monitors`] is a pure function of a [`FullscreenSample`]; the
+//! [`FullscreenDetector`] samples a [`FullscreenSource`] and reports only
+//! changes, so tests can drive it with synthetic samples.
+
+use std::collections::BTreeSet;
+
+use crate::monitor::{MonitorId, MonitorInfo};
+use crate::overlay::region::Rect;
+
+/// What the shell says about the user's current activity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShellState {
+    #[default]
+    Normal,
+    /// A fullscreen application is running ("busy").
+    Busy,
+    /// An exclusive-mode Direct3D application is running.
+    D3dFullscreen,
+    /// The user turned on presentation mode.
+    Presentation,
+}
+
+/// One observation of the desktop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FullscreenSample {
+    /// Visible frame of the foreground window, in virtual-screen
+    /// coordinates.  `None` if there is none or it is the desktop itself.
+    pub foreground: Option<Rect>,
+    /// Whether the foreground window has a caption or is maximised.  Such a
+    /// window can span its monitor, e.g. maximised with an auto-hiding
+    /// taskbar, without being fullscreen.
+    pub framed: bool,
+    pub shell: ShellState,
+}
+
+/// Source of [`FullscreenSample`]s.
+pub trait FullscreenSource {
+    fn sample(&self) -> FullscreenSample;
+}
+
+/// The interactive session's foreground window and shell state.
+pub struct SystemFullscreen;
+
+impl FullscreenSource for SystemFullscreen {
+    #[cfg(windows)]
+    fn sample(&self) -> FullscreenSample {
+        use windows::Win32::UI::Shell::{
+            QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN,
+            SHQueryUserNotificationState,
+        };
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GWL_STYLE, GetForegroundWindow, GetWindowLongPtrW, WS_CAPTION, WS_MAXIMIZE,
+        };
+
+        use crate::foreground::{frame_bounds, is_desktop};
+
+        unsafe {
+            let shell = match SHQueryUserNotificationState() {
+                Ok(QUNS_BUSY) => ShellState::Busy,
+                Ok(QUNS_RUNNING_D3D_FULL_SCREEN) => ShellState::D3dFullscreen,
+                Ok(QUNS_PRESENTATION_MODE) => ShellState::Presentation,
+                _ => ShellState::Normal,
+            };
+
+            let hwnd = GetForegroundWindow();
+            if hwnd.0.is_null() {
+                return FullscreenSample {
+                    shell,
+                    ..Default::default()
+                };
+            }
+            // The desktop window always spans its monitor.
+            let foreground = if is_desktop(hwnd) {
+                None
+            } else {
+                frame_bounds(hwnd)
+            };
+            let style = GetWindowLongPtrW(hwnd, GWL_STYLE) as u32;
+            // WS_CAPTION is two bits; a bare border or dialog frame is fine.
+            let framed = style & WS_CAPTION.0 == WS_CAPTION.0 || style & WS_MAXIMIZE.0 != 0;
+
+            FullscreenSample {
+                foreground,
+                framed,
+                shell,
+            }
+        }
+    }
+
+    /// No portable notion of fullscreen windows; nothing is ever paused.
+    #[cfg(not(windows))]
+    fn sample(&self) -> FullscreenSample {
+        FullscreenSample::default()
+    }
+}
+
+// ── Detection ────────────────────────────────────────────────────────────────
+
+/// Whether `window` spans all of `monitor`.
+pub fn covers(window: Rect, monitor: Rect) -> bool {
+    let (wx, wy, ww, wh) = window;
+    let (mx, my, mw, mh) = monitor;
+    wx <= mx && wy <= my && wx + ww >= mx + mw && wy + wh >= my + mh
+}
+
+/// Pixels shared by two rectangles.
+fn overlap(a: Rect, b: Rect) -> i64 {
+    let (ax, ay, aw, ah) = a;
+    let (bx, by, bw, bh) = b;
+    let w = ((ax + aw).min(bx + bw) - ax.max(bx)).max(0) as i64;
+    let h = ((ay + ah).min(by + bh) - ay.max(by)).max(0) as i64;
+    w * h
+}
+
+/// Monitors a fullscreen application covers in `sample`.
+pub fn paused_monitors(sample: &FullscreenSample, monitors: &[MonitorInfo]) -> BTreeSet<MonitorId> {
+    let Some(fg) = sample.foreground else {
+        return BTreeSet::new();
+    };
+    let rect = |m: &MonitorInfo| (m.x, m.y, m.width, m.height);
+
+    // Borderless fullscreen: an undecorated window spans the monitor.
+    let mut paused: BTreeSet<MonitorId> = monitors
+        .iter()
+        .filter(|m| !sample.framed && covers(fg, rect(m)))
+        .map(|m| m.id.clone())
+        .collect();
+
+    // The shell knows better: pause the monitor the window is mostly on.
+    if sample.shell != ShellState::Normal {
+        let home = monitors
+            .iter()
+            .map(|m| (overlap(fg, rect(m)), m))
+            .filter(|(area, _)| *area > 0)
+            .max_by_key(|(area, _)| *area);
+        if let Some((_, m)) = home {
+            paused.insert(m.id.clone());
+        }
+    }
+    paused
+}
+
+/// Edge-triggered fullscreen detection.
+pub struct FullscreenDetector<S: FullscreenSource> {
+    source: S,
+    /// Result of the previous tick.
+    last: BTreeSet<MonitorId>,
+}
+
+impl<S: FullscreenSource> FullscreenDetector<S> {
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            last: BTreeSet::new(),
+        }
+    }
+
+    /// Sample the source and report the new set of covered monitors when it
+    /// changed.  With `enabled` off nothing is ever covered, so turning the
+    /// feature off un-pauses everything on the next tick.
+    pub fn tick(&mut self, monitors: &[MonitorInfo], enabled: bool) -> Option<BTreeSet<MonitorId>> {
+        let paused = if enabled {
+            paused_monitors(&self.source.sample(), monitors)
+        } else {
+            BTreeSet::new()
+        };
+        if paused == self.last {
+            return None;
+        }
+        self.last = paused.clone();
+        Some(paused)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEFT: (i32, i32, i32, i32) = (0, 0, 1920, 1080);
+    const RIGHT: (i32, i32, i32, i32) = (1920, 0, 1920, 1080);
+
+    fn sample(foreground: Rect, framed: bool, shell: ShellState) -> FullscreenSample {
+        FullscreenSample {
+            foreground: Some(foreground),
+            framed,
+            shell,
+        }
+    }
+
+    fn ids(ids: &[&str]) -> BTreeSet<MonitorId> {
+        ids.iter().map(|id| MonitorId(id.to_string())).collect()
+    }
+
+    #[test]
+    fn borderless_window_spanning_a_monitor_pauses_it() {
+        let monitors = [MonitorInfo::at("a", LEFT), MonitorInfo::at("b", RIGHT)];
+        let s = sample((1920, 0, 1920, 1080), false, ShellState::Normal);
+        assert_eq!(paused_monitors(&s, &monitors), ids(&["b"]));
+    }
+
+    #[test]
+    fn framed_window_spanning_a_monitor_does_not_pause_it() {
+        // Maximised with an auto-hiding taskbar: the frame covers the monitor.
+        let monitors = [MonitorInfo::at("a", LEFT)];
+        let s = sample((0, 0, 1920, 1080), true, ShellState::Normal);
+        assert!(paused_monitors(&s, &monitors).is_empty());
+    }
+
+    #[test]
+    fn window_short_of_the_monitor_does_not_pause_it() {
+        let monitors = [MonitorInfo::at("a", LEFT)];
+        let s = sample((0, 0, 1920, 1040), false, ShellState::Normal);
+        assert!(paused_monitors(&s, &monitors).is_empty());
+    }
+
+    #[test]
+    fn shell_fullscreen_pauses_the_monitor_the_window_is_mostly_on() {
+        let monitors = [MonitorInfo::at("a", LEFT), MonitorInfo::at("b", RIGHT)];
+        // Exclusive fullscreen at a lower resolution; the rectangle does not
+        // match the monitor.
+        let s = sample((1800, 0, 1280, 720), true, ShellState::D3dFullscreen);
+        assert_eq!(paused_monitors(&s, &monitors), ids(&["b"]));
+    }
+
+    #[test]
+    fn nothing_is_paused_without_a_foreground_window() {
+        let monitors = [MonitorInfo::at("a", LEFT)];
+        let s = FullscreenSample {
+            shell: ShellState::Busy,
+            ..Default::default()
+        };
+        assert!(paused_monitors(&s, &monitors).is_empty());
+    }
+
+    #[test]
+    fn detector_reports_changes_and_unpauses_when_disabled() {
+        struct Fixed(FullscreenSample);
+        impl FullscreenSource for Fixed {
+            fn sample(&self) -> FullscreenSample {
+                self.0
+            }
+        }
+
+        let monitors = [MonitorInfo::at("a", LEFT)];
+        let mut detector =
+            FullscreenDetector::new(Fixed(sample((0, 0, 1920, 1080), false, ShellState::Normal)));
+        assert_eq!(detector.tick(&monitors, true), Some(ids(&["a"])));
+        assert_eq!(detector.tick(&monitors, true), None);
+        assert_eq!(detector.tick(&monitors, false), Some(ids(&[])));
+    }
+}
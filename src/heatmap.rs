@@ -0,0 +1,949 @@
+//! Static-content analysis.
+//!
+//! Dimming treats every pixel alike, but burn-in comes from what stays put:
+//! taskbars, docked panels, HUDs and logos shown bright for hours.  With
+//! content analysis on, the daemon samples a downscaled frame of each
+//! monitor every [`SAMPLE_INTERVAL`] and accumulates a [`Heatmap`] of how
+//! long each grid cell stayed both unchanged and bright.  Groups of cells
+//! that were static for most of the observed time become suggested overlay
+//! [`Region`]s ([`suggest_regions`]).
+//!
+//! Frames come from a [`FrameSource`]; accumulation and suggestion are pure,
+//! so tests can feed synthetic frame sequences without a screen.  Capturing
+//! blocks for a while, so the daemon does it on a thread of its own and only
+//! hands the finished frames to the [`ContentAnalyzer`].
+//!
+//! Heatmaps are kept in `heatmap.json` next to the settings, so suggestions
+//! survive a restart.  Only the accumulated times are stored, never a frame.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::monitor::{MonitorId, MonitorInfo};
+use crate::overlay::region::{Rect, Region, RegionRect, RegionUnit};
+use crate::settings;
+
+/// File name of the heatmaps inside the config directory.
+const FILE_NAME: &str = "heatmap.json";
+
+/// Columns of the analysis grid.
+pub const GRID_COLS: u32 = 48;
+
+/// Rows of the analysis grid (square cells on a 16:9 monitor).
+pub const GRID_ROWS: u32 = 27;
+
+/// Time between two frame samples.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest gap between two samples that is still accounted.  Anything
+/// longer means the machine slept or analysis was paused.
+pub const MAX_GAP_MS: u64 = 3 * SAMPLE_INTERVAL.as_millis() as u64;
+
+/// Luminance (0–255) from which a cell counts as bright.
+pub const BRIGHT_LUMA: u8 = 128;
+
+/// Largest luminance change between two samples of a static cell.
+pub const STATIC_TOLERANCE: u8 = 4;
+
+/// Share of the observed time a cell must be static and bright to be
+/// covered by a suggestion.
+pub const HOT_SHARE: f32 = 0.6;
+
+/// Observed time before a monitor gets suggestions (30 minutes).
+pub const MIN_OBSERVED_MS: u64 = 30 * 60 * 1000;
+
+/// Fewest connected hot cells that make a suggestion; single cells are
+/// usually a resting cursor or a clock digit.
+pub const MIN_CELLS: usize = 2;
+
+/// Shortest time between two writes of the heatmaps (5 minutes).
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+// ── Frames ───────────────────────────────────────────────────────────────────
+
+/// A downscaled greyscale sample of one monitor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub cols: u32,
+    pub rows: u32,
+    /// Luminance per cell, rows from the top.
+    pub luma: Vec<u8>,
+}
+
+impl Frame {
+    /// Frame from 32-bit `0x__RRGGBB` pixels, rows from the top.
+    pub fn from_rgb(cols: u32, rows: u32, pixels: &[u32]) -> Self {
+        Self {
+            cols,
+            rows,
+            luma: pixels.iter().map(|&p| luma(p)).collect(),
+        }
+    }
+}
+
+/// Rec. 709 luminance of a `0x__RRGGBB` pixel.
+pub fn luma(pixel: u32) -> u8 {
+    let (r, g, b) = ((pixel >> 16) & 0xFF, (pixel >> 8) & 0xFF, pixel & 0xFF);
+    ((54 * r + 183 * g + 19 * b) >> 8) as u8
+}
+
+/// Source of monitor frames.
+pub trait FrameSource {
+    /// `monitor` (in virtual-screen coordinates) averaged down to
+    /// `cols` × `rows` cells, or `None` if it cannot be captured right now.
+    fn capture(&self, monitor: Rect, cols: u32, rows: u32) -> Option<Frame>;
+}
+
+/// The interactive session's screen, through GDI.
+pub struct SystemCapture;
+
+impl FrameSource for SystemCapture {
+    /// Stretches the screen into a small bitmap with halftoning, which
+    /// averages each cell.  Without `CAPTUREBLT` layered windows — the
+    /// overlays themselves — are left out, so dimming does not hide the
+    /// content underneath.
+    #[cfg(windows)]
+    fn capture(&self, monitor: Rect, cols: u32, rows: u32) -> Option<Frame> {
+        use windows::Win32::Graphics::Gdi::{
+            BI_RGB, BITMAPINFO, BITMAPINFOHEADER, CreateCompatibleBitmap, CreateCompatibleDC,
+            DIB_RGB_COLORS, DeleteDC, DeleteObject, GetDC, GetDIBits, HALFTONE, ReleaseDC, SRCCOPY,
+            SelectObject, SetBrushOrgEx, SetStretchBltMode, StretchBlt,
+        };
+
+        let (x, y, width, height) = monitor;
+        if width <= 0 || height <= 0 || cols == 0 || rows == 0 {
+            return None;
+        }
+        unsafe {
+            let screen = GetDC(None);
+            if screen.is_invalid() {
+                return None;
+            }
+            let dc = CreateCompatibleDC(Some(screen));
+            let bitmap = CreateCompatibleBitmap(screen, cols as i32, rows as i32);
+            let mut pixels = vec![0u32; (cols * rows) as usize];
+            let mut copied = false;
+            if !dc.is_invalid() && !bitmap.is_invalid() {
+                let previous = SelectObject(dc, bitmap.into());
+                SetStretchBltMode(dc, HALFTONE);
+                let _ = SetBrushOrgEx(dc, 0, 0, None);
+                copied = StretchBlt(
+                    dc,
+                    0,
+                    0,
+                    cols as i32,
+                    rows as i32,
+                    Some(screen),
+                    x,
+                    y,
+                    width,
+                    height,
+                    SRCCOPY,
+                )
+                .as_bool();
+                // GetDIBits wants the bitmap deselected.
+                SelectObject(dc, previous);
+                let mut info = BITMAPINFO {
+                    bmiHeader: BITMAPINFOHEADER {
+                        biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                        biWidth: cols as i32,
+                        // Negative height: top-down rows.
+                        biHeight: -(rows as i32),
+                        biPlanes: 1,
+                        biBitCount: 32,
+                        biCompression: BI_RGB.0,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                copied &= GetDIBits(
+                    dc,
+                    bitmap,
+                    0,
+                    rows,
+                    Some(pixels.as_mut_ptr().cast()),
+                    &mut info,
+                    DIB_RGB_COLORS,
+                ) == rows as i32;
+            }
+            if !bitmap.is_invalid() {
+                let _ = DeleteObject(bitmap.into());
+            }
+            if !dc.is_invalid() {
+                let _ = DeleteDC(dc);
+            }
+            ReleaseDC(None, screen);
+            copied.then(|| Frame::from_rgb(cols, rows, &pixels))
+        }
+    }
+
+    /// No portable screen capture; nothing is ever analysed.
+    #[cfg(not(windows))]
+    fn capture(&self, _monitor: Rect, _cols: u32, _rows: u32) -> Option<Frame> {
+        None
+    }
+}
+
+// ── Heatmap ──────────────────────────────────────────────────────────────────
+
+/// How long each cell of one monitor stayed static at high luminance.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Heatmap {
+    cols: u32,
+    rows: u32,
+    /// Milliseconds each cell was static and bright, rows from the top.
+    static_ms: Vec<u64>,
+    /// Milliseconds between accounted pairs of samples.
+    observed_ms: u64,
+    /// Latest sample, compared against the next one.  Never stored: it is
+    /// screen content.
+    #[serde(skip)]
+    previous: Option<Frame>,
+}
+
+impl Heatmap {
+    pub fn new(cols: u32, rows: u32) -> Self {
+        Self {
+            cols,
+            rows,
+            static_ms: vec![0; (cols * rows) as usize],
+            observed_ms: 0,
+            previous: None,
+        }
+    }
+
+    /// Account the `elapsed_ms` since the previous sample: every cell that is
+    /// bright in both samples and changed by at most [`STATIC_TOLERANCE`]
+    /// was static for that long.  The first sample (and the first after
+    /// [`interrupt`](Self::interrupt)) only sets the baseline; one of a
+    /// different size starts the heatmap afresh.
+    pub fn add(&mut self, frame: Frame, elapsed_ms: u64) {
+        if frame.cols != self.cols
+            || frame.rows != self.rows
+            || frame.luma.len() != self.static_ms.len()
+        {
+            *self = Heatmap::new(frame.cols, frame.rows);
+        }
+        if let Some(previous) = &self.previous {
+            self.observed_ms += elapsed_ms;
+            let cells = previous.luma.iter().zip(&frame.luma);
+            for (ms, (&a, &b)) in self.static_ms.iter_mut().zip(cells) {
+                if a.min(b) >= BRIGHT_LUMA && a.abs_diff(b) <= STATIC_TOLERANCE {
+                    *ms += elapsed_ms;
+                }
+            }
+        }
+        self.previous = Some(frame);
+    }
+
+    /// Drop the previous sample, so the time until the next one is not
+    /// accounted.
+    pub fn interrupt(&mut self) {
+        self.previous = None;
+    }
+}
+
+// ── Suggestions ──────────────────────────────────────────────────────────────
+
+/// Rectangle of grid cells `(col, row, cols, rows)`.
+pub type CellRect = (u32, u32, u32, u32);
+
+/// Bounding boxes of the hot areas of `heatmap`: groups of at least
+/// [`MIN_CELLS`] edge-connected cells that were static and bright for at
+/// least `min_share` of the observed time.  Overlapping boxes are merged;
+/// the result runs top to bottom, then left to right.
+pub fn hot_areas(heatmap: &Heatmap, min_share: f32) -> Vec<CellRect> {
+    // A stored heatmap whose grid does not add up has nothing usable.
+    if heatmap.observed_ms == 0 || heatmap.static_ms.len() != (heatmap.cols * heatmap.rows) as usize
+    {
+        return Vec::new();
+    }
+    let (w, h) = (heatmap.cols as usize, heatmap.rows as usize);
+    let threshold = heatmap.observed_ms as f32 * min_share;
+    let hot: Vec<bool> = heatmap
+        .static_ms
+        .iter()
+        .map(|&ms| ms > 0 && ms as f32 >= threshold)
+        .collect();
+
+    let mut seen = vec![false; hot.len()];
+    let mut areas = Vec::new();
+    for (start, &is_hot) in hot.iter().enumerate() {
+        if !is_hot || seen[start] {
+            continue;
+        }
+        // Flood-fill the group, tracking its extent.
+        seen[start] = true;
+        let mut stack = vec![start];
+        let (mut left, mut top, mut right, mut bottom) = (w, h, 0, 0);
+        let mut count = 0;
+        while let Some(i) = stack.pop() {
+            count += 1;
+            let (x, y) = (i % w, i / w);
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x);
+            bottom = bottom.max(y);
+            let neighbours = [
+                (x > 0).then(|| i - 1),
+                (x + 1 < w).then(|| i + 1),
+                (y > 0).then(|| i - w),
+                (y + 1 < h).then(|| i + w),
+            ];
+            for n in neighbours.into_iter().flatten() {
+                if hot[n] && !seen[n] {
+                    seen[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        if count >= MIN_CELLS {
+            areas.push((
+                left as u32,
+                top as u32,
+                (right - left + 1) as u32,
+                (bottom - top + 1) as u32,
+            ));
+        }
+    }
+    merge_overlapping(areas)
+}
+
+/// Whether two cell rectangles share a cell.
+fn intersects(a: CellRect, b: CellRect) -> bool {
+    a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+}
+
+/// Replace overlapping rectangles by their bounding box until none overlap.
+fn merge_overlapping(mut areas: Vec<CellRect>) -> Vec<CellRect> {
+    'restart: loop {
+        for i in 0..areas.len() {
+            for j in i + 1..areas.len() {
+                if intersects(areas[i], areas[j]) {
+                    let (a, b) = (areas[i], areas.swap_remove(j));
+                    let (left, top) = (a.0.min(b.0), a.1.min(b.1));
+                    let right = (a.0 + a.2).max(b.0 + b.2);
+                    let bottom = (a.1 + a.3).max(b.1 + b.3);
+                    areas[i] = (left, top, right - left, bottom - top);
+                    continue 'restart;
+                }
+            }
+        }
+        break;
+    }
+    areas.sort_by_key(|&(x, y, _, _)| (y, x));
+    areas
+}
+
+/// Overlay regions over the hot areas of `heatmap`, in pixels of a
+/// `width` × `height` monitor, named `suggested-1`, `suggested-2`, …
+pub fn suggest_regions(heatmap: &Heatmap, width: i32, height: i32) -> Vec<Region> {
+    let (cols, rows) = (heatmap.cols.max(1) as f32, heatmap.rows.max(1) as f32);
+    let (cell_w, cell_h) = (width as f32 / cols, height as f32 / rows);
+    hot_areas(heatmap, HOT_SHARE)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (x, y, w, h))| Region {
+            name: format!("suggested-{}", i + 1),
+            rect: RegionRect {
+                unit: RegionUnit::Pixels,
+                x: (x as f32 * cell_w).floor(),
+                y: (y as f32 * cell_h).floor(),
+                width: (w as f32 * cell_w).ceil(),
+                height: (h as f32 * cell_h).ceil(),
+            },
+            opacity: None,
+        })
+        .collect()
+}
+
+/// The `suggestions` for a monitor that has `existing` regions, without
+/// those already added.  Suggestion names are only unique within one reply,
+/// so any that is taken is renamed to the first free `suggested-N`, and
+/// adding it cannot replace a region.
+pub fn unclaimed(suggestions: &[Region], existing: &[Region]) -> Vec<Region> {
+    let mut taken: Vec<String> = existing.iter().map(|r| r.name.clone()).collect();
+    let mut fresh = Vec::new();
+    for s in suggestions {
+        if existing.iter().any(|r| r.rect == s.rect) {
+            continue;
+        }
+        let name = if taken.contains(&s.name) {
+            (1..)
+                .map(|n| format!("suggested-{}", n))
+                .find(|n| !taken.contains(n))
+                .unwrap()
+        } else {
+            s.name.clone()
+        };
+        taken.push(name.clone());
+        fresh.push(Region { name, ..s.clone() });
+    }
+    fresh
+}
+
+// ── Analyzer ─────────────────────────────────────────────────────────────────
+
+/// Capture every one of `monitors` from `source` at the analysis grid size;
+/// `None` for those that cannot be captured right now.
+pub fn capture_all(
+    source: &impl FrameSource,
+    monitors: &[MonitorInfo],
+) -> Vec<(MonitorId, Option<Frame>)> {
+    monitors
+        .iter()
+        .map(|m| {
+            let rect = (m.x, m.y, m.width, m.height);
+            (m.id.clone(), source.capture(rect, GRID_COLS, GRID_ROWS))
+        })
+        .collect()
+}
+
+/// Per-monitor heatmaps, fed with captured frames.
+#[derive(Debug, Default)]
+pub struct ContentAnalyzer {
+    heatmaps: BTreeMap<MonitorId, Heatmap>,
+    /// Time of the previous sample.
+    last: Option<Instant>,
+    /// Whether the heatmaps changed since they were last flushed.
+    dirty: bool,
+    /// Time of the last flush.
+    flushed: Option<Instant>,
+}
+
+impl ContentAnalyzer {
+    /// Continue from `heatmaps` saved by an earlier session.
+    pub fn new(heatmaps: BTreeMap<MonitorId, Heatmap>) -> Self {
+        Self {
+            heatmaps,
+            ..Self::default()
+        }
+    }
+
+    /// Account `frames` (see [`capture_all`]) sampled at `now`.  After a gap
+    /// longer than [`MAX_GAP_MS`], a failed capture or a monitor's absence,
+    /// its next sample only sets a new baseline.
+    pub fn record(&mut self, frames: Vec<(MonitorId, Option<Frame>)>, now: Instant) {
+        let accounted = self
+            .last
+            .map(|last| now.saturating_duration_since(last).as_millis() as u64)
+            .filter(|&ms| ms <= MAX_GAP_MS);
+        self.last = Some(now);
+
+        for (id, heatmap) in self.heatmaps.iter_mut() {
+            if !frames.iter().any(|(f, _)| f == id) {
+                heatmap.interrupt();
+            }
+        }
+        for (id, frame) in frames {
+            let heatmap = self
+                .heatmaps
+                .entry(id)
+                .or_insert_with(|| Heatmap::new(GRID_COLS, GRID_ROWS));
+            match frame {
+                Some(frame) => {
+                    if accounted.is_none() {
+                        heatmap.interrupt();
+                    }
+                    heatmap.add(frame, accounted.unwrap_or(0));
+                }
+                None => heatmap.interrupt(),
+            }
+        }
+        self.dirty = true;
+    }
+
+    /// Suggested regions for each of `monitors` observed for at least
+    /// [`MIN_OBSERVED_MS`].
+    pub fn suggestions(&self, monitors: &[MonitorInfo]) -> BTreeMap<MonitorId, Vec<Region>> {
+        monitors
+            .iter()
+            .filter_map(|m| {
+                let heatmap = self.heatmaps.get(&m.id)?;
+                (heatmap.observed_ms >= MIN_OBSERVED_MS)
+                    .then(|| (m.id.clone(), suggest_regions(heatmap, m.width, m.height)))
+            })
+            .collect()
+    }
+
+    /// Forget everything observed so far.  The next flush clears the file.
+    pub fn reset(&mut self) {
+        self.heatmaps.clear();
+        self.last = None;
+        self.dirty = true;
+    }
+
+    /// The heatmaps, when they changed and were last flushed at least
+    /// [`FLUSH_INTERVAL`] before `now`.  The caller writes them out.
+    pub fn due(&mut self, now: Instant) -> Option<BTreeMap<MonitorId, Heatmap>> {
+        let recent = self
+            .flushed
+            .is_some_and(|at| now.saturating_duration_since(at) < FLUSH_INTERVAL);
+        if !self.dirty || recent {
+            return None;
+        }
+        Some(self.flush(now))
+    }
+
+    /// The heatmaps to write out now (e.g. on shutdown).
+    pub fn flush(&mut self, now: Instant) -> BTreeMap<MonitorId, Heatmap> {
+        self.flushed = Some(now);
+        self.dirty = false;
+        self.heatmaps.clone()
+    }
+}
+
+// ── Persistence ──────────────────────────────────────────────────────────────
+
+/// Full path of the heatmap file, or `None` if no config directory exists.
+pub fn heatmap_path() -> Option<PathBuf> {
+    settings::config_dir().map(|d| d.join(FILE_NAME))
+}
+
+/// Load the heatmaps from the default location.
+///
+/// Never fails: a missing file yields no heatmaps, and a corrupt one is
+/// renamed to `heatmap.json.corrupt` first.
+pub fn load() -> BTreeMap<MonitorId, Heatmap> {
+    match heatmap_path() {
+        Some(path) => load_from(&path),
+        None => BTreeMap::new(),
+    }
+}
+
+/// Load the heatmaps from `path`, see [`load`].
+pub fn load_from(path: &Path) -> BTreeMap<MonitorId, Heatmap> {
+    let bytes = match fs::read(path) {
+        Ok(b) => b,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return BTreeMap::new(),
+        Err(e) => {
+            eprintln!("[heatmap] Cannot read {}: {:?}", path.display(), e);
+            return BTreeMap::new();
+        }
+    };
+    match serde_json::from_slice(&bytes) {
+        Ok(heatmaps) => heatmaps,
+        Err(e) => {
+            eprintln!(
+                "[heatmap] {} is unusable ({}); starting afresh",
+                path.display(),
+                e
+            );
+            let _ = fs::rename(path, path.with_extension("json.corrupt"));
+            BTreeMap::new()
+        }
+    }
+}
+
+/// Save the heatmaps to the default location.
+pub fn save(heatmaps: &BTreeMap<MonitorId, Heatmap>) -> io::Result<()> {
+    let path = heatmap_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    save_to(&path, heatmaps)
+}
+
+/// Atomically write `heatmaps` to `path`.
+pub fn save_to(path: &Path, heatmaps: &BTreeMap<MonitorId, Heatmap>) -> io::Result<()> {
+    let body =
+        serde_json::to_vec(heatmaps).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    settings::write_atomic(path, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    const SECOND: Duration = Duration::from_secs(1);
+    const FHD: (i32, i32, i32, i32) = (0, 0, 1920, 1080);
+
+    fn frame(cols: u32, rows: u32, luma: &[u8]) -> Frame {
+        Frame {
+            cols,
+            rows,
+            luma: luma.to_vec(),
+        }
+    }
+
+    /// A screen whose bottom row is a bright taskbar and whose other cells
+    /// change on every capture.  Captures fail while `blank` is set.
+    #[derive(Clone, Default)]
+    struct FakeScreen {
+        captures: Rc<Cell<u32>>,
+        blank: Rc<Cell<bool>>,
+    }
+
+    impl FrameSource for FakeScreen {
+        fn capture(&self, _monitor: Rect, cols: u32, rows: u32) -> Option<Frame> {
+            if self.blank.get() {
+                return None;
+            }
+            let n = self.captures.get();
+            self.captures.set(n + 1);
+            let luma = (0..cols * rows)
+                .map(|i| {
+                    if i / cols == rows - 1 {
+                        220
+                    } else {
+                        (n * 40 % 250) as u8
+                    }
+                })
+                .collect();
+            Some(Frame { cols, rows, luma })
+        }
+    }
+
+    /// Capture `monitors` from `screen` every [`SAMPLE_INTERVAL`] for
+    /// `samples` samples, starting at `*now`.
+    fn run(
+        analyzer: &mut ContentAnalyzer,
+        screen: &FakeScreen,
+        monitors: &[MonitorInfo],
+        now: &mut Instant,
+        samples: u32,
+    ) {
+        for _ in 0..samples {
+            analyzer.record(capture_all(screen, monitors), *now);
+            *now += SAMPLE_INTERVAL;
+        }
+    }
+
+    // ── Accumulation ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn luma_weights_green_most() {
+        assert_eq!(luma(0x000000), 0);
+        assert_eq!(luma(0xFFFFFF), 255);
+        assert!(luma(0x00FF00) > luma(0xFF0000));
+        assert!(luma(0xFF0000) > luma(0x0000FF));
+    }
+
+    #[test]
+    fn first_sample_only_sets_the_baseline() {
+        let mut heatmap = Heatmap::new(2, 1);
+        heatmap.add(frame(2, 1, &[200, 200]), 10_000);
+        assert_eq!(heatmap.observed_ms, 0);
+        assert_eq!(heatmap.static_ms, [0, 0]);
+    }
+
+    #[test]
+    fn static_bright_cells_accumulate() {
+        let mut heatmap = Heatmap::new(4, 1);
+        heatmap.add(frame(4, 1, &[200, 200, 40, 200]), 0);
+        heatmap.add(frame(4, 1, &[200, 200, 40, 90]), 10_000);
+        heatmap.add(frame(4, 1, &[200, 200, 40, 90]), 5_000);
+        assert_eq!(heatmap.observed_ms, 15_000);
+        // Static and bright; static and bright; static but dark; dimmed.
+        assert_eq!(heatmap.static_ms, [15_000, 15_000, 0, 0]);
+    }
+
+    #[test]
+    fn changes_within_the_tolerance_count_as_static() {
+        let mut heatmap = Heatmap::new(2, 1);
+        heatmap.add(frame(2, 1, &[200, 200]), 0);
+        heatmap.add(
+            frame(2, 1, &[200 + STATIC_TOLERANCE, 200 + STATIC_TOLERANCE + 1]),
+            10_000,
+        );
+        assert_eq!(heatmap.static_ms, [10_000, 0]);
+    }
+
+    #[test]
+    fn a_cell_must_be_bright_in_both_samples() {
+        let mut heatmap = Heatmap::new(2, 1);
+        heatmap.add(frame(2, 1, &[BRIGHT_LUMA, BRIGHT_LUMA - 1]), 0);
+        heatmap.add(frame(2, 1, &[BRIGHT_LUMA, BRIGHT_LUMA + 1]), 10_000);
+        assert_eq!(heatmap.static_ms, [10_000, 0]);
+    }
+
+    #[test]
+    fn interrupt_skips_the_time_until_the_next_sample() {
+        let mut heatmap = Heatmap::new(1, 1);
+        heatmap.add(frame(1, 1, &[200]), 0);
+        heatmap.add(frame(1, 1, &[200]), 10_000);
+        heatmap.interrupt();
+        heatmap.add(frame(1, 1, &[200]), 60_000);
+        heatmap.add(frame(1, 1, &[200]), 10_000);
+        assert_eq!(heatmap.observed_ms, 20_000);
+        assert_eq!(heatmap.static_ms, [20_000]);
+    }
+
+    #[test]
+    fn a_frame_of_another_size_starts_afresh() {
+        let mut heatmap = Heatmap::new(1, 1);
+        heatmap.add(frame(1, 1, &[200]), 0);
+        heatmap.add(frame(1, 1, &[200]), 10_000);
+        heatmap.add(frame(2, 1, &[200, 200]), 10_000);
+        assert_eq!((heatmap.cols, heatmap.rows), (2, 1));
+        assert_eq!(heatmap.observed_ms, 0);
+        assert_eq!(heatmap.static_ms, [0, 0]);
+    }
+
+    // ── Suggestions ──────────────────────────────────────────────────────────
+
+    /// A heatmap observed for 100 s whose cells were static for `static_s`
+    /// seconds each.
+    fn heatmap(cols: u32, rows: u32, static_s: &[u64]) -> Heatmap {
+        Heatmap {
+            cols,
+            rows,
+            static_ms: static_s.iter().map(|s| s * 1000).collect(),
+            observed_ms: 100_000,
+            previous: None,
+        }
+    }
+
+    #[test]
+    fn hot_areas_bound_connected_groups() {
+        #[rustfmt::skip]
+        let h = heatmap(5, 3, &[
+            100, 100,   0,   0,   0,
+              0, 100,   0,  90,  90,
+              0,   0,   0,   0,  90,
+        ]);
+        assert_eq!(hot_areas(&h, 0.6), [(0, 0, 2, 2), (3, 1, 2, 2)]);
+    }
+
+    #[test]
+    fn hot_areas_skip_single_cells_and_rarely_static_ones() {
+        #[rustfmt::skip]
+        let h = heatmap(4, 2, &[
+            100,  0, 50, 50,
+              0,  0,  0,  0,
+        ]);
+        // A lone cell is too small; the pair is static only half the time.
+        assert!(hot_areas(&h, 0.6).is_empty());
+        assert_eq!(hot_areas(&h, 0.5), [(2, 0, 2, 1)]);
+    }
+
+    #[test]
+    fn hot_areas_need_observed_time() {
+        let mut h = heatmap(2, 1, &[0, 0]);
+        h.observed_ms = 0;
+        assert!(hot_areas(&h, 0.0).is_empty());
+    }
+
+    #[test]
+    fn hot_areas_ignore_a_grid_that_does_not_add_up() {
+        let h = heatmap(3, 3, &[100, 100]);
+        assert!(hot_areas(&h, 0.6).is_empty());
+    }
+
+    #[test]
+    fn overlapping_boxes_merge() {
+        // The pair in the bottom right is apart from the L-shaped group, but
+        // inside its box.
+        #[rustfmt::skip]
+        let h = heatmap(4, 3, &[
+            100, 100, 100,   0,
+            100,   0,   0,   0,
+            100,   0, 100, 100,
+        ]);
+        assert_eq!(hot_areas(&h, 0.6), [(0, 0, 4, 3)]);
+        assert_eq!(
+            merge_overlapping(vec![(4, 4, 1, 1), (0, 0, 2, 2), (1, 1, 2, 2)]),
+            [(0, 0, 3, 3), (4, 4, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn suggestions_are_pixel_regions_over_hot_areas() {
+        // A taskbar along the bottom row of a 1920 × 1080 monitor.
+        let mut static_s = vec![0; (GRID_COLS * GRID_ROWS) as usize];
+        let bottom = ((GRID_ROWS - 1) * GRID_COLS) as usize;
+        static_s[bottom..].fill(100);
+        let h = heatmap(GRID_COLS, GRID_ROWS, &static_s);
+
+        let regions = suggest_regions(&h, 1920, 1080);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].name, "suggested-1");
+        assert_eq!(regions[0].opacity, None);
+        let rect = &regions[0].rect;
+        assert_eq!(rect.unit, RegionUnit::Pixels);
+        assert_eq!(
+            (rect.x, rect.y, rect.width, rect.height),
+            (0.0, 1040.0, 1920.0, 40.0)
+        );
+    }
+
+    fn region(name: &str, y: f32) -> Region {
+        Region {
+            name: name.into(),
+            rect: RegionRect {
+                unit: RegionUnit::Pixels,
+                x: 0.0,
+                y,
+                width: 1920.0,
+                height: 40.0,
+            },
+            opacity: None,
+        }
+    }
+
+    #[test]
+    fn unclaimed_drops_suggestions_already_added() {
+        let suggestions = [region("suggested-1", 0.0), region("suggested-2", 1040.0)];
+        let existing = [region("taskbar", 1040.0)];
+        assert_eq!(
+            unclaimed(&suggestions, &existing),
+            [region("suggested-1", 0.0)]
+        );
+    }
+
+    #[test]
+    fn unclaimed_renames_suggestions_whose_name_is_taken() {
+        // suggested-1 was added earlier, when it was the only suggestion.
+        let suggestions = [region("suggested-1", 0.0), region("suggested-2", 500.0)];
+        let existing = [region("suggested-1", 1040.0)];
+        assert_eq!(
+            unclaimed(&suggestions, &existing),
+            [region("suggested-2", 0.0), region("suggested-3", 500.0)]
+        );
+    }
+
+    // ── Analyzer ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn analyzer_suggests_the_static_taskbar_after_enough_time() {
+        let screen = FakeScreen::default();
+        let monitors = [MonitorInfo::at("A", FHD)];
+        let mut analyzer = ContentAnalyzer::default();
+        let mut now = Instant::now();
+
+        let samples = (MIN_OBSERVED_MS / SAMPLE_INTERVAL.as_millis() as u64) as u32;
+        run(&mut analyzer, &screen, &monitors, &mut now, samples);
+        assert!(analyzer.suggestions(&monitors).is_empty());
+
+        run(&mut analyzer, &screen, &monitors, &mut now, 1);
+        let suggestions = analyzer.suggestions(&monitors);
+        let regions = &suggestions[&MonitorId("A".into())];
+        assert_eq!(regions.len(), 1);
+        let rect = &regions[0].rect;
+        assert_eq!(
+            (rect.x, rect.y, rect.width, rect.height),
+            (0.0, 1040.0, 1920.0, 40.0)
+        );
+    }
+
+    #[test]
+    fn analyzer_skips_long_gaps() {
+        let screen = FakeScreen::default();
+        let monitors = [MonitorInfo::at("A", FHD)];
+        let mut analyzer = ContentAnalyzer::default();
+        let mut now = Instant::now();
+
+        run(&mut analyzer, &screen, &monitors, &mut now, 2);
+        now += Duration::from_millis(MAX_GAP_MS) + SECOND;
+        run(&mut analyzer, &screen, &monitors, &mut now, 2);
+        let heatmap = &analyzer.heatmaps[&MonitorId("A".into())];
+        assert_eq!(heatmap.observed_ms, 2 * SAMPLE_INTERVAL.as_millis() as u64);
+    }
+
+    #[test]
+    fn analyzer_interrupts_failed_captures_and_absent_monitors() {
+        let screen = FakeScreen::default();
+        let (a, b) = (MonitorInfo::at("A", FHD), MonitorInfo::at("B", FHD));
+        let mut analyzer = ContentAnalyzer::default();
+        let mut now = Instant::now();
+
+        run(&mut analyzer, &screen, &[a.clone(), b.clone()], &mut now, 2);
+        // B is unplugged for one sample, then every capture fails once.
+        run(
+            &mut analyzer,
+            &screen,
+            std::slice::from_ref(&a),
+            &mut now,
+            1,
+        );
+        screen.blank.set(true);
+        run(&mut analyzer, &screen, &[a.clone(), b.clone()], &mut now, 1);
+        screen.blank.set(false);
+        run(&mut analyzer, &screen, &[a.clone(), b.clone()], &mut now, 2);
+
+        let step = SAMPLE_INTERVAL.as_millis() as u64;
+        assert_eq!(analyzer.heatmaps[&a.id].observed_ms, 3 * step);
+        assert_eq!(analyzer.heatmaps[&b.id].observed_ms, 2 * step);
+    }
+
+    #[test]
+    fn reset_forgets_everything() {
+        let screen = FakeScreen::default();
+        let monitors = [MonitorInfo::at("A", FHD)];
+        let mut analyzer = ContentAnalyzer::default();
+        let mut now = Instant::now();
+        run(&mut analyzer, &screen, &monitors, &mut now, 3);
+
+        analyzer.reset();
+        assert!(analyzer.heatmaps.is_empty());
+        // The sample after a reset is a baseline again.
+        run(&mut analyzer, &screen, &monitors, &mut now, 1);
+        assert_eq!(analyzer.heatmaps[&monitors[0].id].observed_ms, 0);
+    }
+
+    // ── Persistence ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn due_waits_for_changes_and_the_flush_interval() {
+        let screen = FakeScreen::default();
+        let monitors = [MonitorInfo::at("A", FHD)];
+        let mut analyzer = ContentAnalyzer::default();
+        let mut now = Instant::now();
+        assert!(analyzer.due(now).is_none());
+
+        run(&mut analyzer, &screen, &monitors, &mut now, 2);
+        assert_eq!(analyzer.due(now).map(|h| h.len()), Some(1));
+        run(&mut analyzer, &screen, &monitors, &mut now, 1);
+        assert!(analyzer.due(now).is_none());
+        assert!(analyzer.due(now + FLUSH_INTERVAL).is_some());
+        assert!(analyzer.due(now + FLUSH_INTERVAL * 2).is_none());
+
+        // A reset is written out too, clearing the file.
+        analyzer.reset();
+        assert_eq!(
+            analyzer.due(now + FLUSH_INTERVAL * 2).map(|h| h.len()),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn heatmaps_round_trip_without_screen_content() {
+        let dir = std::env::temp_dir().join(format!("oled-care-heatmap-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(FILE_NAME);
+
+        let screen = FakeScreen::default();
+        let monitors = [MonitorInfo::at("A", FHD)];
+        let mut analyzer = ContentAnalyzer::default();
+        let mut now = Instant::now();
+        run(&mut analyzer, &screen, &monitors, &mut now, 4);
+        let saved = analyzer.flush(now);
+        save_to(&path, &saved).unwrap();
+
+        let loaded = load_from(&path);
+        let (before, after) = (&saved[&monitors[0].id], &loaded[&monitors[0].id]);
+        assert_eq!(after.static_ms, before.static_ms);
+        assert_eq!(after.observed_ms, before.observed_ms);
+        assert!(before.previous.is_some());
+        assert!(after.previous.is_none());
+
+        // Loaded heatmaps keep suggesting, and resume with a baseline.
+        let mut resumed = ContentAnalyzer::new(loaded);
+        run(&mut resumed, &screen, &monitors, &mut now, 1);
+        assert_eq!(
+            resumed.heatmaps[&monitors[0].id].observed_ms,
+            before.observed_ms
+        );
+
+        fs::write(&path, b"{ not json").unwrap();
+        assert!(load_from(&path).is_empty());
+        assert!(path.with_extension("json.corrupt").exists());
+        assert!(load_from(&dir.join("missing.json")).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,168 @@
+//! Idle detection.
+//!
+//! Burn-in is worst while nobody is looking at the screen.  When the user
+//! has not touched keyboard or mouse for [`IdleSettings::timeout_secs`], the
+//! daemon ramps every overlay up to [`IdleSettings::opacity`] (255 blanks
+//! the screen completely) and restores the normal opacity on the next input.
+//!
+//! The [`IdleDetector`] reads the time since the last input from an
+//! [`InputSource`], so tests can drive it without real input.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Default idle timeout (5 minutes).
+pub const DEFAULT_IDLE_TIMEOUT_SECS: u32 = 300;
+
+/// Default opacity while idle.
+pub const DEFAULT_IDLE_OPACITY: u8 = 220;
+
+/// User-configurable idle behaviour.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdleSettings {
+    /// Whether idle dimming is enabled at all.
+    pub enabled: bool,
+    /// Seconds without input before the overlays dim.
+    pub timeout_secs: u32,
+    /// Overlay opacity while idle (255 = fully black).
+    pub opacity: u8,
+}
+
+impl Default for IdleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_secs: DEFAULT_IDLE_TIMEOUT_SECS,
+            opacity: DEFAULT_IDLE_OPACITY,
+        }
+    }
+}
+
+/// Source of the time elapsed since the user's last input.
+pub trait InputSource {
+    fn idle_time(&self) -> Duration;
+}
+
+/// Last keyboard / mouse input of the interactive session.
+pub struct SystemInput;
+
+impl InputSource for SystemInput {
+    #[cfg(windows)]
+    fn idle_time(&self) -> Duration {
+        use windows::Win32::System::SystemInformation::GetTickCount;
+        use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        unsafe {
+            if !GetLastInputInfo(&mut info).as_bool() {
+                return Duration::ZERO;
+            }
+            // Both are 32-bit tick counts; wrapping_sub survives the 49-day
+            // rollover.
+            Duration::from_millis(GetTickCount().wrapping_sub(info.dwTime) as u64)
+        }
+    }
+
+    /// No portable source of session input; never reports idle.
+    #[cfg(not(windows))]
+    fn idle_time(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+/// Turns the idle time into enter / leave transitions.
+pub struct IdleDetector<I: InputSource> {
+    input: I,
+    idle: bool,
+}
+
+impl<I: InputSource> IdleDetector<I> {
+    pub fn new(input: I) -> Self {
+        Self { input, idle: false }
+    }
+
+    /// Sample the input source.  Returns `Some(true)` when the user just went
+    /// idle, `Some(false)` when they just came back (or idle dimming was
+    /// disabled while idle) and `None` otherwise.
+    pub fn tick(&mut self, settings: &IdleSettings) -> Option<bool> {
+        let idle = settings.enabled
+            && self.input.idle_time() >= Duration::from_secs(settings.timeout_secs as u64);
+        if idle == self.idle {
+            return None;
+        }
+        self.idle = idle;
+        Some(idle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake::Fake;
+
+    impl InputSource for Fake<Duration> {
+        fn idle_time(&self) -> Duration {
+            self.get()
+        }
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn enabled(timeout_secs: u32) -> IdleSettings {
+        IdleSettings {
+            enabled: true,
+            timeout_secs,
+            ..IdleSettings::default()
+        }
+    }
+
+    #[test]
+    fn enters_at_the_timeout_and_leaves_on_input() {
+        let input = Fake::default();
+        let mut detector = IdleDetector::new(input.clone());
+        let settings = enabled(60);
+
+        input.set(secs(59));
+        assert_eq!(detector.tick(&settings), None);
+        input.set(secs(60));
+        assert_eq!(detector.tick(&settings), Some(true));
+        input.set(secs(600));
+        assert_eq!(detector.tick(&settings), None);
+        input.set(secs(0));
+        assert_eq!(detector.tick(&settings), Some(false));
+        assert_eq!(detector.tick(&settings), None);
+    }
+
+    #[test]
+    fn disabled_never_goes_idle() {
+        let input = Fake::default();
+        let mut detector = IdleDetector::new(input.clone());
+        input.set(secs(86_400));
+        assert_eq!(detector.tick(&IdleSettings::default()), None);
+    }
+
+    #[test]
+    fn disabling_while_idle_leaves_idle() {
+        let input = Fake::default();
+        let mut detector = IdleDetector::new(input.clone());
+        input.set(secs(600));
+        assert_eq!(detector.tick(&enabled(60)), Some(true));
+        assert_eq!(detector.tick(&IdleSettings::default()), Some(false));
+    }
+
+    #[test]
+    fn raising_the_timeout_while_idle_leaves_idle() {
+        let input = Fake::default();
+        let mut detector = IdleDetector::new(input.clone());
+        input.set(secs(120));
+        assert_eq!(detector.tick(&enabled(60)), Some(true));
+        assert_eq!(detector.tick(&enabled(300)), Some(false));
+    }
+}
@@ -0,0 +1,216 @@
+//! Per-session IPC secret.
+//!
+//! On startup the daemon generates a random token and writes it to
+//! `ipc.token` in the per-user config directory, together with the endpoint
+//! it bound.  Clients read the file, connect to that endpoint only, and
+//! present the token in their [`super::UiMsg::Hello`]; connections that do
+//! not are refused.  Only processes able to read the user's files can
+//! therefore drive the daemon, and the token never goes to an endpoint the
+//! daemon is not serving, such as a TCP port someone else holds.
+//!
+//! The file is created with a protected DACL granting the current user
+//! only, rather than whatever `%APPDATA%` passes down.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// File name of the token inside [`crate::settings::config_dir`].
+const TOKEN_FILE: &str = "ipc.token";
+
+/// Number of random bytes in a token (hex-encoded on disk and on the wire).
+const TOKEN_BYTES: usize = 32;
+
+/// Full path of the token file, or `None` if no config directory exists.
+pub fn token_path() -> Option<PathBuf> {
+    crate::settings::config_dir().map(|d| d.join(TOKEN_FILE))
+}
+
+/// What a client needs to reach the running daemon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    /// Secret to present in [`super::UiMsg::Hello`].
+    pub token: String,
+    /// The endpoint the daemon listens on, as
+    /// [`Transport::describe`](super::transport::Transport::describe) names
+    /// it.
+    pub endpoint: String,
+}
+
+impl Session {
+    /// The file's contents: the token and the endpoint, one per line.
+    fn to_text(&self) -> String {
+        format!("{}\n{}\n", self.token, self.endpoint)
+    }
+
+    /// Parse [`to_text`](Self::to_text) output; `None` if either line is
+    /// missing.
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines().map(str::trim);
+        let token = lines.next().filter(|t| !t.is_empty())?;
+        let endpoint = lines.next().filter(|e| !e.is_empty())?;
+        Some(Self {
+            token: token.to_string(),
+            endpoint: endpoint.to_string(),
+        })
+    }
+}
+
+/// A fresh random token, hex-encoded.
+fn generate_token() -> io::Result<String> {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    fill_random(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Generate a fresh token and publish it to [`token_path`] with the
+/// `endpoint` the daemon bound, replacing any left by a previous session.
+pub fn create_token(endpoint: &str) -> io::Result<String> {
+    let session = Session {
+        token: generate_token()?,
+        endpoint: endpoint.to_string(),
+    };
+    write_session(&session)?;
+    Ok(session.token)
+}
+
+/// Publish `session` to [`token_path`].
+fn write_session(session: &Session) -> io::Result<()> {
+    let path = token_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    // Write a private temporary file and rename it into place so a client
+    // never reads a half-written token.
+    let tmp = path.with_extension("token.tmp");
+    let _ = fs::remove_file(&tmp);
+    {
+        let mut file = private_file(&tmp)?;
+        file.write_all(session.to_text().as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, &path)
+}
+
+/// Read the running daemon's token and endpoint.
+pub fn read_session() -> io::Result<Session> {
+    let path = token_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    Session::parse(&fs::read_to_string(path)?).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the IPC token file names no endpoint",
+        )
+    })
+}
+
+/// Compare a presented token against the expected one in constant time.
+pub fn verify(expected: &str, presented: &str) -> bool {
+    let (a, b) = (expected.as_bytes(), presented.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ── Platform helpers ─────────────────────────────────────────────────────────
+
+fn private_file(path: &std::path::Path) -> io::Result<fs::File> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::FromRawHandle;
+    use windows::Win32::Storage::FileSystem::{
+        CREATE_ALWAYS, CreateFileW, FILE_ATTRIBUTE_NORMAL, FILE_GENERIC_WRITE, FILE_SHARE_NONE,
+    };
+    use windows::core::PCWSTR;
+
+    let name: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let sd = super::security::UserOnly::new()?;
+    let handle = unsafe {
+        CreateFileW(
+            PCWSTR(name.as_ptr()),
+            FILE_GENERIC_WRITE.0,
+            FILE_SHARE_NONE,
+            Some(&sd.attributes()),
+            CREATE_ALWAYS,
+            FILE_ATTRIBUTE_NORMAL,
+            None,
+        )
+    }
+    .map_err(io::Error::other)?;
+    Ok(unsafe { fs::File::from_raw_handle(handle.0) })
+}
+
+fn fill_random(buf: &mut [u8]) -> io::Result<()> {
+    use windows::Win32::Security::Cryptography::{
+        BCRYPT_USE_SYSTEM_PREFERRED_RNG, BCryptGenRandom,
+    };
+    unsafe { BCryptGenRandom(None, buf, BCRYPT_USE_SYSTEM_PREFERRED_RNG) }
+        .ok()
+        .map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(token: &str, endpoint: &str) -> Session {
+        Session {
+            token: token.into(),
+            endpoint: endpoint.into(),
+        }
+    }
+
+    #[test]
+    fn verify_accepts_the_same_token() {
+        assert!(verify("3fa9c0", "3fa9c0"));
+        assert!(verify("", ""));
+    }
+
+    #[test]
+    fn verify_refuses_a_same_length_mismatch() {
+        assert!(!verify("3fa9c0", "3fa9c1"));
+        assert!(!verify("3fa9c0", "0fa9c0"));
+        assert!(!verify("3fa9c0", "3FA9C0"));
+    }
+
+    #[test]
+    fn verify_refuses_a_length_mismatch() {
+        assert!(!verify("3fa9c0", "3fa9c"));
+        assert!(!verify("3fa9c0", "3fa9c00"));
+        assert!(!verify("3fa9c0", ""));
+        assert!(!verify("", "3fa9c0"));
+    }
+
+    #[test]
+    fn generated_tokens_are_hex_of_the_full_length() {
+        let token = generate_token().unwrap();
+        assert_eq!(token.len(), 2 * TOKEN_BYTES);
+        assert!(
+            token
+                .bytes()
+                .all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase())
+        );
+        assert_ne!(token, generate_token().unwrap());
+    }
+
+    #[test]
+    fn session_round_trips_through_its_text() {
+        let s = session("00ff", r"\\.\pipe\oled-care-S-1-5-21-1");
+        assert_eq!(Session::parse(&s.to_text()), Some(s));
+    }
+
+    #[test]
+    fn session_without_an_endpoint_is_refused() {
+        // A token file from a build that did not record the endpoint.
+        assert_eq!(Session::parse("00ff"), None);
+        assert_eq!(Session::parse("00ff\n\n"), None);
+        assert_eq!(Session::parse(""), None);
+    }
+
+    #[test]
+    fn session_ignores_surrounding_whitespace() {
+        assert_eq!(
+            Session::parse("00ff\r\ntcp://127.0.0.1:17432\r\n"),
+            Some(session("00ff", "tcp://127.0.0.1:17432"))
+        );
+    }
+}
@@ -0,0 +1,656 @@
+//! Inter-process communication between the daemon and the UI process.
+//!
+//! Protocol: every message is prefixed with a 4-byte little-endian `u32`
+//! giving the length of the JSON body that follows.  Both sides use
+//! [`write_msg`] / [`read_frame`] for all communication.  The byte stream
+//! itself comes from one of the [`transport`]s (a per-user named pipe, with
+//! loopback TCP as a fallback).
+//!
+//! Clients send [`Request`]s, each tagged with a client-chosen id.  The
+//! daemon answers every request with a [`DaemonFrame::Reply`] carrying the
+//! same id.  After [`UiMsg::Subscribe`] the daemon additionally pushes
+//! [`DaemonFrame::Event`]s whenever something changes, so replies and events
+//! interleave on the same stream and must be told apart by the frame kind.
+//!
+//! Clients must open with [`UiMsg::Hello`] carrying the session token from
+//! [`auth`]; the daemon answers with its [`PROTOCOL_VERSION`] and capability
+//! list and closes the connection if the versions differ or the token is
+//! wrong.  Commands the daemon cannot decode or refuses to apply
+//! are answered with [`DaemonMsg::Error`] instead of dropping the connection,
+//! and clients skip frames they cannot decode.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::apps::{AppAction, AppRule};
+use crate::idle::IdleSettings;
+use crate::monitor::{MonitorId, MonitorInfo};
+use crate::overlay::{FadeCurve, Fill, MonitorSettings, OrbitSettings, Region, RevealSettings};
+use crate::schedule::ScheduleRule;
+use crate::stats::StatsReport;
+
+pub mod auth;
+mod security;
+pub mod transport;
+
+pub use transport::{Connection, Listener, bind_daemon, connect_to_daemon};
+
+/// TCP port of the fallback transport.  Must not conflict with other local
+/// services; chosen to be well outside the ephemeral-port range.
+pub const DAEMON_PORT: u16 = 17432;
+
+/// Wire-protocol version.  Bump on any incompatible change to the messages
+/// below; additive changes should add a [`CAPABILITIES`] entry instead.
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// Optional features this build of the daemon supports, reported in the
+/// [`DaemonMsg::Hello`] reply.
+pub const CAPABILITIES: &[&str] = &[
+    "subscribe",
+    "monitor-settings",
+    "schedules",
+    "idle",
+    "regions",
+    "reserved-areas",
+    "fade-curves",
+    "cursor-reveal",
+    "focus-follow",
+    "app-rules",
+    "fullscreen-pause",
+    "pixel-orbit",
+    "fills",
+    "stats",
+    "heatmap",
+    "ui-sessions",
+    "set-selection",
+    "region-died",
+];
+
+/// Upper bound on a single message body.
+const MAX_MSG_LEN: usize = 4 * 1024 * 1024;
+
+// ── Shared state snapshot ────────────────────────────────────────────────────
+
+/// Full application state as reported by the daemon to the UI.
+///
+/// Sent on every command response, and pushed to subscribed clients whenever
+/// it changes, so the UI always has an up-to-date view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonState {
+    /// Currently connected monitors (refreshed on display changes).
+    pub monitors: Vec<MonitorInfo>,
+    /// Ids of the selected monitors.  May include monitors that are not in
+    /// `monitors` because they are currently disconnected.
+    pub selected: BTreeSet<MonitorId>,
+    /// Current global overlay opacity (0 = transparent, 255 = opaque).
+    pub opacity: u8,
+    /// Per-monitor overlay behaviour.  Monitors without an entry use
+    /// [`MonitorSettings::default`], i.e. the global opacity.
+    pub monitor_settings: BTreeMap<MonitorId, MonitorSettings>,
+    /// Whether overlay protection is currently active.
+    pub overlays_active: bool,
+    /// Ids of the monitors whose overlay window has been fully created
+    /// (HWND registered).
+    pub overlay_alive: BTreeSet<MonitorId>,
+    /// Opacity each connected monitor's overlays show while not hovered:
+    /// its own or the global opacity, overridden by an application rule, a
+    /// schedule or idle dimming.
+    #[serde(default)]
+    pub effective_opacity: BTreeMap<MonitorId, u8>,
+    /// Time-of-day rules, in priority order.
+    #[serde(default)]
+    pub schedules: Vec<ScheduleRule>,
+    /// Monitors the open schedule windows protect, with their opacity.
+    #[serde(default)]
+    pub scheduled: BTreeMap<MonitorId, u8>,
+    /// Idle dimming thresholds.
+    pub idle: IdleSettings,
+    /// Whether the user is currently idle (overlays dimmed to
+    /// `idle.opacity`).
+    pub user_idle: bool,
+    /// Per-application rules, in priority order.
+    #[serde(default)]
+    pub app_rules: Vec<AppRule>,
+    /// Action of the application rule matching the foreground window, if
+    /// any.
+    #[serde(default)]
+    pub app_action: Option<AppAction>,
+    /// Pause a monitor's overlays while a fullscreen application covers it.
+    #[serde(default)]
+    pub pause_on_fullscreen: bool,
+    /// Monitors whose overlays are paused for a fullscreen application.
+    #[serde(default)]
+    pub paused: BTreeSet<MonitorId>,
+    /// Whether the screen is sampled for static content.
+    #[serde(default)]
+    pub analyze_content: bool,
+}
+
+// ── Messages: UI → Daemon ────────────────────────────────────────────────────
+
+/// A command from a client, tagged with an id the matching reply echoes.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Request {
+    /// Client-chosen correlation id.
+    pub id: u64,
+    /// The command itself.
+    pub msg: UiMsg,
+}
+
+/// Commands sent from the UI process to the daemon.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum UiMsg {
+    /// Handshake (required first message after connecting).  Answered with
+    /// [`DaemonMsg::Hello`], or [`ErrorCode::Unauthorized`] if `token` is
+    /// wrong.
+    Hello {
+        /// Client's [`PROTOCOL_VERSION`].
+        protocol: u32,
+        /// Client's [`CAPABILITIES`].
+        capabilities: Vec<String>,
+        /// Session secret read from the daemon's token file.
+        #[serde(default)]
+        token: String,
+        /// What kind of client this connection is.
+        #[serde(default)]
+        client: ClientKind,
+    },
+    /// Request the full current state.
+    GetState,
+    /// Start receiving [`DaemonFrame::Event`]s on this connection.  Replied
+    /// to with the current state.
+    Subscribe,
+    /// Set the global overlay opacity.
+    SetOpacity(u8),
+    /// Override one monitor's opacity (`None` = follow the global opacity).
+    SetMonitorOpacity(MonitorId, Option<u8>),
+    /// Enable or disable fade-on-hover for one monitor.
+    SetFadeOnHover(MonitorId, bool),
+    /// Set how long one fade takes on a monitor, in milliseconds.
+    SetFadeDuration(MonitorId, u32),
+    /// Set the shape of a monitor's fades.
+    SetFadeCurve(MonitorId, FadeCurve),
+    /// Set the opacity a monitor's overlay fades to while hovered.
+    SetHoverOpacity(MonitorId, u8),
+    /// Reveal only an area around the cursor while hovering a monitor
+    /// (`None` = fade the whole overlay).
+    SetReveal(MonitorId, Option<RevealSettings>),
+    /// Keep the foreground window bright and dim the rest of a monitor.
+    SetFocusFollow(MonitorId, bool),
+    /// Toggle a monitor's selection by its stable id.  Refused while
+    /// protection is active.
+    ToggleMonitor(MonitorId),
+    /// Select exactly these connected monitors (disconnected ones keep their
+    /// selection).  Allowed while protection is active: newly selected
+    /// monitors are covered before deselected ones are uncovered.
+    SetSelection(BTreeSet<MonitorId>),
+    /// Enable (`true`) or disable (`false`) overlay protection.
+    SetActive(bool),
+    /// Add a region to a monitor, or replace the one with the same name.
+    /// A monitor with regions is only covered inside them.
+    SetRegion(MonitorId, Region),
+    /// Remove a monitor's region by name.
+    RemoveRegion(MonitorId, String),
+    /// Cover only the taskbar / app-bar areas of a monitor (`true`), or
+    /// return to its regions or full coverage (`false`).
+    SetReservedOnly(MonitorId, bool),
+    /// Drift a faint pattern across a monitor (`None` = no pixel orbit).
+    SetOrbit(MonitorId, Option<OrbitSettings>),
+    /// Set the colour, gradient or vignette a monitor's overlays are filled
+    /// with.
+    SetFill(MonitorId, Fill),
+    /// Enable or disable idle dimming.
+    SetIdleEnabled(bool),
+    /// Set the seconds without input before the overlays dim (at least 1).
+    SetIdleTimeout(u32),
+    /// Set the overlay opacity while idle (255 = fully black).
+    SetIdleOpacity(u8),
+    /// Enable or disable pausing overlays under fullscreen applications.
+    SetPauseOnFullscreen(bool),
+    /// Enable or disable static-content analysis.  Disabling forgets what
+    /// was observed.
+    SetContentAnalysis(bool),
+    /// List the time-of-day rules.  Answered with [`DaemonMsg::Schedules`].
+    ListSchedules,
+    /// Append a time-of-day rule (its `id` is assigned by the daemon).
+    AddSchedule(ScheduleRule),
+    /// Remove the time-of-day rule with this id.
+    RemoveSchedule(u32),
+    /// List the application rules.  Answered with [`DaemonMsg::AppRules`].
+    ListAppRules,
+    /// Append an application rule (its `id` is assigned by the daemon).
+    AddAppRule(AppRule),
+    /// Remove the application rule with this id.
+    RemoveAppRule(u32),
+    /// Report how long each monitor spent protected and unprotected.
+    /// Answered with [`DaemonMsg::Stats`].
+    GetStats,
+    /// Suggest regions over the static content seen so far.  Answered with
+    /// [`DaemonMsg::RegionSuggestions`].
+    GetRegionSuggestions,
+    /// Sent by a second instance of the executable to ask the daemon to open
+    /// a new UI window.  The sending process exits after this.
+    ShowUi,
+    /// Shut down the entire application (daemon + all overlays).
+    Quit,
+}
+
+// ── Messages: Daemon → UI ────────────────────────────────────────────────────
+
+/// Frames sent from the daemon to a client.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum DaemonFrame {
+    /// Answer to the [`Request`] with the same `id`.
+    Reply { id: u64, msg: DaemonMsg },
+    /// Unsolicited notification (only sent to subscribed connections).
+    Event(DaemonMsg),
+}
+
+/// Payloads sent from the daemon to the UI, either as replies or as events.
+///
+/// The daemon always replies with the full current state after every command,
+/// and subscribed clients are pushed changes as they happen, so the UI never
+/// needs a separate "poll" mechanism.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DaemonMsg {
+    /// Reply to [`UiMsg::Hello`].
+    Hello {
+        /// Daemon's [`PROTOCOL_VERSION`].
+        protocol: u32,
+        /// Daemon's [`CAPABILITIES`].
+        capabilities: Vec<String>,
+        /// Package version of the daemon binary, for error messages.
+        version: String,
+    },
+    /// Reply: the command could not be decoded or was refused.
+    Error { code: ErrorCode, message: String },
+    /// Reply: the full current state.
+    State(DaemonState),
+    /// Reply to [`UiMsg::ListSchedules`], in priority order.
+    Schedules(Vec<ScheduleRule>),
+    /// Reply to [`UiMsg::ListAppRules`], in priority order.
+    AppRules(Vec<AppRule>),
+    /// Reply to [`UiMsg::GetStats`].
+    Stats(StatsReport),
+    /// Reply to [`UiMsg::GetRegionSuggestions`]: regions to add with
+    /// [`UiMsg::SetRegion`], for each monitor observed long enough.
+    RegionSuggestions(BTreeMap<MonitorId, Vec<Region>>),
+    /// Event: the state changed (protection toggled, opacity, selection, …).
+    StateChanged(DaemonState),
+    /// Event: monitors were attached, detached or rearranged.  Always
+    /// followed by a [`DaemonMsg::StateChanged`].
+    TopologyChanged(Vec<MonitorInfo>),
+    /// Event: the overlay window covering a whole monitor exited
+    /// unexpectedly.
+    OverlayDied(MonitorId),
+    /// Event: one region, reserved-area strip or pixel-orbit overlay on a
+    /// monitor exited unexpectedly; the monitor's other overlays are still
+    /// up.
+    RegionDied(MonitorId, String),
+}
+
+/// What kind of client a connection is, declared in [`UiMsg::Hello`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClientKind {
+    /// A settings window.  While one is connected the daemon does not open
+    /// another.
+    Ui,
+    /// Anything else: the CLI, a second launcher instance, scripts.
+    #[default]
+    Other,
+}
+
+/// Machine-readable reason carried by [`DaemonMsg::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorCode {
+    /// The request was not valid JSON or named a command this daemon does
+    /// not know (typically a newer client).
+    UnknownCommand,
+    /// The command was understood but its arguments are invalid in the
+    /// current state (unknown monitor, nothing selected, …).
+    InvalidArgument,
+    /// The connection did not open with a [`UiMsg::Hello`] carrying the
+    /// session token.
+    Unauthorized,
+}
+
+/// The [`UiMsg::Hello`] this build sends as a `client`, presenting `token`.
+pub fn hello(token: String, client: ClientKind) -> UiMsg {
+    UiMsg::Hello {
+        protocol: PROTOCOL_VERSION,
+        capabilities: CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+        token,
+        client,
+    }
+}
+
+/// Decode a request body.
+///
+/// On failure returns the request id (if one could be recovered, else 0)
+/// together with a description, so the daemon can still answer with a
+/// correlated [`DaemonMsg::Error`].
+pub fn decode_request(body: &[u8]) -> Result<Request, (u64, String)> {
+    let value: serde_json::Value =
+        serde_json::from_slice(body).map_err(|e| (0, format!("malformed request: {}", e)))?;
+    let id = value.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
+    serde_json::from_value(value).map_err(|e| (id, format!("unknown command: {}", e)))
+}
+
+// ── Wire helpers ─────────────────────────────────────────────────────────────
+
+/// Write a message to `w` using the 4-byte-length-prefix + JSON format.
+pub fn write_msg<W: Write, T: Serialize>(w: &mut BufWriter<W>, msg: &T) -> io::Result<()> {
+    let body =
+        serde_json::to_vec(msg).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let len = (body.len() as u32).to_le_bytes();
+    w.write_all(&len)?;
+    w.write_all(&body)?;
+    w.flush()
+}
+
+/// Read one raw message body from `r` (4-byte length prefix stripped).
+///
+/// Lets the caller decide what to do with a body it cannot decode instead of
+/// treating it as a broken connection.
+pub fn read_frame<R: Read>(r: &mut BufReader<R>) -> io::Result<Vec<u8>> {
+    let mut len_buf = [0u8; 4];
+    r.read_exact(&mut len_buf)?;
+    let len = u32::from_le_bytes(len_buf) as usize;
+    // Guard against malformed/huge messages.
+    if len > MAX_MSG_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "IPC message too large",
+        ));
+    }
+    let mut body = vec![0u8; len];
+    r.read_exact(&mut body)?;
+    Ok(body)
+}
+
+/// Send `msg` as request `id` and wait for its reply, discarding any events
+/// (and undecodable frames from a newer daemon) that arrive in between.
+///
+/// Intended for simple one-shot clients; long-lived clients that subscribe
+/// should read frames themselves.
+pub fn request<R: Read, W: Write>(
+    reader: &mut BufReader<R>,
+    writer: &mut BufWriter<W>,
+    id: u64,
+    msg: UiMsg,
+) -> io::Result<DaemonMsg> {
+    write_msg(writer, &Request { id, msg })?;
+    loop {
+        let body = read_frame(reader)?;
+        match serde_json::from_slice::<DaemonFrame>(&body) {
+            Ok(DaemonFrame::Reply { id: reply_id, msg }) if reply_id == id => return Ok(msg),
+            Ok(_) => continue,
+            Err(e) => {
+                // Only fatal if it was *our* reply that could not be decoded.
+                let value: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+                let reply_id = value.pointer("/Reply/id").and_then(|v| v.as_u64());
+                if reply_id == Some(id) {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+                }
+            }
+        }
+    }
+}
+
+/// Perform the [`UiMsg::Hello`] handshake as request `id` for a `client`,
+/// presenting the token from [`auth::read_session`].
+///
+/// Returns the daemon's package version on success, or an error describing
+/// why the daemon cannot be used: a missing token, a rejected token, or a
+/// version mismatch (including daemons too old to understand `Hello`, which
+/// answer with an error or simply close the connection).
+pub fn handshake<R: Read, W: Write>(
+    reader: &mut BufReader<R>,
+    writer: &mut BufWriter<W>,
+    id: u64,
+    client: ClientKind,
+) -> Result<String, String> {
+    let token = auth::read_session()
+        .map_err(|e| format!("cannot read the IPC token: {}", e))?
+        .token;
+    handshake_with(reader, writer, id, client, token)
+}
+
+/// [`handshake`] presenting `token`.
+fn handshake_with<R: Read, W: Write>(
+    reader: &mut BufReader<R>,
+    writer: &mut BufWriter<W>,
+    id: u64,
+    client: ClientKind,
+    token: String,
+) -> Result<String, String> {
+    match request(reader, writer, id, hello(token, client)) {
+        Ok(DaemonMsg::Hello {
+            protocol, version, ..
+        }) if protocol == PROTOCOL_VERSION => Ok(version),
+        Ok(DaemonMsg::Hello {
+            protocol, version, ..
+        }) => Err(format!(
+            "the running daemon is version {} (protocol {}), this is version {} (protocol {})",
+            version,
+            protocol,
+            env!("CARGO_PKG_VERSION"),
+            PROTOCOL_VERSION
+        )),
+        Ok(DaemonMsg::Error {
+            code: ErrorCode::Unauthorized,
+            message,
+        }) => Err(format!("the daemon refused the connection: {}", message)),
+        Ok(DaemonMsg::Error { message, .. }) => Err(format!(
+            "the running daemon does not support this version ({})",
+            message
+        )),
+        Ok(other) => Err(format!("unexpected handshake reply: {:?}", other)),
+        Err(e) => Err(format!(
+            "the running daemon closed the connection during the handshake \
+             (probably an incompatible version): {}",
+            e
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// A length-prefixed frame with `body`.
+    fn raw(body: &[u8]) -> Vec<u8> {
+        let mut frame = (body.len() as u32).to_le_bytes().to_vec();
+        frame.extend_from_slice(body);
+        frame
+    }
+
+    /// A reader that yields `frames`, then end of stream.
+    fn daemon(frames: &[Vec<u8>]) -> BufReader<Cursor<Vec<u8>>> {
+        BufReader::new(Cursor::new(frames.concat()))
+    }
+
+    fn frame(f: &DaemonFrame) -> Vec<u8> {
+        raw(&serde_json::to_vec(f).unwrap())
+    }
+
+    fn reply(id: u64, msg: DaemonMsg) -> Vec<u8> {
+        frame(&DaemonFrame::Reply { id, msg })
+    }
+
+    fn daemon_hello(protocol: u32) -> DaemonMsg {
+        DaemonMsg::Hello {
+            protocol,
+            capabilities: vec!["subscribe".into()],
+            version: "9.9.9".into(),
+        }
+    }
+
+    /// Run the handshake against a daemon answering with `frames`.
+    fn handshake_against(frames: &[Vec<u8>]) -> Result<String, String> {
+        let mut reader = daemon(frames);
+        let mut writer = BufWriter::new(Vec::new());
+        handshake_with(&mut reader, &mut writer, 0, ClientKind::Ui, "secret".into())
+    }
+
+    // ── Requests ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn request_decodes() {
+        let req = decode_request(br#"{"id": 5, "msg": {"SetOpacity": 42}}"#).unwrap();
+        assert_eq!(req.id, 5);
+        assert!(matches!(req.msg, UiMsg::SetOpacity(42)));
+    }
+
+    #[test]
+    fn malformed_request_recovers_id_zero() {
+        let (id, message) = decode_request(b"{\"id\": 5, \"msg\"").unwrap_err();
+        assert_eq!(id, 0);
+        assert!(message.starts_with("malformed request"), "{}", message);
+    }
+
+    #[test]
+    fn unknown_command_keeps_its_id() {
+        let (id, message) = decode_request(br#"{"id": 7, "msg": "Teleport"}"#).unwrap_err();
+        assert_eq!(id, 7);
+        assert!(message.starts_with("unknown command"), "{}", message);
+
+        // Known command, arguments of a newer shape.
+        let (id, _) = decode_request(br#"{"id": 8, "msg": {"SetOpacity": "max"}}"#).unwrap_err();
+        assert_eq!(id, 8);
+    }
+
+    // ── Frames ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn frames_round_trip() {
+        let mut writer = BufWriter::new(Vec::new());
+        write_msg(
+            &mut writer,
+            &Request {
+                id: 3,
+                msg: UiMsg::GetState,
+            },
+        )
+        .unwrap();
+        let mut reader = BufReader::new(Cursor::new(writer.into_inner().unwrap()));
+        let req = decode_request(&read_frame(&mut reader).unwrap()).unwrap();
+        assert_eq!(req.id, 3);
+        assert!(matches!(req.msg, UiMsg::GetState));
+    }
+
+    #[test]
+    fn oversized_frame_is_refused() {
+        let len = (MAX_MSG_LEN as u32 + 1).to_le_bytes();
+        let mut reader = BufReader::new(Cursor::new(len.to_vec()));
+        let err = read_frame(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn request_skips_events_other_replies_and_undecodable_frames() {
+        let mut reader = daemon(&[
+            frame(&DaemonFrame::Event(DaemonMsg::OverlayDied(MonitorId(
+                "A".into(),
+            )))),
+            reply(6, DaemonMsg::Schedules(Vec::new())),
+            raw(br#"{"Event": "FromTheFuture"}"#),
+            raw(br#"{"Reply": {"id": 9, "msg": "FromTheFuture"}}"#),
+            reply(5, DaemonMsg::AppRules(Vec::new())),
+        ]);
+        let mut writer = BufWriter::new(Vec::new());
+        let msg = request(&mut reader, &mut writer, 5, UiMsg::ListAppRules).unwrap();
+        assert!(matches!(msg, DaemonMsg::AppRules(rules) if rules.is_empty()));
+    }
+
+    #[test]
+    fn request_fails_when_its_own_reply_is_undecodable() {
+        let mut reader = daemon(&[raw(br#"{"Reply": {"id": 5, "msg": "FromTheFuture"}}"#)]);
+        let mut writer = BufWriter::new(Vec::new());
+        let err = request(&mut reader, &mut writer, 5, UiMsg::GetState).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    // ── Handshake ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn handshake_presents_the_token() {
+        let mut reader = daemon(&[reply(0, daemon_hello(PROTOCOL_VERSION))]);
+        let mut writer = BufWriter::new(Vec::new());
+        let version =
+            handshake_with(&mut reader, &mut writer, 0, ClientKind::Ui, "secret".into()).unwrap();
+        assert_eq!(version, "9.9.9");
+
+        let sent = writer.into_inner().unwrap();
+        let req = decode_request(&read_frame(&mut BufReader::new(Cursor::new(sent))).unwrap());
+        match req.unwrap().msg {
+            UiMsg::Hello {
+                protocol,
+                token,
+                client,
+                ..
+            } => {
+                assert_eq!(protocol, PROTOCOL_VERSION);
+                assert_eq!(token, "secret");
+                assert_eq!(client, ClientKind::Ui);
+            }
+            other => panic!("sent {:?}", other),
+        }
+    }
+
+    #[test]
+    fn handshake_skips_events_before_the_reply() {
+        let result = handshake_against(&[
+            frame(&DaemonFrame::Event(DaemonMsg::OverlayDied(MonitorId(
+                "A".into(),
+            )))),
+            reply(0, daemon_hello(PROTOCOL_VERSION)),
+        ]);
+        assert_eq!(result, Ok("9.9.9".into()));
+    }
+
+    #[test]
+    fn handshake_reports_the_daemon_version_on_mismatch() {
+        let err = handshake_against(&[reply(0, daemon_hello(PROTOCOL_VERSION + 1))]).unwrap_err();
+        let expected = format!(
+            "the running daemon is version 9.9.9 (protocol {}), this is version {} (protocol {})",
+            PROTOCOL_VERSION + 1,
+            env!("CARGO_PKG_VERSION"),
+            PROTOCOL_VERSION
+        );
+        assert_eq!(err, expected);
+    }
+
+    #[test]
+    fn handshake_reports_a_refused_token() {
+        let err = handshake_against(&[reply(
+            0,
+            DaemonMsg::Error {
+                code: ErrorCode::Unauthorized,
+                message: "wrong token".into(),
+            },
+        )])
+        .unwrap_err();
+        assert_eq!(err, "the daemon refused the connection: wrong token");
+    }
+
+    #[test]
+    fn handshake_reports_a_daemon_without_hello() {
+        let err = handshake_against(&[reply(
+            0,
+            DaemonMsg::Error {
+                code: ErrorCode::UnknownCommand,
+                message: "unknown command: Hello".into(),
+            },
+        )])
+        .unwrap_err();
+        assert!(err.contains("does not support this version"), "{}", err);
+
+        // A daemon that just hangs up.
+        let err = handshake_against(&[]).unwrap_err();
+        assert!(err.contains("closed the connection"), "{}", err);
+    }
+}
@@ -0,0 +1,136 @@
+//! Windows access control for the IPC endpoint and the token file.
+//!
+//! The named pipe and the token file are both created with a protected DACL
+//! that grants the current user, and nobody else, access, instead of
+//! inheriting whatever their parent allows.  Pipe names are machine-global,
+//! so clients additionally check that the process serving the pipe runs as
+//! their own user before sending it anything.
+
+use std::ffi::c_void;
+use std::io;
+use std::mem;
+
+use windows::Win32::Foundation::{CloseHandle, HANDLE, HLOCAL, LocalFree};
+use windows::Win32::Security::Authorization::{
+    ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+};
+use windows::Win32::Security::{
+    EqualSid, GetTokenInformation, PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES, TOKEN_QUERY,
+    TOKEN_USER, TokenUser,
+};
+use windows::Win32::System::Threading::{
+    GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows::core::{PCWSTR, PWSTR};
+
+/// The user account a process runs as.
+pub struct UserSid {
+    /// `TOKEN_USER` followed by the SID it points into.  `u64`s keep the
+    /// structure aligned.
+    buf: Vec<u64>,
+}
+
+impl UserSid {
+    fn psid(&self) -> windows::Win32::Security::PSID {
+        unsafe { (*(self.buf.as_ptr() as *const TOKEN_USER)).User.Sid }
+    }
+
+    /// The SID in its `S-1-5-21-…` string form.
+    pub fn to_sddl(&self) -> io::Result<String> {
+        unsafe {
+            let mut text = PWSTR::null();
+            ConvertSidToStringSidW(self.psid(), &mut text).map_err(io::Error::other)?;
+            let sid = text.to_string().map_err(io::Error::other);
+            let _ = LocalFree(Some(HLOCAL(text.0 as *mut c_void)));
+            sid
+        }
+    }
+}
+
+impl PartialEq for UserSid {
+    fn eq(&self, other: &Self) -> bool {
+        unsafe { EqualSid(self.psid(), other.psid()).is_ok() }
+    }
+}
+
+/// The user the token of `process` belongs to.
+fn token_user(process: HANDLE) -> io::Result<UserSid> {
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(process, TOKEN_QUERY, &mut token).map_err(io::Error::other)?;
+        let mut len = 0u32;
+        // Fails with ERROR_INSUFFICIENT_BUFFER, reporting the size needed.
+        let _ = GetTokenInformation(token, TokenUser, None, 0, &mut len);
+        let mut buf = vec![0u64; (len as usize).div_ceil(mem::size_of::<u64>())];
+        let filled = GetTokenInformation(
+            token,
+            TokenUser,
+            Some(buf.as_mut_ptr().cast()),
+            len,
+            &mut len,
+        );
+        let _ = CloseHandle(token);
+        filled.map_err(io::Error::other)?;
+        Ok(UserSid { buf })
+    }
+}
+
+/// The user this process runs as.
+pub fn current_user() -> io::Result<UserSid> {
+    token_user(unsafe { GetCurrentProcess() })
+}
+
+/// The user process `pid` runs as.
+pub fn process_user(pid: u32) -> io::Result<UserSid> {
+    unsafe {
+        let process =
+            OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).map_err(io::Error::other)?;
+        let user = token_user(process);
+        let _ = CloseHandle(process);
+        user
+    }
+}
+
+/// A security descriptor whose protected DACL grants full access to the
+/// current user only.
+pub struct UserOnly(PSECURITY_DESCRIPTOR);
+
+// SAFETY: the descriptor is an immutable LocalAlloc block, usable from any
+// thread.
+unsafe impl Send for UserOnly {}
+
+impl UserOnly {
+    pub fn new() -> io::Result<Self> {
+        let sddl = format!("D:P(A;;GA;;;{})", current_user()?.to_sddl()?);
+        let sddl: Vec<u16> = sddl.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut sd = PSECURITY_DESCRIPTOR::default();
+        unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                PCWSTR(sddl.as_ptr()),
+                SDDL_REVISION_1,
+                &mut sd,
+                None,
+            )
+            .map_err(io::Error::other)?;
+        }
+        Ok(Self(sd))
+    }
+
+    /// Attributes for `CreateNamedPipeW` / `CreateFileW`.  Only valid while
+    /// `self` is alive.
+    pub fn attributes(&self) -> SECURITY_ATTRIBUTES {
+        SECURITY_ATTRIBUTES {
+            nLength: mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: self.0.0,
+            bInheritHandle: false.into(),
+        }
+    }
+}
+
+impl Drop for UserOnly {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = LocalFree(Some(HLOCAL(self.0.0)));
+        }
+    }
+}
@@ -0,0 +1,454 @@
+//! Byte-stream transports that carry the IPC protocol.
+//!
+//! The daemon prefers a per-user named pipe that other users and
+//! browser-reachable processes cannot open.  The loopback TCP port
+//! [`DAEMON_PORT`] is kept as a fallback for systems where the pipe cannot
+//! be created.  Clients connect only to the endpoint the daemon recorded
+//! next to its token ([`super::auth`]), never to the next transport in line:
+//! any local process may hold the TCP port, and must not receive the token.
+//!
+//! Every transport hands out boxed [`IpcStream`]s, so the framing in
+//! [`super`] and the daemon's connection handling are transport-agnostic.
+
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use super::{DAEMON_PORT, auth};
+
+/// A connected, bidirectional byte stream.
+pub trait IpcStream: Read + Write + Send {
+    /// A second handle to the same stream, so reads and writes can happen on
+    /// different threads.
+    fn try_clone(&self) -> io::Result<Connection>;
+}
+
+/// An [`IpcStream`] of any transport.
+pub type Connection = Box<dyn IpcStream>;
+
+/// Server side of a transport.
+pub trait Listener: Send {
+    /// Block until the next client connects.
+    fn accept(&self) -> io::Result<Connection>;
+}
+
+/// One way for clients to reach the daemon.
+pub trait Transport {
+    /// Human-readable endpoint, for logs.
+    fn describe(&self) -> String;
+
+    /// Start listening.  Fails with [`io::ErrorKind::AddrInUse`] if another
+    /// daemon already owns the endpoint.
+    fn bind(&self) -> io::Result<Box<dyn Listener>>;
+
+    /// Connect to a daemon listening on this endpoint (single attempt).
+    fn connect(&self) -> io::Result<Connection>;
+}
+
+/// The transports this build uses, most preferred first.
+pub fn transports() -> Vec<Box<dyn Transport>> {
+    vec![
+        Box::new(pipe::NamedPipeTransport::new(pipe::default_pipe_name())),
+        Box::new(TcpTransport::new(DAEMON_PORT)),
+    ]
+}
+
+/// Bind the first usable transport.
+///
+/// A transport that fails for any reason other than being in use is skipped
+/// in favour of the next one.  If an endpoint is in use another daemon is
+/// running, so no fallback is attempted.
+pub fn bind_daemon() -> io::Result<(Box<dyn Listener>, String)> {
+    let mut last_err = io::Error::new(io::ErrorKind::NotFound, "no IPC transport available");
+    for transport in transports() {
+        match transport.bind() {
+            Ok(listener) => return Ok((listener, transport.describe())),
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => return Err(e),
+            Err(e) => {
+                eprintln!(
+                    "[ipc] Cannot listen on {}: {}; trying next transport",
+                    transport.describe(),
+                    e
+                );
+                last_err = e;
+            }
+        }
+    }
+    Err(last_err)
+}
+
+/// Connect to the endpoint the running daemon recorded in its token file,
+/// retrying until `timeout_ms` elapses (a timeout of 0 makes a single
+/// attempt).
+///
+/// Returns the connection on success, or the last I/O error on timeout.
+pub fn connect_to_daemon(timeout_ms: u64) -> io::Result<Connection> {
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    loop {
+        // Re-read every round: a daemon that is starting up publishes its
+        // endpoint only once it has bound it.
+        let err = match connect_recorded() {
+            Ok(conn) => return Ok(conn),
+            Err(e) => e,
+        };
+        if Instant::now() >= deadline {
+            return Err(err);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// One attempt at the endpoint named in the token file.
+fn connect_recorded() -> io::Result<Connection> {
+    let endpoint = auth::read_session()?.endpoint;
+    let transport = transports()
+        .into_iter()
+        .find(|t| t.describe() == endpoint)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("the daemon listens on an unknown endpoint: {}", endpoint),
+            )
+        })?;
+    transport.connect()
+}
+
+// ── TCP ──────────────────────────────────────────────────────────────────────
+
+/// Loopback TCP on a fixed port.  Reachable by every local process, so only
+/// used when the local endpoint is unavailable.
+pub struct TcpTransport {
+    addr: SocketAddr,
+}
+
+impl TcpTransport {
+    pub fn new(port: u16) -> Self {
+        Self {
+            addr: SocketAddr::from((Ipv4Addr::LOCALHOST, port)),
+        }
+    }
+}
+
+impl Transport for TcpTransport {
+    fn describe(&self) -> String {
+        format!("tcp://{}", self.addr)
+    }
+
+    fn bind(&self) -> io::Result<Box<dyn Listener>> {
+        Ok(Box::new(TcpListener::bind(self.addr)?))
+    }
+
+    fn connect(&self) -> io::Result<Connection> {
+        // A refused loopback connect can take seconds on Windows without an
+        // explicit timeout.
+        let stream = TcpStream::connect_timeout(&self.addr, Duration::from_millis(300))?;
+        let _ = stream.set_nodelay(true);
+        Ok(Box::new(stream))
+    }
+}
+
+impl Listener for TcpListener {
+    fn accept(&self) -> io::Result<Connection> {
+        let (stream, _) = TcpListener::accept(self)?;
+        let _ = stream.set_nodelay(true);
+        Ok(Box::new(stream))
+    }
+}
+
+impl IpcStream for TcpStream {
+    fn try_clone(&self) -> io::Result<Connection> {
+        Ok(Box::new(TcpStream::try_clone(self)?))
+    }
+}
+
+// ── Named pipe ───────────────────────────────────────────────────────────────
+
+mod pipe {
+    use std::io::{self, Read, Write};
+    use std::sync::Mutex;
+
+    use windows::Win32::Foundation::{
+        CloseHandle, DUPLICATE_SAME_ACCESS, DuplicateHandle, ERROR_ACCESS_DENIED,
+        ERROR_BROKEN_PIPE, ERROR_IO_PENDING, ERROR_PIPE_BUSY, ERROR_PIPE_CONNECTED, HANDLE,
+        WIN32_ERROR,
+    };
+    use windows::Win32::Storage::FileSystem::{
+        CreateFileW, FILE_FLAG_FIRST_PIPE_INSTANCE, FILE_FLAG_OVERLAPPED, FILE_GENERIC_READ,
+        FILE_GENERIC_WRITE, FILE_SHARE_NONE, OPEN_EXISTING, PIPE_ACCESS_DUPLEX, ReadFile,
+        SECURITY_IDENTIFICATION, SECURITY_SQOS_PRESENT, WriteFile,
+    };
+    use windows::Win32::System::IO::{GetOverlappedResult, OVERLAPPED};
+    use windows::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, GetNamedPipeServerProcessId, PIPE_READMODE_BYTE,
+        PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+        WaitNamedPipeW,
+    };
+    use windows::Win32::System::Threading::{CreateEventW, GetCurrentProcess};
+    use windows::core::PCWSTR;
+
+    use super::super::security::{self, UserOnly};
+    use super::{Connection, IpcStream, Listener, Transport};
+
+    const BUFFER_SIZE: u32 = 64 * 1024;
+
+    /// `\\.\pipe\oled-care-<user SID>`.  Pipe names are machine-global, so
+    /// the SID keeps two signed-in users' daemons apart; unlike a user name
+    /// it cannot be shared by a local and a domain account.
+    pub fn default_pipe_name() -> String {
+        let user = security::current_user()
+            .and_then(|u| u.to_sddl())
+            .unwrap_or_else(|_| std::env::var("USERNAME").unwrap_or_default());
+        format!(r"\\.\pipe\oled-care-{}", user)
+    }
+
+    /// Owned Win32 handle, closed on drop.
+    struct Handle(HANDLE);
+
+    // SAFETY: kernel handles may be used from any thread.
+    unsafe impl Send for Handle {}
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = CloseHandle(self.0);
+            }
+        }
+    }
+
+    fn io_err(e: windows::core::Error) -> io::Error {
+        io::Error::other(e)
+    }
+
+    /// Whether `e` wraps the Win32 error `code`.
+    fn is_win32(e: &io::Error, code: WIN32_ERROR) -> bool {
+        e.get_ref()
+            .and_then(|inner| inner.downcast_ref::<windows::core::Error>())
+            .is_some_and(|w| w.code() == code.to_hresult())
+    }
+
+    /// Run one overlapped operation on `handle` to completion.
+    ///
+    /// The pipe is opened for overlapped I/O because synchronous handles
+    /// serialise all I/O on the file object: a reader thread blocked in
+    /// `ReadFile` would otherwise stall every write from the writer thread.
+    unsafe fn overlapped_io(
+        handle: HANDLE,
+        start: impl FnOnce(*mut OVERLAPPED) -> windows::core::Result<()>,
+    ) -> io::Result<u32> {
+        unsafe {
+            let event = Handle(CreateEventW(None, true, false, PCWSTR::null()).map_err(io_err)?);
+            let mut ov = OVERLAPPED {
+                hEvent: event.0,
+                ..Default::default()
+            };
+            if let Err(e) = start(&mut ov)
+                && e.code() != ERROR_IO_PENDING.to_hresult()
+            {
+                return Err(io_err(e));
+            }
+            let mut transferred = 0u32;
+            GetOverlappedResult(handle, &ov, &mut transferred, true).map_err(io_err)?;
+            Ok(transferred)
+        }
+    }
+
+    /// Named pipe restricted to local clients of the current user: every
+    /// instance carries a [`UserOnly`] DACL, and clients only talk to a server
+    /// running as their own user (see [`verify_server`]).
+    pub struct NamedPipeTransport {
+        name: String,
+    }
+
+    impl NamedPipeTransport {
+        pub fn new(name: String) -> Self {
+            Self { name }
+        }
+    }
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    /// Refuse a pipe served by a process of another user.
+    ///
+    /// Anyone may create the first instance of a pipe name.  Another user
+    /// who squats ours gets no connections from us, and so never sees the
+    /// token.
+    fn verify_server(pipe: HANDLE) -> io::Result<()> {
+        let mut pid = 0u32;
+        unsafe { GetNamedPipeServerProcessId(pipe, &mut pid) }.map_err(io_err)?;
+        if security::process_user(pid)? != security::current_user()? {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "the IPC pipe is served by another user's process",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Create one server instance of the pipe.
+    fn create_instance(name: &[u16], first: bool, sd: &UserOnly) -> io::Result<Handle> {
+        let mut open_mode = PIPE_ACCESS_DUPLEX | FILE_FLAG_OVERLAPPED;
+        if first {
+            open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+        }
+        let handle = unsafe {
+            CreateNamedPipeW(
+                PCWSTR(name.as_ptr()),
+                open_mode,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                BUFFER_SIZE,
+                BUFFER_SIZE,
+                0,
+                Some(&sd.attributes()),
+            )
+        };
+        if handle.is_invalid() {
+            let err = windows::core::Error::from_thread();
+            // The first instance already exists: another daemon owns the pipe.
+            if first && err.code() == ERROR_ACCESS_DENIED.to_hresult() {
+                return Err(io::Error::new(io::ErrorKind::AddrInUse, err));
+            }
+            return Err(io_err(err));
+        }
+        Ok(Handle(handle))
+    }
+
+    impl Transport for NamedPipeTransport {
+        fn describe(&self) -> String {
+            self.name.clone()
+        }
+
+        fn bind(&self) -> io::Result<Box<dyn Listener>> {
+            let name = wide(&self.name);
+            let sd = UserOnly::new()?;
+            let first = match create_instance(&name, true, &sd) {
+                Ok(h) => h,
+                // Only our own daemon makes this "already running"; a pipe
+                // squatted by someone else sends us to the next transport.
+                Err(e) if e.kind() == io::ErrorKind::AddrInUse && self.connect().is_err() => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "the pipe name is taken by another user's process",
+                    ));
+                }
+                Err(e) => return Err(e),
+            };
+            Ok(Box::new(PipeListener {
+                name,
+                sd,
+                pending: Mutex::new(Some(first)),
+            }))
+        }
+
+        fn connect(&self) -> io::Result<Connection> {
+            let name = wide(&self.name);
+            let open = || unsafe {
+                CreateFileW(
+                    PCWSTR(name.as_ptr()),
+                    (FILE_GENERIC_READ | FILE_GENERIC_WRITE).0,
+                    FILE_SHARE_NONE,
+                    None,
+                    OPEN_EXISTING,
+                    // Never let the server impersonate us.
+                    FILE_FLAG_OVERLAPPED | SECURITY_SQOS_PRESENT | SECURITY_IDENTIFICATION,
+                    None,
+                )
+            };
+            let handle = match open() {
+                Ok(h) => h,
+                Err(e) if e.code() == ERROR_PIPE_BUSY.to_hresult() => {
+                    // Every instance is taken; wait briefly for the next.
+                    unsafe {
+                        let _ = WaitNamedPipeW(PCWSTR(name.as_ptr()), 200);
+                    }
+                    open().map_err(io_err)?
+                }
+                Err(e) => return Err(io_err(e)),
+            };
+            let stream = PipeStream(Handle(handle));
+            verify_server(handle)?;
+            Ok(Box::new(stream))
+        }
+    }
+
+    /// Listens by keeping one unconnected pipe instance ready at all times.
+    struct PipeListener {
+        name: Vec<u16>,
+        /// DACL given to every instance.
+        sd: UserOnly,
+        pending: Mutex<Option<Handle>>,
+    }
+
+    impl Listener for PipeListener {
+        fn accept(&self) -> io::Result<Connection> {
+            let mut pending = self.pending.lock().unwrap();
+            let instance = match pending.take() {
+                Some(h) => h,
+                None => create_instance(&self.name, false, &self.sd)?,
+            };
+
+            let connected =
+                unsafe { overlapped_io(instance.0, |ov| ConnectNamedPipe(instance.0, Some(ov))) };
+            match connected {
+                Ok(_) => {}
+                // The client connected between creation and ConnectNamedPipe.
+                Err(e) if is_win32(&e, ERROR_PIPE_CONNECTED) => {}
+                Err(e) => return Err(e),
+            }
+
+            // Have the next instance ready before handing this one out.
+            *pending = create_instance(&self.name, false, &self.sd).ok();
+            Ok(Box::new(PipeStream(instance)))
+        }
+    }
+
+    /// One end of a connected pipe.
+    struct PipeStream(Handle);
+
+    impl Read for PipeStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let h = self.0.0;
+            let n = unsafe { overlapped_io(h, |ov| ReadFile(h, Some(buf), None, Some(ov))) };
+            match n {
+                Ok(n) => Ok(n as usize),
+                // The other end closed the pipe: end of stream.
+                Err(e) if is_win32(&e, ERROR_BROKEN_PIPE) => Ok(0),
+                Err(e) => Err(e),
+            }
+        }
+    }
+
+    impl Write for PipeStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let h = self.0.0;
+            let n = unsafe { overlapped_io(h, |ov| WriteFile(h, Some(buf), None, Some(ov)))? };
+            Ok(n as usize)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl IpcStream for PipeStream {
+        fn try_clone(&self) -> io::Result<Connection> {
+            let mut dup = HANDLE::default();
+            unsafe {
+                let process = GetCurrentProcess();
+                DuplicateHandle(
+                    process,
+                    self.0.0,
+                    process,
+                    &mut dup,
+                    0,
+                    false,
+                    DUPLICATE_SAME_ACCESS,
+                )
+                .map_err(io_err)?;
+            }
+            Ok(Box::new(PipeStream(Handle(dup))))
+        }
+    }
+}
@@ -1,26 +1,43 @@
+mod apps;
+mod cli;
 mod daemon;
+#[cfg(test)]
+mod fake;
+mod foreground;
+mod fullscreen;
+mod heatmap;
+mod idle;
 mod ipc;
 mod monitor;
 mod overlay;
+mod schedule;
+mod settings;
+mod stats;
 mod tray;
 mod ui;
 
 use std::io::{BufReader, BufWriter};
-use std::net::TcpStream;
 use std::sync::mpsc;
-use std::time::Duration;
 
 use gpui::{AppContext, Application, Bounds, WindowBounds, WindowOptions, px, size};
 use raw_window_handle::RawWindowHandle;
 use windows::Win32::Foundation::HWND;
 
-use crate::ipc::{DaemonMsg, DaemonState, UiMsg, connect_to_daemon};
-use crate::ui::Controller;
+use crate::ipc::{
+    ClientKind, Connection, DaemonFrame, DaemonMsg, DaemonState, Request, UiMsg, connect_to_daemon,
+};
+use crate::ui::{Controller, DaemonUpdate};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
-    if args.iter().any(|a| a == "--ui") {
+    if args
+        .get(1)
+        .is_some_and(|a| cli::COMMANDS.contains(&a.as_str()))
+    {
+        // ── CLI mode: drive the running daemon from the command line ─────
+        std::process::exit(cli::run(&args[1..]));
+    } else if args.iter().any(|a| a == "--ui") {
         // ── UI mode: connect to running daemon and show the window ───────
         run_ui();
     } else {
@@ -28,14 +45,24 @@ fn main() {
         // Try to connect to an already-running daemon.  If one exists, ask
         // it to open a UI window and exit immediately.  Otherwise become
         // the daemon ourselves.
-        match TcpStream::connect_timeout(
-            &format!("127.0.0.1:{}", ipc::DAEMON_PORT).parse().unwrap(),
-            Duration::from_millis(300),
-        ) {
+        match connect_to_daemon(0) {
             Ok(stream) => {
-                // Daemon is running — ask it to open a UI, then exit.
+                // Daemon is running — ask it to open a UI, then exit.  A
+                // daemon from another release cannot be driven, so report
+                // that instead of silently doing nothing.
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
                 let mut writer = BufWriter::new(stream);
-                let _ = ipc::write_msg(&mut writer, &UiMsg::ShowUi);
+                if let Err(e) = ipc::handshake(&mut reader, &mut writer, 0, ClientKind::Other) {
+                    eprintln!("OLED Care is already running, but {}", e);
+                    std::process::exit(1);
+                }
+                let _ = ipc::write_msg(
+                    &mut writer,
+                    &Request {
+                        id: 1,
+                        msg: UiMsg::ShowUi,
+                    },
+                );
                 // The daemon will spawn oled-care.exe --ui on its own.
             }
             Err(_) => {
@@ -62,34 +89,36 @@ fn run_ui() {
         }
     };
 
-    // Fetch the initial application state before starting GPUI.
-    let initial_state: DaemonState = {
-        let mut reader = BufReader::new(stream.try_clone().unwrap());
-        let mut writer = BufWriter::new(stream.try_clone().unwrap());
-        if let Err(e) = ipc::write_msg(&mut writer, &UiMsg::GetState) {
-            eprintln!("[ui] Failed to send GetState: {:?}", e);
-            std::process::exit(1);
-        }
-        match ipc::read_msg::<_, DaemonMsg>(&mut reader) {
+    // Subscribe to daemon events; the reply carries the initial state.  The
+    // same reader is handed to the IPC reader thread afterwards so no event
+    // that arrives right behind the reply is lost in a dropped buffer.
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = BufWriter::new(stream);
+    if let Err(e) = ipc::handshake(&mut reader, &mut writer, 0, ClientKind::Ui) {
+        eprintln!("[ui] Incompatible daemon: {}", e);
+        std::process::exit(1);
+    }
+    let initial_state: DaemonState =
+        match ipc::request(&mut reader, &mut writer, 1, UiMsg::Subscribe) {
             Ok(DaemonMsg::State(s)) => s,
+            Ok(other) => {
+                eprintln!("[ui] Unexpected reply to Subscribe: {:?}", other);
+                std::process::exit(1);
+            }
             Err(e) => {
                 eprintln!("[ui] Failed to read initial state: {:?}", e);
                 std::process::exit(1);
             }
-        }
-    };
+        };
 
-    // ── IPC background thread ─────────────────────────────────────────────
-    // cmd_tx  : Controller → this thread → daemon (commands)
-    // state_rx: daemon → this thread → Controller (state updates)
+    // ── IPC background threads ────────────────────────────────────────────
+    // cmd_tx  : Controller → writer thread → daemon (commands)
+    // state_rx: daemon → reader thread → Controller (replies and events)
     let (cmd_tx, cmd_rx) = mpsc::sync_channel::<UiMsg>(32);
-    let (state_tx, state_rx) = mpsc::channel::<DaemonState>();
+    let (state_tx, state_rx) = futures::channel::mpsc::unbounded::<DaemonUpdate>();
 
-    {
-        let reader = BufReader::new(stream.try_clone().unwrap());
-        let writer = BufWriter::new(stream);
-        std::thread::spawn(move || ipc_thread(reader, writer, cmd_rx, state_tx));
-    }
+    std::thread::spawn(move || ipc_writer_thread(writer, cmd_rx));
+    std::thread::spawn(move || ipc_reader_thread(reader, state_tx));
 
     // ── GPUI ─────────────────────────────────────────────────────────────
     Application::new().run(move |app: &mut gpui::App| {
@@ -117,30 +146,71 @@ fn run_ui() {
                     });
 
                     let cmd_tx = cmd_tx.clone();
-                    cx.new(move |cx| Controller::new(initial_state, cmd_tx, state_rx, cx))
+                    let controller =
+                        cx.new(move |cx| Controller::new(initial_state, cmd_tx, state_rx, cx));
+                    // Focus the window so Tab reaches the controls without a
+                    // click first.
+                    window.focus(&controller.read(cx).focus_handle);
+                    controller
                 },
             )
             .unwrap();
     });
 }
 
-/// Background thread: relays commands from the Controller to the daemon and
-/// forwards daemon state-update replies back to the Controller.
-fn ipc_thread(
-    mut reader: BufReader<TcpStream>,
-    mut writer: BufWriter<TcpStream>,
-    cmd_rx: mpsc::Receiver<UiMsg>,
-    state_tx: mpsc::Sender<DaemonState>,
-) {
-    while let Ok(cmd) = cmd_rx.recv() {
-        if ipc::write_msg(&mut writer, &cmd).is_err() {
+/// Background thread: relays commands from the Controller to the daemon,
+/// tagging each with a fresh request id.
+fn ipc_writer_thread(mut writer: BufWriter<Connection>, cmd_rx: mpsc::Receiver<UiMsg>) {
+    // Ids 0 and 1 were used by the handshake and Subscribe.
+    let mut next_id: u64 = 2;
+    while let Ok(msg) = cmd_rx.recv() {
+        let req = Request { id: next_id, msg };
+        next_id += 1;
+        if ipc::write_msg(&mut writer, &req).is_err() {
             break;
         }
-        match ipc::read_msg::<_, DaemonMsg>(&mut reader) {
-            Ok(DaemonMsg::State(s)) => {
-                let _ = state_tx.send(s);
+    }
+}
+
+/// Background thread: forwards every state the daemon sends — command
+/// replies and pushed `StateChanged` events alike — every statistics and
+/// suggestions reply and every refused command to the Controller.
+fn ipc_reader_thread(
+    mut reader: BufReader<Connection>,
+    state_tx: futures::channel::mpsc::UnboundedSender<DaemonUpdate>,
+) {
+    loop {
+        let Ok(body) = ipc::read_frame(&mut reader) else {
+            break;
+        };
+        let update = match serde_json::from_slice::<DaemonFrame>(&body) {
+            Ok(DaemonFrame::Reply {
+                msg: DaemonMsg::State(s),
+                ..
+            })
+            | Ok(DaemonFrame::Event(DaemonMsg::StateChanged(s))) => DaemonUpdate::State(s),
+            Ok(DaemonFrame::Reply {
+                msg: DaemonMsg::Stats(report),
+                ..
+            }) => DaemonUpdate::Stats(report),
+            Ok(DaemonFrame::Reply {
+                msg: DaemonMsg::RegionSuggestions(suggestions),
+                ..
+            }) => DaemonUpdate::Suggestions(suggestions),
+            Ok(DaemonFrame::Reply {
+                id,
+                msg: DaemonMsg::Error { code, message },
+            }) => {
+                eprintln!("[ui] Request {} refused ({:?}): {}", id, code, message);
+                DaemonUpdate::Refused
             }
-            Err(_) => break,
+            // Topology / overlay events are always followed by a full state.
+            Ok(_) => continue,
+            // Frames from a newer daemon that this build cannot decode.
+            Err(_) => continue,
+        };
+        if state_tx.unbounded_send(update).is_err() {
+            break; // window closed
         }
     }
 }
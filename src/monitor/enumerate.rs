@@ -11,26 +11,45 @@ use windows::Win32::Foundation::{LPARAM, RECT};
 use windows::Win32::Graphics::Gdi::{
     EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFOEXW,
 };
+use windows::Win32::System::Registry::{HKEY_LOCAL_MACHINE, RRF_RT_REG_BINARY, RegGetValueW};
+use windows::core::PCWSTR;
 
-use super::types::MonitorInfo;
+use super::types::{MonitorId, MonitorInfo, edid_serial};
+
+/// Bit in `DISPLAYCONFIG_TARGET_DEVICE_NAME_FLAGS` that says the EDID
+/// manufacturer / product ids are valid.
+const EDID_IDS_VALID: u32 = 0x4;
+
+/// Per-target details gathered from the Display Configuration API.
+struct TargetInfo {
+    /// Friendly monitor model name (may be empty).
+    friendly_name: String,
+    /// `monitorDevicePath` of the target (may be empty).
+    device_path: String,
+    /// EDID `(manufacturer, product)` ids when Windows reports them as valid.
+    edid: Option<(u16, u16)>,
+    /// EDID serial number, read from the registry (may be absent).
+    serial: Option<String>,
+}
 
 /// Enumerate all monitors currently connected to the system.
 ///
 /// Uses `EnumDisplayMonitors` for geometry and `QueryDisplayConfig` +
-/// `DisplayConfigGetDeviceInfo` for the human-readable model name.
+/// `DisplayConfigGetDeviceInfo` for the human-readable model name and the
+/// stable [`MonitorId`].
 pub fn enumerate_monitors() -> Vec<MonitorInfo> {
-    // Build GDI-name → friendly-name lookup first.
-    let friendly_map = build_friendly_name_map();
+    // Build GDI-name → target lookup first.
+    let target_map = build_target_map();
 
     // Bundle the output Vec and the map so the callback can reach both.
     struct CallbackData {
         monitors: Vec<MonitorInfo>,
-        friendly_map: HashMap<String, String>,
+        target_map: HashMap<String, TargetInfo>,
     }
 
     let mut cb_data = CallbackData {
         monitors: Vec::new(),
-        friendly_map,
+        target_map,
     };
 
     unsafe extern "system" fn enum_proc(
@@ -47,20 +66,38 @@ pub fn enumerate_monitors() -> Vec<MonitorInfo> {
 
             if GetMonitorInfoW(hmonitor, &mut info as *mut _ as *mut _).as_bool() {
                 let rc = info.monitorInfo.rcMonitor;
+                let work = info.monitorInfo.rcWork;
 
                 let raw = &info.szDevice;
                 let end = raw.iter().position(|&c| c == 0).unwrap_or(raw.len());
                 let name = String::from_utf16_lossy(&raw[..end]);
 
-                let friendly_name = data.friendly_map.get(&name).cloned().unwrap_or_default();
+                let (friendly_name, id) = match data.target_map.get(&name) {
+                    Some(t) => (
+                        t.friendly_name.clone(),
+                        MonitorId::from_parts(&t.device_path, t.edid, t.serial.as_deref(), &name),
+                    ),
+                    None => {
+                        eprintln!(
+                            "[monitor] No display configuration for {}; keying it on the GDI name",
+                            name
+                        );
+                        (String::new(), MonitorId::from_parts("", None, None, &name))
+                    }
+                };
 
                 data.monitors.push(MonitorInfo {
+                    id,
                     name,
                     friendly_name,
                     x: rc.left,
                     y: rc.top,
                     width: rc.right - rc.left,
                     height: rc.bottom - rc.top,
+                    work_x: work.left,
+                    work_y: work.top,
+                    work_width: work.right - work.left,
+                    work_height: work.bottom - work.top,
                     hmonitor: hmonitor.0 as isize,
                 });
             }
@@ -77,14 +114,14 @@ pub fn enumerate_monitors() -> Vec<MonitorInfo> {
     cb_data.monitors
 }
 
-// ── Friendly-name lookup ──────────────────────────────────────────────────────
+// ── Target lookup ─────────────────────────────────────────────────────────────
 
 /// Query the Display Configuration API to build a map from GDI device name
 /// (e.g. `\\.\DISPLAY1`) to the monitor's friendly model name
-/// (e.g. `"LG ULTRAGEAR 27GP850-B"`).
+/// (e.g. `"LG ULTRAGEAR 27GP850-B"`), device path, EDID ids and serial.
 ///
 /// Returns an empty map on any failure; the caller falls back to the GDI name.
-fn build_friendly_name_map() -> HashMap<String, String> {
+fn build_target_map() -> HashMap<String, TargetInfo> {
     let mut map = HashMap::new();
 
     unsafe {
@@ -135,7 +172,7 @@ fn build_friendly_name_map() -> HashMap<String, String> {
                 .unwrap_or(src.viewGdiDeviceName.len());
             let gdi_name = String::from_utf16_lossy(&src.viewGdiDeviceName[..end]);
 
-            // ── Target: get the friendly monitor model name ───────────────
+            // ── Target: get the friendly name, device path and EDID ids ───
             let mut tgt: DISPLAYCONFIG_TARGET_DEVICE_NAME = mem::zeroed();
             tgt.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME;
             tgt.header.size = mem::size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>() as u32;
@@ -153,13 +190,80 @@ fn build_friendly_name_map() -> HashMap<String, String> {
                 .unwrap_or(tgt.monitorFriendlyDeviceName.len());
             let friendly = String::from_utf16_lossy(&tgt.monitorFriendlyDeviceName[..end]);
 
-            // Keep the first mapping for each GDI name; skip blank names
-            // (happens for some embedded / virtual displays).
-            if !friendly.is_empty() {
-                map.entry(gdi_name).or_insert(friendly);
-            }
+            let end = tgt
+                .monitorDevicePath
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(tgt.monitorDevicePath.len());
+            let device_path = String::from_utf16_lossy(&tgt.monitorDevicePath[..end]);
+
+            let edid = if tgt.flags.Anonymous.value & EDID_IDS_VALID != 0 {
+                Some((tgt.edidManufactureId, tgt.edidProductCodeId))
+            } else {
+                None
+            };
+
+            // Keep the first mapping for each GDI name (mirrored displays
+            // share one source).  Blank friendly names happen for some
+            // embedded / virtual displays; the id is still useful.
+            map.entry(gdi_name).or_insert_with(|| TargetInfo {
+                friendly_name: friendly,
+                serial: edid
+                    .and_then(|_| read_edid(&device_path))
+                    .and_then(|e| edid_serial(&e)),
+                device_path,
+                edid,
+            });
         }
     }
 
     map
 }
+
+/// Read the EDID block Windows cached for the monitor at `device_path`
+/// (`\\?\DISPLAY#<hardware id>#<instance>#{guid}`) from the `EDID` value
+/// of its `Device Parameters` registry key.
+///
+/// The Display Configuration API reports the EDID manufacturer and product
+/// but not the serial number, which is only available from the raw block.
+fn read_edid(device_path: &str) -> Option<Vec<u8>> {
+    let mut parts = device_path.split('#');
+    parts.next()?;
+    let hardware_id = parts.next()?;
+    let instance = parts.next()?;
+    let key = format!(
+        r"SYSTEM\CurrentControlSet\Enum\DISPLAY\{}\{}\Device Parameters",
+        hardware_id, instance
+    );
+    let key: Vec<u16> = key.encode_utf16().chain(std::iter::once(0)).collect();
+    let value: Vec<u16> = "EDID".encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        let mut len = 0u32;
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            PCWSTR(key.as_ptr()),
+            PCWSTR(value.as_ptr()),
+            RRF_RT_REG_BINARY,
+            None,
+            None,
+            Some(&mut len),
+        )
+        .ok()
+        .ok()?;
+        let mut buf = vec![0u8; len as usize];
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            PCWSTR(key.as_ptr()),
+            PCWSTR(value.as_ptr()),
+            RRF_RT_REG_BINARY,
+            None,
+            Some(buf.as_mut_ptr().cast()),
+            Some(&mut len),
+        )
+        .ok()
+        .ok()?;
+        buf.truncate(len as usize);
+        Some(buf)
+    }
+}
@@ -1,5 +1,9 @@
 mod enumerate;
+mod topology;
 mod types;
+mod watch;
 
 pub use enumerate::enumerate_monitors;
-pub use types::MonitorInfo;
+pub use topology::diff_monitors;
+pub use types::{MonitorId, MonitorInfo};
+pub use watch::spawn_display_watcher;
@@ -0,0 +1,114 @@
+//! Topology diffing.
+//!
+//! After a display change the daemon re-enumerates the monitors and
+//! compares the new list with the previous one by [`MonitorId`], so overlays
+//! are only opened, closed or moved for the monitors that actually changed.
+
+use super::types::{MonitorId, MonitorInfo};
+
+/// Difference between two monitor enumerations, matched by [`MonitorId`].
+#[derive(Debug, Default)]
+pub struct TopologyDiff {
+    /// Monitors present only in the new list.
+    pub added: Vec<MonitorInfo>,
+    /// Ids of monitors present only in the old list.
+    pub removed: Vec<MonitorId>,
+    /// Monitors present in both lists whose position, size or work area
+    /// changed (carrying the *new* geometry).
+    pub moved: Vec<MonitorInfo>,
+}
+
+impl TopologyDiff {
+    /// `true` when nothing relevant to overlays changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// Compare two monitor lists and report what was added, removed or moved.
+///
+/// Order is ignored: Windows may enumerate the same set of monitors in a
+/// different order after a topology change.
+pub fn diff_monitors(old: &[MonitorInfo], new: &[MonitorInfo]) -> TopologyDiff {
+    let mut diff = TopologyDiff::default();
+
+    for mon in new {
+        match old.iter().find(|o| o.id == mon.id) {
+            None => diff.added.push(mon.clone()),
+            Some(prev) if !same_geometry(prev, mon) => diff.moved.push(mon.clone()),
+            Some(_) => {}
+        }
+    }
+
+    for mon in old {
+        if !new.iter().any(|n| n.id == mon.id) {
+            diff.removed.push(mon.id.clone());
+        }
+    }
+
+    diff
+}
+
+/// Whether two monitors occupy the same virtual-screen rectangle and have
+/// the same work area.
+fn same_geometry(a: &MonitorInfo, b: &MonitorInfo) -> bool {
+    a.x == b.x
+        && a.y == b.y
+        && a.width == b.width
+        && a.height == b.height
+        && a.work_x == b.work_x
+        && a.work_y == b.work_y
+        && a.work_width == b.work_width
+        && a.work_height == b.work_height
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FHD: (i32, i32, i32, i32) = (0, 0, 1920, 1080);
+    const RIGHT: (i32, i32, i32, i32) = (1920, 0, 1920, 1080);
+
+    fn ids(monitors: &[MonitorInfo]) -> Vec<&str> {
+        monitors.iter().map(|m| m.id.0.as_str()).collect()
+    }
+
+    #[test]
+    fn reordering_alone_changes_nothing() {
+        let old = [MonitorInfo::at("A", FHD), MonitorInfo::at("B", RIGHT)];
+        let new = [MonitorInfo::at("B", RIGHT), MonitorInfo::at("A", FHD)];
+        assert!(diff_monitors(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_monitors_are_reported() {
+        let old = [MonitorInfo::at("A", FHD), MonitorInfo::at("B", RIGHT)];
+        let new = [MonitorInfo::at("A", FHD), MonitorInfo::at("C", RIGHT)];
+        let diff = diff_monitors(&old, &new);
+        assert_eq!(ids(&diff.added), ["C"]);
+        assert_eq!(diff.removed, [MonitorId("B".into())]);
+        assert!(diff.moved.is_empty());
+    }
+
+    #[test]
+    fn geometry_changes_are_reported_as_moved() {
+        let old = [MonitorInfo::at("A", FHD), MonitorInfo::at("B", RIGHT)];
+        let new = [
+            MonitorInfo::at("A", FHD),
+            MonitorInfo::at("B", (-1920, 0, 1920, 1080)),
+        ];
+        let diff = diff_monitors(&old, &new);
+        assert_eq!(ids(&diff.moved), ["B"]);
+        assert_eq!(diff.moved[0].x, -1920, "carries the new geometry");
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+    }
+
+    #[test]
+    fn work_area_changes_are_reported_as_moved() {
+        let old = [MonitorInfo::at("A", FHD)];
+        let mut new = old.clone();
+        // The taskbar was set to auto-hide.
+        new[0].work_height = 1080;
+        assert_eq!(ids(&diff_monitors(&old, &new).moved), ["A"]);
+    }
+}
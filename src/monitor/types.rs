@@ -1,9 +1,104 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
+/// Stable identity of a physical display.
+///
+/// Unlike the GDI device name (`\\.\DISPLAY1`) or the position in the list
+/// returned by [`enumerate_monitors`](super::enumerate_monitors), this does
+/// not change when displays are re-plugged or re-ordered.  With a known EDID
+/// serial number it is the EDID manufacturer / product code and serial alone
+/// (`GSM5B7F-101NTAB1`), which keeps two panels of the same model apart and
+/// follows a panel to another connector.  Without a serial the Display
+/// Configuration target device path (which encodes the connector instance)
+/// stands in for it, and only with neither is the GDI name used.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MonitorId(pub String);
+
+impl MonitorId {
+    /// Build an id from the pieces reported by the Display Configuration API.
+    ///
+    /// * `device_path` — `monitorDevicePath` of the target, may be empty.
+    /// * `edid`        — `(manufacturer, product)` when the EDID ids are valid.
+    /// * `serial`      — EDID serial number (see [`edid_serial`]), if any.
+    /// * `gdi_name`    — last-resort fallback (`\\.\DISPLAYn`).
+    pub fn from_parts(
+        device_path: &str,
+        edid: Option<(u16, u16)>,
+        serial: Option<&str>,
+        gdi_name: &str,
+    ) -> Self {
+        let model = edid.map(|(mfg, product)| format!("{}{:04X}", pnp_vendor(mfg), product));
+        match (model, serial, device_path.is_empty()) {
+            (Some(model), Some(serial), _) => Self(format!("{}-{}", model, serial)),
+            (Some(model), None, false) => Self(format!("{}@{}", model, device_path.to_lowercase())),
+            (None, _, false) => Self(device_path.to_lowercase()),
+            (Some(model), None, true) => Self(format!("{}@{}", model, gdi_name)),
+            (None, _, true) => Self(gdi_name.to_string()),
+        }
+    }
+}
+
+/// Serial number from a raw EDID block: the text of its "display product
+/// serial number" descriptor if it has one, else the numeric serial in bytes
+/// 12–15.  `None` if the block is truncated or carries no serial.
+///
+/// Only ASCII letters and digits are kept, so the serial can never be
+/// mistaken for the separators inside a [`MonitorId`].
+pub fn edid_serial(edid: &[u8]) -> Option<String> {
+    if edid.len() < 128 {
+        return None;
+    }
+    // Four 18-byte descriptors; tag 0xFF marks the serial number string.
+    for desc in edid[54..126].chunks_exact(18) {
+        if desc[..3] == [0, 0, 0] && desc[3] == 0xFF {
+            let text: String = desc[5..]
+                .iter()
+                .take_while(|&&b| b != 0x0A && b != 0)
+                .filter(|b| b.is_ascii_alphanumeric())
+                .map(|&b| b as char)
+                .collect();
+            if !text.is_empty() {
+                return Some(text);
+            }
+        }
+    }
+    let serial = u32::from_le_bytes([edid[12], edid[13], edid[14], edid[15]]);
+    (serial != 0).then(|| format!("{:08X}", serial))
+}
+
+impl fmt::Display for MonitorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Decode the three-letter PnP vendor code packed into an EDID manufacturer id.
+///
+/// Windows reports the id byte-swapped relative to the EDID block; each letter
+/// is a 5-bit value where 1 = `A`.
+fn pnp_vendor(raw: u16) -> String {
+    let id = raw.swap_bytes();
+    [(id >> 10) & 0x1F, (id >> 5) & 0x1F, id & 0x1F]
+        .iter()
+        .map(|&c| {
+            if (1..=26).contains(&c) {
+                (b'A' + c as u8 - 1) as char
+            } else {
+                '?'
+            }
+        })
+        .collect()
+}
+
 /// Information about a connected display monitor.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct MonitorInfo {
+    /// Stable hardware identity; use this (not the list index) to refer to a
+    /// monitor across IPC, selection and persistence.
+    pub id: MonitorId,
     /// Device name reported by Windows (e.g. `\\.\.\DISPLAY1`).
     pub name: String,
     /// Human-readable monitor model name from the display configuration
@@ -18,7 +113,122 @@ pub struct MonitorInfo {
     pub width: i32,
     /// Height in pixels.
     pub height: i32,
+    /// Work area (`rcWork`): the monitor rectangle minus the taskbar and any
+    /// app bars, in virtual-screen coordinates.
+    pub work_x: i32,
+    pub work_y: i32,
+    pub work_width: i32,
+    pub work_height: i32,
     /// Raw HMONITOR handle as opaque integer (meaningful only in the process
     /// that enumerated monitors; the UI process ignores this field).
     pub hmonitor: isize,
 }
+
+#[cfg(test)]
+impl MonitorInfo {
+    /// A monitor at `(x, y, width, height)` for tests, without names and
+    /// with a 40 px taskbar along the bottom of its work area.
+    pub fn at(id: &str, (x, y, width, height): (i32, i32, i32, i32)) -> Self {
+        Self {
+            id: MonitorId(id.into()),
+            name: String::new(),
+            friendly_name: String::new(),
+            x,
+            y,
+            width,
+            height,
+            work_x: x,
+            work_y: y,
+            work_width: width,
+            work_height: height - 40,
+            hmonitor: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Windows' byte-swapped encoding of the PnP vendor `GSM`.
+    const GSM: u16 = 0x6D1E;
+
+    const PATH: &str =
+        r"\\?\DISPLAY#GSM5B7F#5&2a8c1e4a&0&UID4352#{e6f07b5f-ee97-4a90-b076-33f57bf4eaa7}";
+
+    /// A 128-byte EDID with `numeric` in bytes 12–15 and, if given, a serial
+    /// number descriptor holding `text`.
+    fn edid(numeric: u32, text: Option<&str>) -> Vec<u8> {
+        let mut block = vec![0u8; 128];
+        block[12..16].copy_from_slice(&numeric.to_le_bytes());
+        if let Some(text) = text {
+            let desc = &mut block[72..90];
+            desc[3] = 0xFF;
+            desc[5..5 + text.len()].copy_from_slice(text.as_bytes());
+            desc[5 + text.len()] = 0x0A;
+        }
+        block
+    }
+
+    #[test]
+    fn serial_separates_identical_panels() {
+        let a = MonitorId::from_parts("", Some((GSM, 0x5B7F)), Some("101NTAB1"), r"\\.\DISPLAY1");
+        let b = MonitorId::from_parts("", Some((GSM, 0x5B7F)), Some("101NTAB2"), r"\\.\DISPLAY1");
+        assert_ne!(a, b);
+        assert_eq!(a.0, "GSM5B7F-101NTAB1");
+    }
+
+    #[test]
+    fn serial_ids_ignore_connector_and_gdi_name() {
+        let id = MonitorId::from_parts(PATH, Some((GSM, 0x5B7F)), Some("42"), r"\\.\DISPLAY1");
+        assert_eq!(id.0, "GSM5B7F-42");
+        assert_eq!(
+            MonitorId::from_parts("", Some((GSM, 0x5B7F)), Some("42"), r"\\.\DISPLAY3"),
+            id
+        );
+    }
+
+    #[test]
+    fn device_path_is_lowercased_after_the_model() {
+        let id = MonitorId::from_parts(PATH, Some((GSM, 0x5B7F)), None, "");
+        assert!(id.0.starts_with(r"GSM5B7F@\\?\display#gsm5b7f#"));
+    }
+
+    #[test]
+    fn falls_back_without_serial_or_edid() {
+        assert_eq!(
+            MonitorId::from_parts(PATH, None, None, "x").0,
+            PATH.to_lowercase()
+        );
+        assert_eq!(
+            MonitorId::from_parts("", Some((GSM, 0x5B7F)), None, r"\\.\DISPLAY2").0,
+            r"GSM5B7F@\\.\DISPLAY2"
+        );
+        assert_eq!(
+            MonitorId::from_parts("", None, None, r"\\.\DISPLAY2").0,
+            r"\\.\DISPLAY2"
+        );
+    }
+
+    #[test]
+    fn serial_prefers_the_descriptor_text() {
+        assert_eq!(
+            edid_serial(&edid(0x1234, Some("101NTAB1"))).as_deref(),
+            Some("101NTAB1")
+        );
+        assert_eq!(
+            edid_serial(&edid(0x1234, None)).as_deref(),
+            Some("00001234")
+        );
+        assert_eq!(edid_serial(&edid(0, None)), None);
+        assert_eq!(edid_serial(&[0u8; 64]), None);
+    }
+
+    #[test]
+    fn serial_keeps_only_letters_and_digits() {
+        assert_eq!(
+            edid_serial(&edid(0, Some("AB-12@ 3"))).as_deref(),
+            Some("AB123")
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Display-change notifications.
+//!
+//! Windows broadcasts `WM_DISPLAYCHANGE` to every top-level window when a
+//! monitor is attached, detached or changes resolution / arrangement, and
+//! `WM_SETTINGCHANGE` with `SPI_SETWORKAREA` when the taskbar or an app bar
+//! moves or resizes.  Message-only windows (such as the tray's) do not
+//! receive broadcasts, so this module owns a hidden top-level window on its
+//! own thread and forwards each notification through an `mpsc` channel.
+
+use std::sync::mpsc;
+use std::thread;
+
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, MSG, PostQuitMessage,
+    RegisterClassW, SPI_SETWORKAREA, TranslateMessage, WM_DESTROY, WM_DISPLAYCHANGE,
+    WM_SETTINGCHANGE, WNDCLASSW, WS_EX_TOOLWINDOW, WS_POPUP,
+};
+use windows::core::PCWSTR;
+
+/// Spawn the display-change watcher thread.
+///
+/// A unit value is sent on `change_tx` for every `WM_DISPLAYCHANGE` and
+/// work-area change.  Changes usually arrive in bursts, so receivers should
+/// drain the channel and re-enumerate once.
+pub fn spawn_display_watcher(change_tx: mpsc::Sender<()>) {
+    thread::spawn(move || run_watcher_thread(change_tx));
+}
+
+// ── Thread-local state ───────────────────────────────────────────────────────
+
+thread_local! {
+    static TX: std::cell::RefCell<Option<mpsc::Sender<()>>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+// ── Internal implementation ──────────────────────────────────────────────────
+
+fn run_watcher_thread(change_tx: mpsc::Sender<()>) {
+    unsafe {
+        let hinstance: HINSTANCE = match GetModuleHandleW(None) {
+            Ok(h) => h.into(),
+            Err(e) => {
+                eprintln!("[watch] GetModuleHandleW failed: {:?}", e);
+                return;
+            }
+        };
+
+        let class_name_buf: Vec<u16> = "OLEDCareDisplayWatchClass\0".encode_utf16().collect();
+        let wc = WNDCLASSW {
+            lpfnWndProc: Some(wnd_proc),
+            hInstance: hinstance,
+            lpszClassName: PCWSTR(class_name_buf.as_ptr()),
+            ..Default::default()
+        };
+        let _ = RegisterClassW(&wc);
+
+        // Top-level but never shown: invisible windows still receive
+        // broadcast messages.
+        let window_name_buf: Vec<u16> = "OLEDCareDisplayWatch\0".encode_utf16().collect();
+        if let Err(e) = CreateWindowExW(
+            WS_EX_TOOLWINDOW,
+            PCWSTR(class_name_buf.as_ptr()),
+            PCWSTR(window_name_buf.as_ptr()),
+            WS_POPUP,
+            0,
+            0,
+            0,
+            0,
+            None,
+            None,
+            Some(hinstance),
+            None,
+        ) {
+            eprintln!("[watch] CreateWindowExW failed: {:?}", e);
+            return;
+        }
+
+        TX.with(|cell| *cell.borrow_mut() = Some(change_tx));
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+}
+
+// ── Window procedure ──────────────────────────────────────────────────────────
+
+fn notify() {
+    TX.with(|cell| {
+        if let Some(tx) = cell.borrow().as_ref() {
+            let _ = tx.send(());
+        }
+    });
+}
+
+unsafe extern "system" fn wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    unsafe {
+        match msg {
+            WM_DISPLAYCHANGE => {
+                notify();
+                LRESULT(0)
+            }
+            WM_SETTINGCHANGE if wparam.0 == SPI_SETWORKAREA.0 as usize => {
+                notify();
+                LRESULT(0)
+            }
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                LRESULT(0)
+            }
+            _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+        }
+    }
+}
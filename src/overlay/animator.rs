@@ -0,0 +1,276 @@
+//! Platform-independent opacity animation.
+//!
+//! An overlay's window procedure feeds the [`Animator`] a target opacity
+//! (base, hover or idle) and the time since its last timer tick; the
+//! animator answers with the opacity to apply.  Each transition takes the
+//! configured duration and follows a [`FadeCurve`].  Nothing here touches
+//! Win32, so the stepping can be exercised over simulated time.
+
+use serde::{Deserialize, Serialize};
+
+/// Default duration of one fade, in milliseconds.
+pub const DEFAULT_FADE_MS: u32 = 350;
+
+/// Longest fade the daemon accepts, in milliseconds.
+pub const MAX_FADE_MS: u32 = 10_000;
+
+/// Shape of a fade over its duration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FadeCurve {
+    /// Constant rate.
+    #[default]
+    Linear,
+    /// Slow start and end (smoothstep).
+    EaseInOut,
+    /// Fast start, long tail (exponential ease-out).
+    Exponential,
+}
+
+impl FadeCurve {
+    /// Progress (0.0–1.0) of the opacity change after `t` (0.0–1.0) of the
+    /// duration has elapsed.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            FadeCurve::Linear => t,
+            FadeCurve::EaseInOut => t * t * (3.0 - 2.0 * t),
+            // Normalised so the curve reaches exactly 1 at t = 1.
+            FadeCurve::Exponential => (1.0 - 2f32.powf(-10.0 * t)) / (1.0 - 2f32.powf(-10.0)),
+        }
+    }
+
+    /// Compact numeric code, for packing into window messages.
+    pub fn to_code(self) -> u8 {
+        self as u8
+    }
+
+    /// Inverse of [`to_code`](Self::to_code); unknown codes map to linear.
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            1 => FadeCurve::EaseInOut,
+            2 => FadeCurve::Exponential,
+            _ => FadeCurve::Linear,
+        }
+    }
+}
+
+/// Animates an opacity towards a target over a fixed duration.
+#[derive(Clone, Debug)]
+pub struct Animator {
+    curve: FadeCurve,
+    duration_ms: u32,
+    /// Opacity the current transition started from.
+    from: u8,
+    /// Opacity the current transition is heading for.
+    to: u8,
+    /// Time spent in the current transition.
+    elapsed_ms: u32,
+    /// Opacity as of the last [`step`](Self::step).
+    current: u8,
+}
+
+impl Animator {
+    /// An animator resting at `opacity`.
+    pub fn new(opacity: u8, curve: FadeCurve, duration_ms: u32) -> Self {
+        Self {
+            curve,
+            duration_ms,
+            from: opacity,
+            to: opacity,
+            elapsed_ms: 0,
+            current: opacity,
+        }
+    }
+
+    /// Opacity as of the last step.
+    pub fn current(&self) -> u8 {
+        self.current
+    }
+
+    /// Whether the current transition has finished.
+    pub fn is_done(&self) -> bool {
+        self.current == self.to
+    }
+
+    /// Change the curve and duration.  A transition in progress continues
+    /// from where it is with the new parameters.
+    pub fn configure(&mut self, curve: FadeCurve, duration_ms: u32) {
+        self.curve = curve;
+        self.duration_ms = duration_ms;
+        self.retarget(self.to);
+    }
+
+    /// Head for `target`.  A new target restarts the transition from the
+    /// current opacity; repeating the same target is a no-op.
+    pub fn set_target(&mut self, target: u8) {
+        if target != self.to {
+            self.retarget(target);
+        }
+    }
+
+    /// Jump to `opacity` immediately, without animating.
+    pub fn jump(&mut self, opacity: u8) {
+        *self = Self::new(opacity, self.curve, self.duration_ms);
+    }
+
+    /// Advance by `dt_ms` and return the new opacity.
+    pub fn step(&mut self, dt_ms: u32) -> u8 {
+        if self.is_done() {
+            return self.current;
+        }
+        self.elapsed_ms = self.elapsed_ms.saturating_add(dt_ms);
+        if self.duration_ms == 0 || self.elapsed_ms >= self.duration_ms {
+            self.current = self.to;
+            return self.current;
+        }
+        let t = self.elapsed_ms as f32 / self.duration_ms as f32;
+        let progress = self.curve.apply(t);
+        let value = self.from as f32 + (self.to as f32 - self.from as f32) * progress;
+        self.current = value.round().clamp(0.0, 255.0) as u8;
+        self.current
+    }
+
+    fn retarget(&mut self, target: u8) {
+        self.from = self.current;
+        self.to = target;
+        self.elapsed_ms = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// One overlay timer tick.
+    const FRAME: Duration = Duration::from_millis(16);
+
+    /// Step `animator` through `time` in timer ticks and return every
+    /// opacity it reports.
+    fn run(animator: &mut Animator, time: Duration) -> Vec<u8> {
+        let frames = time.as_millis().div_ceil(FRAME.as_millis());
+        (0..frames)
+            .map(|_| animator.step(FRAME.as_millis() as u32))
+            .collect()
+    }
+
+    #[test]
+    fn linear_moves_at_a_constant_rate() {
+        let mut a = Animator::new(0, FadeCurve::Linear, 200);
+        a.set_target(200);
+        assert_eq!(a.step(50), 50);
+        assert_eq!(a.step(50), 100);
+        assert_eq!(a.step(50), 150);
+        assert_eq!(a.step(50), 200);
+        assert!(a.is_done());
+    }
+
+    #[test]
+    fn ease_in_out_is_slow_at_both_ends() {
+        let mut a = Animator::new(0, FadeCurve::EaseInOut, 400);
+        a.set_target(200);
+        let quarter = a.step(100);
+        assert_eq!(quarter, 31);
+        assert_eq!(a.step(100), 100);
+        assert_eq!(a.step(100), 169);
+        assert_eq!(a.step(100), 200);
+    }
+
+    #[test]
+    fn exponential_front_loads_the_change() {
+        let mut a = Animator::new(0, FadeCurve::Exponential, 400);
+        a.set_target(200);
+        let half = a.step(200);
+        assert!(half > 190, "{}", half);
+        assert!(half < 200);
+        assert_eq!(a.step(200), 200);
+    }
+
+    #[test]
+    fn fading_down_mirrors_fading_up() {
+        let mut a = Animator::new(220, FadeCurve::Linear, 100);
+        a.set_target(20);
+        assert_eq!(a.step(50), 120);
+        assert_eq!(a.step(50), 20);
+    }
+
+    #[test]
+    fn every_curve_lands_exactly_on_the_target() {
+        for curve in [
+            FadeCurve::Linear,
+            FadeCurve::EaseInOut,
+            FadeCurve::Exponential,
+        ] {
+            let mut a = Animator::new(13, curve, DEFAULT_FADE_MS);
+            a.set_target(241);
+            let values = run(&mut a, Duration::from_millis(DEFAULT_FADE_MS as u64));
+            assert!(values.windows(2).all(|w| w[0] <= w[1]), "{:?}", curve);
+            assert_eq!(values.last(), Some(&241), "{:?}", curve);
+            assert!(a.is_done());
+            // Further ticks stay put.
+            assert_eq!(a.step(FRAME.as_millis() as u32), 241);
+        }
+    }
+
+    #[test]
+    fn zero_duration_jumps_on_the_next_step() {
+        let mut a = Animator::new(0, FadeCurve::EaseInOut, 0);
+        a.set_target(180);
+        assert_eq!(a.current(), 0);
+        assert_eq!(a.step(0), 180);
+        assert!(a.is_done());
+    }
+
+    #[test]
+    fn retarget_mid_fade_starts_from_the_current_opacity() {
+        let mut a = Animator::new(0, FadeCurve::Linear, 100);
+        a.set_target(200);
+        assert_eq!(a.step(50), 100);
+        a.set_target(0);
+        // The full duration again, from 100 down.
+        assert_eq!(a.step(50), 50);
+        assert_eq!(a.step(50), 0);
+    }
+
+    #[test]
+    fn repeating_the_target_does_not_restart() {
+        let mut a = Animator::new(0, FadeCurve::Linear, 100);
+        a.set_target(200);
+        a.step(50);
+        a.set_target(200);
+        assert_eq!(a.step(50), 200);
+    }
+
+    #[test]
+    fn configure_mid_fade_continues_from_where_it_is() {
+        let mut a = Animator::new(0, FadeCurve::Linear, 100);
+        a.set_target(200);
+        assert_eq!(a.step(50), 100);
+        a.configure(FadeCurve::Linear, 1000);
+        assert_eq!(a.step(500), 150);
+        a.configure(FadeCurve::Exponential, 0);
+        assert_eq!(a.step(0), 200);
+    }
+
+    #[test]
+    fn jump_skips_the_animation() {
+        let mut a = Animator::new(0, FadeCurve::Linear, 100);
+        a.set_target(200);
+        a.jump(90);
+        assert_eq!(a.current(), 90);
+        assert!(a.is_done());
+        assert_eq!(a.step(50), 90);
+    }
+
+    #[test]
+    fn curve_codes_round_trip() {
+        for curve in [
+            FadeCurve::Linear,
+            FadeCurve::EaseInOut,
+            FadeCurve::Exponential,
+        ] {
+            assert_eq!(FadeCurve::from_code(curve.to_code()), curve);
+        }
+        assert_eq!(FadeCurve::from_code(200), FadeCurve::Linear);
+    }
+}
@@ -1,7 +1,96 @@
 use std::sync::Arc;
+use std::sync::atomic::AtomicU8;
 
+use serde::{Deserialize, Serialize};
 use windows::Win32::Foundation::HWND;
 
+use crate::monitor::MonitorId;
+use crate::overlay::animator::{DEFAULT_FADE_MS, FadeCurve};
+use crate::overlay::fill::Fill;
+use crate::overlay::orbit::{ORBIT_OVERLAY, OrbitSettings};
+use crate::overlay::region::{Rect, Region};
+use crate::overlay::reveal::RevealSettings;
+
+/// User-configurable overlay behaviour for one monitor.
+///
+/// Stored per [`MonitorId`](crate::monitor::MonitorId) in the daemon,
+/// persisted in the settings file and reported to the UI.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorSettings {
+    /// Overlay opacity for this monitor; `None` follows the global opacity.
+    pub opacity: Option<u8>,
+    /// Fade the overlay to `hover_opacity` while the cursor is on it (the
+    /// whole monitor, or the region under the cursor).
+    pub fade_on_hover: bool,
+    /// Opacity while hovered (0 = fully transparent).
+    pub hover_opacity: u8,
+    /// Shape of every fade.
+    pub fade_curve: FadeCurve,
+    /// Duration of one fade in milliseconds (0 = instant).
+    pub fade_ms: u32,
+    /// Reveal only this area around the cursor while hovered, keeping the
+    /// rest of the overlay dimmed, instead of fading the whole overlay.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reveal: Option<RevealSettings>,
+    /// Keep the foreground window at `hover_opacity` and dim the rest of
+    /// the monitor, instead of reacting to the cursor.
+    pub focus_follow: bool,
+    /// Partial-screen regions.  Empty means one overlay over the whole
+    /// monitor; otherwise only the regions are covered.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<Region>,
+    /// Cover only the areas reserved for the taskbar and app bars (the
+    /// monitor minus its work area).  Takes precedence over `regions`.
+    pub reserved_only: bool,
+    /// Drift a faint pattern across the whole monitor (see
+    /// [`orbit`](crate::overlay::orbit)), on top of the dimming overlays.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orbit: Option<OrbitSettings>,
+    /// Colour, gradient or vignette the dimming overlays are filled with.
+    pub fill: Fill,
+}
+
+impl Default for MonitorSettings {
+    fn default() -> Self {
+        Self {
+            opacity: None,
+            fade_on_hover: true,
+            hover_opacity: 0,
+            fade_curve: FadeCurve::default(),
+            fade_ms: DEFAULT_FADE_MS,
+            reveal: None,
+            focus_follow: false,
+            regions: Vec::new(),
+            reserved_only: false,
+            orbit: None,
+            fill: Fill::default(),
+        }
+    }
+}
+
+impl MonitorSettings {
+    /// Opacity to apply on this monitor given the global opacity.
+    pub fn opacity_or(&self, global: u8) -> u8 {
+        self.opacity.unwrap_or(global)
+    }
+
+    /// The cursor reveal in effect: only while fading on hover at all.
+    pub fn active_reveal(&self) -> Option<RevealSettings> {
+        self.reveal.filter(|_| self.fade_on_hover)
+    }
+}
+
+/// What an overlay window draws.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlayKind {
+    /// Plain black, dimming everything beneath it.
+    Dim,
+    /// A pattern of black cells and see-through gaps, drifting along the
+    /// orbit path.
+    Orbit(OrbitSettings),
+}
+
 /// Parameters needed to spawn a single overlay window on a specific monitor.
 #[derive(Clone, Debug)]
 pub struct OverlayConfig {
@@ -15,6 +104,40 @@ pub struct OverlayConfig {
     pub width: i32,
     /// Height of the target monitor in pixels.
     pub height: i32,
+    /// Fade the overlay to `hover_opacity` while the cursor is on it.
+    pub fade_on_hover: bool,
+    /// Opacity while hovered.
+    pub hover_opacity: u8,
+    /// Shape of every fade.
+    pub fade_curve: FadeCurve,
+    /// Duration of one fade in milliseconds.
+    pub fade_ms: u32,
+    /// Area around the cursor revealed while hovered; `None` fades the
+    /// whole overlay.
+    pub reveal: Option<RevealSettings>,
+    /// Cut the foreground window out of the overlay.
+    pub focus_follow: bool,
+    /// What the overlay draws.
+    pub kind: OverlayKind,
+    /// What a [`OverlayKind::Dim`] overlay is filled with.
+    pub fill: Fill,
+    /// Bounds of the whole monitor, which `fill` is laid out across.
+    pub monitor: Rect,
+}
+
+/// Identifies one overlay window: a whole monitor (`region: None`), one
+/// named region on it, or its pixel orbit (`region:` [`ORBIT_OVERLAY`]).
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OverlayKey {
+    pub monitor: MonitorId,
+    pub region: Option<String>,
+}
+
+impl OverlayKey {
+    /// Whether this is the monitor's pixel-orbit overlay.
+    pub fn is_orbit(&self) -> bool {
+        self.region.as_deref() == Some(ORBIT_OVERLAY)
+    }
 }
 
 /// Tracks the runtime state of an overlay that has been spawned on a monitor.
@@ -24,6 +147,12 @@ pub struct OverlayState {
     pub hwnd: Option<HWND>,
     /// Join handle for the background thread running the overlay message loop.
     pub handle: Option<Arc<std::thread::JoinHandle<()>>>,
+    /// Opacity fixed by the overlay's region, if any.  Such overlays ignore
+    /// changes to the monitor opacity.
+    pub region_opacity: Option<u8>,
+    /// Opacity the window currently shows (outside any reveal or focus
+    /// hole), kept up to date by its thread for the exposure ledger.
+    pub shown: Arc<AtomicU8>,
 }
 
 impl Default for OverlayState {
@@ -31,6 +160,8 @@ impl Default for OverlayState {
         Self {
             hwnd: None,
             handle: None,
+            region_opacity: None,
+            shown: Arc::default(),
         }
     }
 }
@@ -0,0 +1,542 @@
+//! Overlay fills.
+//!
+//! By default an overlay is plain black.  A [`Fill`] can instead tint it
+//! with a colour (e.g. a warm tint that spares the blue subpixels), vary its
+//! strength along a [linear gradient](Fill::Linear) (e.g. dimming the top
+//! and bottom bars more than the centre), or darken the corners more than
+//! the middle ([vignette](Fill::Vignette)).
+//!
+//! Strengths scale the overlay's current opacity, so fades, hover and idle
+//! dimming work the same with any fill.  A solid fill works with uniform
+//! alpha; gradients and vignettes need per-pixel alpha and are rasterised
+//! across the whole monitor by [`Shading`] into premultiplied pixels,
+//! without Win32.
+
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::region::Rect;
+
+/// Most stops a linear gradient may have.
+pub const MAX_GRADIENT_STOPS: usize = 16;
+
+/// An opaque colour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+
+    /// Mix towards `other` by `t` (0.0 = `self`, 1.0 = `other`).
+    fn lerp(self, other: Rgb, t: f32) -> Rgb {
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Rgb(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+}
+
+/// Direction a linear gradient runs in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Axis {
+    /// Top to bottom.
+    #[default]
+    Vertical,
+    /// Left to right.
+    Horizontal,
+}
+
+/// One point of a linear gradient.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GradientStop {
+    /// Position along the axis, 0.0 (top / left) to 1.0 (bottom / right).
+    pub at: f32,
+    pub color: Rgb,
+    /// Fraction of the overlay opacity applied here, 0.0–1.0.
+    pub strength: f32,
+}
+
+/// What an overlay is filled with.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Fill {
+    /// One colour at full strength.
+    Solid(Rgb),
+    /// Colour and strength blend between `stops` along `axis`, and hold
+    /// the first / last stop's values beyond them.
+    Linear {
+        axis: Axis,
+        stops: Vec<GradientStop>,
+    },
+    /// `center` strength in the middle, rising smoothly to full strength in
+    /// the corners.  Up to `radius` (a fraction of the way from the centre
+    /// to a corner) it stays at `center`.
+    Vignette {
+        color: Rgb,
+        center: f32,
+        radius: f32,
+    },
+}
+
+impl Default for Fill {
+    fn default() -> Self {
+        Fill::Solid(Rgb::BLACK)
+    }
+}
+
+/// Whether `v` lies in 0.0–1.0 (and is a number at all).
+fn unit(v: f32) -> bool {
+    (0.0..=1.0).contains(&v)
+}
+
+/// Hermite smoothstep of `t` in 0.0–1.0.
+fn smooth(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+impl Fill {
+    /// Why this fill cannot be used, if anything.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Fill::Solid(_) => Ok(()),
+            Fill::Linear { stops, .. } => {
+                if stops.len() < 2 || stops.len() > MAX_GRADIENT_STOPS {
+                    return Err(format!(
+                        "a gradient needs between 2 and {} stops",
+                        MAX_GRADIENT_STOPS
+                    ));
+                }
+                if stops.iter().any(|s| !unit(s.at) || !unit(s.strength)) {
+                    return Err("gradient positions and strengths must be between 0 and 1".into());
+                }
+                if stops.windows(2).any(|w| w[1].at < w[0].at) {
+                    return Err("gradient stops must be in order".into());
+                }
+                Ok(())
+            }
+            Fill::Vignette { center, radius, .. } => {
+                if !unit(*center) || !unit(*radius) {
+                    return Err("vignette centre and radius must be between 0 and 1".into());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether the fill looks the same everywhere, so uniform alpha can
+    /// show it.
+    pub fn is_uniform(&self) -> bool {
+        matches!(self, Fill::Solid(_))
+    }
+
+    /// Colour to paint with uniform alpha: the fill's colour, or for a
+    /// gradient (when per-pixel alpha is unavailable) its first stop's.
+    pub fn flat_color(&self) -> Rgb {
+        match self {
+            Fill::Solid(color) | Fill::Vignette { color, .. } => *color,
+            Fill::Linear { stops, .. } => stops.first().map_or(Rgb::BLACK, |s| s.color),
+        }
+    }
+}
+
+/// Colour and strength (0.0–1.0) of a gradient at position `t`.
+pub fn gradient_at(stops: &[GradientStop], t: f32) -> (Rgb, f32) {
+    let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+        return (Rgb::BLACK, 1.0);
+    };
+    if t <= first.at {
+        return (first.color, first.strength);
+    }
+    for pair in stops.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        if t <= b.at {
+            let span = b.at - a.at;
+            let f = if span > 0.0 { (t - a.at) / span } else { 1.0 };
+            return (
+                a.color.lerp(b.color, f),
+                a.strength + (b.strength - a.strength) * f,
+            );
+        }
+    }
+    (last.color, last.strength)
+}
+
+/// Strength (0.0–1.0) of a vignette at offset `(dx, dy)` from the centre
+/// of a `width` × `height` surface.
+pub fn vignette_at(center: f32, radius: f32, dx: f32, dy: f32, width: f32, height: f32) -> f32 {
+    // 0.0 in the middle, 1.0 in the corners.
+    let nx = dx / (width / 2.0).max(1.0);
+    let ny = dy / (height / 2.0).max(1.0);
+    let d = ((nx * nx + ny * ny) / 2.0).sqrt();
+    let t = if radius >= 1.0 {
+        0.0
+    } else {
+        ((d - radius) / (1.0 - radius)).clamp(0.0, 1.0)
+    };
+    center + (1.0 - center) * smooth(t)
+}
+
+/// Premultiplied BGRA value of `color` at `alpha`.
+#[inline]
+pub fn premultiply(color: Rgb, alpha: u8) -> u32 {
+    let scale = |c: u8| (c as u32 * alpha as u32 + 127) / 255;
+    ((alpha as u32) << 24) | (scale(color.0) << 16) | (scale(color.1) << 8) | scale(color.2)
+}
+
+/// Strength 0.0–1.0 as a fraction of 255.
+fn weight(strength: f32) -> u8 {
+    (strength.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Colour and strength (of 255) along one axis, or at every pixel.
+#[derive(Clone, Debug)]
+enum Samples {
+    Uniform(Rgb),
+    /// One sample per row (vertical gradient) or column (horizontal).
+    Line(Axis, Vec<(Rgb, u8)>),
+    /// One strength per pixel, rows from the top.
+    Map(Rgb, Vec<u8>),
+}
+
+/// A [`Fill`] rasterised for one surface on a monitor.
+///
+/// Gradients and vignettes span the whole monitor, so a region or reserved
+/// strip shows its own slice of the monitor's fill rather than a complete
+/// copy squeezed into it.
+#[derive(Clone, Debug)]
+pub struct Shading {
+    width: i32,
+    samples: Samples,
+    /// Premultiplied pixels at the opacity last passed to
+    /// [`fill_row`](Self::fill_row): the line of a gradient, or a table by
+    /// strength for a vignette.  A fade repaints every pixel at one opacity
+    /// per tick, so each tick computes them once rather than per pixel.
+    cache: Option<(u8, Vec<u32>)>,
+}
+
+/// `alpha` scaled by a strength of 255.
+#[inline]
+fn scaled(alpha: u8, weight: u8) -> u8 {
+    ((alpha as u32 * weight as u32 + 127) / 255) as u8
+}
+
+impl Shading {
+    /// Rasterise `fill` for `surface`, laid out across `monitor`; both in
+    /// virtual-screen coordinates.
+    pub fn new(fill: &Fill, monitor: Rect, surface: Rect) -> Self {
+        let (mx, my, mw, mh) = monitor;
+        let (sx, sy, sw, sh) = surface;
+        let (w, h) = (sw.max(0), sh.max(0));
+        // Position of a surface pixel's centre relative to the monitor.
+        let (ox, oy) = ((sx - mx) as f32 + 0.5, (sy - my) as f32 + 0.5);
+        let samples = match fill {
+            Fill::Solid(color) => Samples::Uniform(*color),
+            Fill::Linear { axis, stops } => {
+                let (len, offset, span) = match axis {
+                    Axis::Vertical => (h, oy, mh),
+                    Axis::Horizontal => (w, ox, mw),
+                };
+                let span = span.max(1) as f32;
+                let line = (0..len)
+                    .map(|i| {
+                        let (color, strength) = gradient_at(stops, (offset + i as f32) / span);
+                        (color, weight(strength))
+                    })
+                    .collect();
+                Samples::Line(*axis, line)
+            }
+            Fill::Vignette {
+                color,
+                center,
+                radius,
+            } => {
+                let (cx, cy) = (mw as f32 / 2.0, mh as f32 / 2.0);
+                let map = (0..h)
+                    .flat_map(|y| (0..w).map(move |x| (x, y)))
+                    .map(|(x, y)| {
+                        let (dx, dy) = (ox + x as f32 - cx, oy + y as f32 - cy);
+                        weight(vignette_at(*center, *radius, dx, dy, mw as f32, mh as f32))
+                    })
+                    .collect();
+                Samples::Map(*color, map)
+            }
+        };
+        Self {
+            width: w,
+            samples,
+            cache: None,
+        }
+    }
+
+    /// Premultiplied pixel at `(x, y)` of the surface with the overlay at
+    /// `alpha`.
+    #[inline]
+    pub fn pixel(&self, x: i32, y: i32, alpha: u8) -> u32 {
+        match &self.samples {
+            Samples::Uniform(color) => premultiply(*color, alpha),
+            Samples::Line(axis, line) => {
+                let i = match axis {
+                    Axis::Vertical => y,
+                    Axis::Horizontal => x,
+                };
+                let (color, w) = line[i as usize];
+                premultiply(color, scaled(alpha, w))
+            }
+            Samples::Map(color, map) => {
+                premultiply(*color, scaled(alpha, map[(y * self.width + x) as usize]))
+            }
+        }
+    }
+
+    /// Fill `row`, the pixels of row `y` from column `x` on, with the
+    /// overlay at `alpha`.
+    pub fn fill_row(&mut self, row: &mut [u32], x: i32, y: i32, alpha: u8) {
+        let Self {
+            width,
+            samples,
+            cache,
+        } = self;
+        let pixels = cached(cache, samples, alpha);
+        match samples {
+            Samples::Uniform(_) => row.fill(pixels[0]),
+            Samples::Line(Axis::Vertical, _) => row.fill(pixels[y as usize]),
+            Samples::Line(Axis::Horizontal, _) => {
+                row.copy_from_slice(&pixels[x as usize..x as usize + row.len()])
+            }
+            Samples::Map(_, map) => {
+                let start = (y * *width + x) as usize;
+                let strengths = &map[start..start + row.len()];
+                for (px, &w) in row.iter_mut().zip(strengths) {
+                    *px = pixels[w as usize];
+                }
+            }
+        }
+    }
+}
+
+/// The premultiplied pixels of `samples` at `alpha`, from `cache` unless it
+/// holds another opacity.
+fn cached<'a>(cache: &'a mut Option<(u8, Vec<u32>)>, samples: &Samples, alpha: u8) -> &'a [u32] {
+    if cache.as_ref().is_none_or(|(a, _)| *a != alpha) {
+        let pixels = match samples {
+            Samples::Uniform(color) => vec![premultiply(*color, alpha)],
+            Samples::Line(_, line) => line
+                .iter()
+                .map(|&(color, w)| premultiply(color, scaled(alpha, w)))
+                .collect(),
+            Samples::Map(color, _) => (0..=255)
+                .map(|w| premultiply(*color, scaled(alpha, w)))
+                .collect(),
+        };
+        *cache = Some((alpha, pixels));
+    }
+    cache.as_ref().map_or(&[], |(_, pixels)| pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONITOR: Rect = (0, 0, 100, 50);
+
+    fn stop(at: f32, color: Rgb, strength: f32) -> GradientStop {
+        GradientStop {
+            at,
+            color,
+            strength,
+        }
+    }
+
+    /// Black at full strength at the top, clear at the bottom.
+    fn fade_down() -> Fill {
+        Fill::Linear {
+            axis: Axis::Vertical,
+            stops: vec![stop(0.0, Rgb::BLACK, 1.0), stop(1.0, Rgb::BLACK, 0.0)],
+        }
+    }
+
+    fn alpha(px: u32) -> u8 {
+        (px >> 24) as u8
+    }
+
+    /// Every pixel of `shading` on a `w` × `h` surface, rendered by rows.
+    fn render(shading: &mut Shading, w: i32, h: i32, opacity: u8) -> Vec<u32> {
+        let mut buf = vec![0u32; (w * h) as usize];
+        for (y, row) in buf.chunks_mut(w as usize).enumerate() {
+            shading.fill_row(row, 0, y as i32, opacity);
+        }
+        buf
+    }
+
+    #[test]
+    fn gradient_interpolates_and_holds_beyond_its_stops() {
+        let red = Rgb(255, 0, 0);
+        let stops = [stop(0.25, Rgb::BLACK, 0.0), stop(0.75, red, 1.0)];
+        assert_eq!(gradient_at(&stops, 0.0), (Rgb::BLACK, 0.0));
+        assert_eq!(gradient_at(&stops, 0.5), (Rgb(128, 0, 0), 0.5));
+        assert_eq!(gradient_at(&stops, 1.0), (red, 1.0));
+    }
+
+    #[test]
+    fn gradient_with_coincident_stops_steps() {
+        let stops = [
+            stop(0.0, Rgb::BLACK, 1.0),
+            stop(0.5, Rgb::BLACK, 1.0),
+            stop(0.5, Rgb::BLACK, 0.0),
+            stop(1.0, Rgb::BLACK, 0.0),
+        ];
+        assert_eq!(gradient_at(&stops, 0.49).1, 1.0);
+        assert_eq!(gradient_at(&stops, 0.51).1, 0.0);
+    }
+
+    #[test]
+    fn vignette_is_center_inside_radius_and_full_in_corners() {
+        let at = |dx, dy| vignette_at(0.2, 0.5, dx, dy, 100.0, 50.0);
+        assert_eq!(at(0.0, 0.0), 0.2);
+        assert_eq!(at(10.0, 5.0), 0.2);
+        assert_eq!(at(50.0, 25.0), 1.0);
+        let edge = at(50.0, 0.0);
+        assert!(0.2 < edge && edge < 1.0, "{edge}");
+    }
+
+    #[test]
+    fn vignette_with_full_radius_is_flat() {
+        assert_eq!(vignette_at(0.3, 1.0, 50.0, 25.0, 100.0, 50.0), 0.3);
+    }
+
+    #[test]
+    fn premultiply_scales_colour_by_alpha() {
+        assert_eq!(premultiply(Rgb(255, 128, 0), 255), 0xFFFF8000);
+        assert_eq!(premultiply(Rgb(255, 128, 0), 128), 0x80804000);
+        assert_eq!(premultiply(Rgb(255, 255, 255), 0), 0);
+    }
+
+    #[test]
+    fn vertical_gradient_runs_down_the_monitor() {
+        let mut shading = Shading::new(&fade_down(), MONITOR, MONITOR);
+        let buf = render(&mut shading, 100, 50, 200);
+        let column: Vec<u8> = (0..50).map(|y| alpha(buf[y * 100])).collect();
+        assert!(column.windows(2).all(|w| w[0] >= w[1]), "{column:?}");
+        assert!(column[0] > 190 && column[49] < 10, "{column:?}");
+        // Every row is uniform.
+        assert!(
+            buf.chunks(100)
+                .all(|row| row.iter().all(|&px| px == row[0]))
+        );
+    }
+
+    #[test]
+    fn horizontal_gradient_runs_across_the_monitor() {
+        let fill = Fill::Linear {
+            axis: Axis::Horizontal,
+            stops: vec![stop(0.0, Rgb::BLACK, 0.0), stop(1.0, Rgb::BLACK, 1.0)],
+        };
+        let mut shading = Shading::new(&fill, MONITOR, MONITOR);
+        let buf = render(&mut shading, 100, 50, 255);
+        let row: Vec<u8> = buf[..100].iter().map(|&px| alpha(px)).collect();
+        assert!(row.windows(2).all(|w| w[0] <= w[1]), "{row:?}");
+        assert!(row[0] < 5 && row[99] > 250, "{row:?}");
+        assert_eq!(&buf[..100], &buf[4900..]);
+    }
+
+    #[test]
+    fn region_shows_its_slice_of_the_monitor_gradient() {
+        let monitor = (1920, 0, 100, 50);
+        let mut whole = Shading::new(&fade_down(), monitor, monitor);
+        let whole = render(&mut whole, 100, 50, 200);
+
+        // A strip across the bottom fifth, and one down the right edge.
+        for (x, y, w, h) in [(0, 40, 100, 10), (90, 0, 10, 50)] {
+            let region = (1920 + x, y, w, h);
+            let mut shading = Shading::new(&fade_down(), monitor, region);
+            let part = render(&mut shading, w, h, 200);
+            for (ry, row) in part.chunks(w as usize).enumerate() {
+                let from = ((y + ry as i32) * 100 + x) as usize;
+                assert_eq!(row, &whole[from..from + w as usize], "region {region:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn region_shows_its_slice_of_the_monitor_vignette() {
+        let fill = Fill::Vignette {
+            color: Rgb::BLACK,
+            center: 0.0,
+            radius: 0.0,
+        };
+        let mut whole = Shading::new(&fill, MONITOR, MONITOR);
+        let whole = render(&mut whole, 100, 50, 255);
+
+        // The centre of the monitor is clear, its corner dark; a corner
+        // region is not a whole vignette of its own.
+        let corner = (0, 0, 20, 10);
+        let mut shading = Shading::new(&fill, MONITOR, corner);
+        let part = render(&mut shading, 20, 10, 255);
+        assert!(alpha(part[0]) > 200);
+        assert!(alpha(part[(9 * 20 + 19) as usize]) > 50);
+        for (y, row) in part.chunks(20).enumerate() {
+            assert_eq!(row, &whole[y * 100..y * 100 + 20]);
+        }
+        assert!(alpha(whole[25 * 100 + 50]) < 5);
+    }
+
+    #[test]
+    fn fill_row_matches_pixel_at_every_opacity() {
+        let fills = [
+            Fill::Solid(Rgb(10, 20, 30)),
+            fade_down(),
+            Fill::Linear {
+                axis: Axis::Horizontal,
+                stops: vec![
+                    stop(0.0, Rgb(255, 0, 0), 0.2),
+                    stop(1.0, Rgb(0, 0, 255), 0.9),
+                ],
+            },
+            Fill::Vignette {
+                color: Rgb(40, 30, 20),
+                center: 0.3,
+                radius: 0.4,
+            },
+        ];
+        for fill in &fills {
+            let surface = (10, 5, 60, 30);
+            let mut shading = Shading::new(fill, MONITOR, surface);
+            // Alternate opacities so the cache is rebuilt, then reused.
+            for opacity in [0, 200, 200, 37, 255, 37] {
+                let mut row = vec![0u32; 40];
+                for y in 0..30 {
+                    shading.fill_row(&mut row, 15, y, opacity);
+                    for (i, &px) in row.iter().enumerate() {
+                        assert_eq!(px, shading.pixel(15 + i as i32, y, opacity), "{fill:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn validate_rejects_bad_fills() {
+        let linear = |stops| Fill::Linear {
+            axis: Axis::Vertical,
+            stops,
+        };
+        assert!(fade_down().validate().is_ok());
+        assert!(linear(vec![stop(0.0, Rgb::BLACK, 1.0)]).validate().is_err());
+        assert!(
+            linear(vec![stop(0.5, Rgb::BLACK, 1.0), stop(0.2, Rgb::BLACK, 1.0)])
+                .validate()
+                .is_err()
+        );
+        assert!(
+            linear(vec![stop(0.0, Rgb::BLACK, 1.5), stop(1.0, Rgb::BLACK, 1.0)])
+                .validate()
+                .is_err()
+        );
+        let vignette = Fill::Vignette {
+            color: Rgb::BLACK,
+            center: f32::NAN,
+            radius: 0.5,
+        };
+        assert!(vignette.validate().is_err());
+    }
+}
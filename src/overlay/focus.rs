@@ -0,0 +1,249 @@
+//! Focus-follow dimming.
+//!
+//! Instead of keying on the cursor, an overlay in focus-follow mode leaves
+//! the foreground window at the hover opacity and dims the rest of its
+//! monitor.  Every timer tick the overlay windows share one sample of the
+//! foreground window as a [`Foreground`]; [`FocusCut`] turns that into the
+//! hole to cut
+//! out of the overlay's [`RevealMask`](crate::overlay::reveal::RevealMask),
+//! fading it in and out like a hover fade.
+//!
+//! Only plain rectangles come in, so the geometry works without Win32.
+
+use crate::overlay::animator::{Animator, FadeCurve};
+use crate::overlay::region::Rect;
+
+/// The foreground window as sampled by an overlay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Foreground {
+    /// Identity of the window (its handle), to notice focus changes.
+    pub id: usize,
+    /// Visible bounds of the window, in virtual-screen coordinates.
+    pub bounds: Rect,
+    /// Bounds of the monitor Windows considers the window to be on.
+    pub monitor: Rect,
+}
+
+/// Whether two rectangles share any pixels.
+fn intersects(a: Rect, b: Rect) -> bool {
+    let (ax, ay, aw, ah) = a;
+    let (bx, by, bw, bh) = b;
+    ax < bx + bw && bx < ax + aw && ay < by + bh && by < ay + ah
+}
+
+/// The part of `fg` to cut out of an overlay covering `overlay`, relative to
+/// the overlay's top-left corner.
+///
+/// A window is only cut out on the monitor that contains it, so one
+/// straddling two monitors does not punch holes into both.  `None` if the
+/// window is on another monitor or misses this overlay (e.g. a region
+/// overlay elsewhere on the monitor).
+pub fn cutout(overlay: Rect, fg: &Foreground) -> Option<Rect> {
+    if !intersects(overlay, fg.monitor) {
+        return None;
+    }
+    let (ox, oy, ow, oh) = overlay;
+    let (wx, wy, ww, wh) = fg.bounds;
+    let left = wx.max(ox);
+    let top = wy.max(oy);
+    let right = (wx + ww).min(ox + ow);
+    let bottom = (wy + wh).min(oy + oh);
+    (right > left && bottom > top).then_some((left - ox, top - oy, right - left, bottom - top))
+}
+
+/// Tracks the focused window's cut-out on one overlay and fades it.
+///
+/// A window gaining focus on this overlay fades from the surrounding
+/// opacity to the hover opacity; focus moving away fades its last cut-out
+/// back before dropping it.  Moving or resizing the focused window moves
+/// the cut-out without restarting the fade.
+#[derive(Clone, Debug)]
+pub struct FocusCut {
+    anim: Animator,
+    /// Window the cut-out belongs to, while it has focus.
+    window: Option<usize>,
+    /// Last cut-out, kept while it fades back.
+    rect: Option<Rect>,
+}
+
+impl FocusCut {
+    pub fn new(curve: FadeCurve, duration_ms: u32) -> Self {
+        Self {
+            anim: Animator::new(0, curve, duration_ms),
+            window: None,
+            rect: None,
+        }
+    }
+
+    /// Change the fade curve and duration.
+    pub fn configure(&mut self, curve: FadeCurve, duration_ms: u32) {
+        self.anim.configure(curve, duration_ms);
+    }
+
+    /// Advance by `dt_ms` with `fg` in the foreground (`None` if nothing
+    /// should be cut out) on an overlay covering `overlay` at opacity
+    /// `outside`.  Returns the cut-out, relative to the overlay, and its
+    /// current opacity.
+    pub fn step(
+        &mut self,
+        overlay: Rect,
+        fg: Option<&Foreground>,
+        outside: u8,
+        inside: u8,
+        dt_ms: u32,
+    ) -> Option<(Rect, u8)> {
+        let cut = fg.and_then(|fg| Some((fg.id, cutout(overlay, fg)?)));
+        match cut {
+            Some((id, rect)) => {
+                if self.window != Some(id) || self.rect.is_none() {
+                    // Newly focused here: start from the surrounding opacity.
+                    self.anim.jump(outside);
+                }
+                self.window = Some(id);
+                self.rect = Some(rect);
+                self.anim.set_target(inside);
+            }
+            None => {
+                self.window = None;
+                self.anim.set_target(outside);
+            }
+        }
+
+        let opacity = self.anim.step(dt_ms);
+        let rect = self.rect?;
+        if cut.is_none() && self.anim.is_done() {
+            self.rect = None;
+            return None;
+        }
+        Some((rect, opacity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two 1920x1080 monitors side by side.
+    const LEFT: Rect = (0, 0, 1920, 1080);
+    const RIGHT: Rect = (1920, 0, 1920, 1080);
+
+    fn window(id: usize, bounds: Rect, monitor: Rect) -> Foreground {
+        Foreground {
+            id,
+            bounds,
+            monitor,
+        }
+    }
+
+    #[test]
+    fn cutout_is_relative_to_the_overlay() {
+        let fg = window(1, (2000, 100, 800, 600), RIGHT);
+        assert_eq!(cutout(RIGHT, &fg), Some((80, 100, 800, 600)));
+    }
+
+    #[test]
+    fn cutout_is_clipped_to_the_overlay() {
+        // A maximised window's frame pokes past its monitor's edges.
+        let fg = window(1, (-8, -8, 1936, 1096), LEFT);
+        assert_eq!(cutout(LEFT, &fg), Some((0, 0, 1920, 1080)));
+
+        // A region overlay only gets its share of the window.
+        let region = (100, 900, 400, 180);
+        let fg = window(1, (0, 800, 300, 200), LEFT);
+        assert_eq!(cutout(region, &fg), Some((0, 0, 200, 100)));
+    }
+
+    #[test]
+    fn window_straddling_monitors_is_cut_out_on_its_own_monitor_only() {
+        let fg = window(1, (1700, 100, 800, 600), LEFT);
+        assert_eq!(cutout(LEFT, &fg), Some((1700, 100, 220, 600)));
+        assert_eq!(cutout(RIGHT, &fg), None);
+    }
+
+    #[test]
+    fn window_missing_the_overlay_is_not_cut_out() {
+        let region = (0, 1000, 1920, 80);
+        let fg = window(1, (100, 100, 800, 600), LEFT);
+        assert_eq!(cutout(region, &fg), None);
+    }
+
+    #[test]
+    fn newly_focused_window_fades_in_from_the_surrounding_opacity() {
+        let mut cut = FocusCut::new(FadeCurve::Linear, 100);
+        let fg = window(1, (100, 100, 800, 600), LEFT);
+
+        let (rect, first) = cut.step(LEFT, Some(&fg), 200, 0, 50).unwrap();
+        assert_eq!(rect, (100, 100, 800, 600));
+        assert!(0 < first && first < 200, "{first}");
+
+        let (_, last) = cut.step(LEFT, Some(&fg), 200, 0, 50).unwrap();
+        assert_eq!(last, 0);
+    }
+
+    #[test]
+    fn moving_the_focused_window_does_not_restart_the_fade() {
+        let mut cut = FocusCut::new(FadeCurve::Linear, 100);
+        let fg = window(1, (100, 100, 800, 600), LEFT);
+        let (_, before) = cut.step(LEFT, Some(&fg), 200, 0, 50).unwrap();
+
+        let moved = window(1, (300, 200, 800, 600), LEFT);
+        let (rect, after) = cut.step(LEFT, Some(&moved), 200, 0, 25).unwrap();
+        assert_eq!(rect, (300, 200, 800, 600));
+        assert!(after < before, "{after} >= {before}");
+    }
+
+    #[test]
+    fn focus_moving_away_fades_the_last_cutout_back_then_drops_it() {
+        let mut cut = FocusCut::new(FadeCurve::Linear, 100);
+        let fg = window(1, (100, 100, 800, 600), LEFT);
+        cut.step(LEFT, Some(&fg), 200, 0, 100);
+
+        let (rect, fading) = cut.step(LEFT, None, 200, 0, 50).unwrap();
+        assert_eq!(rect, (100, 100, 800, 600));
+        assert!(0 < fading && fading < 200, "{fading}");
+
+        assert_eq!(cut.step(LEFT, None, 200, 0, 50), None);
+        assert_eq!(cut.step(LEFT, None, 200, 0, 50), None);
+    }
+
+    #[test]
+    fn focus_moving_to_another_monitor_fades_this_cutout_back() {
+        let mut cut = FocusCut::new(FadeCurve::Linear, 100);
+        cut.step(
+            LEFT,
+            Some(&window(1, (100, 100, 800, 600), LEFT)),
+            200,
+            0,
+            100,
+        );
+
+        let elsewhere = window(2, (2000, 100, 800, 600), RIGHT);
+        let (rect, fading) = cut.step(LEFT, Some(&elsewhere), 200, 0, 50).unwrap();
+        assert_eq!(rect, (100, 100, 800, 600));
+        assert!(0 < fading && fading < 200, "{fading}");
+        assert_eq!(cut.step(LEFT, Some(&elsewhere), 200, 0, 50), None);
+    }
+
+    #[test]
+    fn switching_windows_restarts_the_fade_at_the_new_window() {
+        let mut cut = FocusCut::new(FadeCurve::Linear, 100);
+        cut.step(
+            LEFT,
+            Some(&window(1, (100, 100, 800, 600), LEFT)),
+            200,
+            0,
+            100,
+        );
+
+        let other = window(2, (1000, 200, 400, 300), LEFT);
+        let (rect, opacity) = cut.step(LEFT, Some(&other), 200, 0, 0).unwrap();
+        assert_eq!(rect, (1000, 200, 400, 300));
+        assert_eq!(opacity, 200);
+    }
+
+    #[test]
+    fn nothing_is_cut_out_without_a_foreground_window() {
+        let mut cut = FocusCut::new(FadeCurve::Linear, 100);
+        assert_eq!(cut.step(LEFT, None, 200, 0, 16), None);
+    }
+}
@@ -1,100 +1,383 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::c_void;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Arc, mpsc};
 
 use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
 use windows::Win32::UI::WindowsAndMessaging::{PostMessageW, WM_CLOSE};
 
-use crate::monitor::MonitorInfo;
-use crate::overlay::config::{OverlayConfig, OverlayState};
-use crate::overlay::window::{WM_UPDATE_OPACITY, spawn_overlay};
+use crate::monitor::{MonitorId, MonitorInfo};
+use crate::overlay::config::{
+    MonitorSettings, OverlayConfig, OverlayKey, OverlayKind, OverlayState,
+};
+use crate::overlay::orbit::ORBIT_OVERLAY;
+use crate::overlay::region::reserved_strips;
+use crate::overlay::window::{
+    WM_UPDATE_BOUNDS, WM_UPDATE_FADE, WM_UPDATE_IDLE, WM_UPDATE_MONITOR, WM_UPDATE_OPACITY,
+    WM_UPDATE_REVEAL, pack_fade, pack_reveal, pack_xy, spawn_overlay,
+};
 
 /// High-level manager that controls the lifecycle of per-monitor overlay windows.
 ///
 /// It bridges the gap between the UI layer (which knows *which* monitors are
 /// selected and at *what* opacity) and the low-level Win32 overlay windows that
 /// live on dedicated background threads.
+///
+/// A monitor has one full-screen overlay, one overlay per
+/// [region](crate::overlay::region) it defines, or — in "reserved areas
+/// only" mode — one per taskbar / app-bar strip, plus one for its
+/// [pixel orbit](crate::overlay::orbit) if enabled.
+#[derive(Default)]
 pub struct OverlayManager {
-    /// One [`OverlayState`] entry per monitor (mirrors the monitor list order).
-    pub states: Vec<OverlayState>,
+    /// One [`OverlayState`] entry per overlay window, keyed by the monitor's
+    /// stable id (and region name) so entries survive re-enumeration.
+    pub states: BTreeMap<OverlayKey, OverlayState>,
+}
+
+/// The overlay windows `mon` should have under `ms`, with `opacity` as the
+/// monitor's effective opacity.
+fn overlay_plan(
+    mon: &MonitorInfo,
+    ms: &MonitorSettings,
+    opacity: u8,
+) -> Vec<(OverlayKey, OverlayConfig, Option<u8>)> {
+    let config = |x, y, width, height, opacity| OverlayConfig {
+        opacity,
+        x,
+        y,
+        width,
+        height,
+        fade_on_hover: ms.fade_on_hover,
+        hover_opacity: ms.hover_opacity,
+        fade_curve: ms.fade_curve,
+        fade_ms: ms.fade_ms,
+        reveal: ms.active_reveal(),
+        focus_follow: ms.focus_follow,
+        kind: OverlayKind::Dim,
+        fill: ms.fill.clone(),
+        monitor: (mon.x, mon.y, mon.width, mon.height),
+    };
+
+    let mut plan: Vec<_> = if ms.reserved_only {
+        let monitor = (mon.x, mon.y, mon.width, mon.height);
+        let work = (mon.work_x, mon.work_y, mon.work_width, mon.work_height);
+        reserved_strips(monitor, work)
+            .into_iter()
+            .map(|(name, (x, y, w, h))| {
+                let key = OverlayKey {
+                    monitor: mon.id.clone(),
+                    region: Some(name.to_string()),
+                };
+                (key, config(mon.x + x, mon.y + y, w, h, opacity), None)
+            })
+            .collect()
+    } else if ms.regions.is_empty() {
+        let key = OverlayKey {
+            monitor: mon.id.clone(),
+            region: None,
+        };
+        vec![(
+            key,
+            config(mon.x, mon.y, mon.width, mon.height, opacity),
+            None,
+        )]
+    } else {
+        ms.regions
+            .iter()
+            .filter_map(|region| {
+                let (x, y, w, h) = region.rect.resolve(mon.width, mon.height)?;
+                let key = OverlayKey {
+                    monitor: mon.id.clone(),
+                    region: Some(region.name.clone()),
+                };
+                let cfg = config(
+                    mon.x + x,
+                    mon.y + y,
+                    w,
+                    h,
+                    region.opacity.unwrap_or(opacity),
+                );
+                Some((key, cfg, region.opacity))
+            })
+            .collect()
+    };
+
+    // The orbit always spans the whole monitor, at its own fixed strength.
+    // It fades with the cursor but never cuts holes.
+    if let Some(orbit) = ms.orbit {
+        let key = OverlayKey {
+            monitor: mon.id.clone(),
+            region: Some(ORBIT_OVERLAY.to_string()),
+        };
+        let cfg = OverlayConfig {
+            hover_opacity: ms.hover_opacity.min(orbit.strength),
+            reveal: None,
+            focus_follow: false,
+            kind: OverlayKind::Orbit(orbit),
+            ..config(mon.x, mon.y, mon.width, mon.height, orbit.strength)
+        };
+        plan.push((key, cfg, Some(orbit.strength)));
+    }
+    plan
 }
 
 impl OverlayManager {
-    /// Create a manager sized to match the given monitor list.
-    pub fn new(monitor_count: usize) -> Self {
-        Self {
-            states: vec![OverlayState::default(); monitor_count],
-        }
+    /// Create a manager with no overlays.
+    pub fn new() -> Self {
+        Self::default()
     }
 
     /// Spawn overlay windows on every monitor that is marked as *selected* but
-    /// does not already have an active overlay.
+    /// does not already have active overlays.
+    ///
+    /// Each overlay uses its monitor's entry in `monitor_settings` (falling
+    /// back to [`MonitorSettings::default`]), with `opacity` as the global
+    /// opacity for monitors that do not override it.  Monitors with regions
+    /// get one overlay per region.
     ///
     /// `hwnd_tx` is used to notify the main channel about each new `HWND` for
     /// deferred bookkeeping inside the render loop.
     pub fn activate(
         &mut self,
         monitors: &[MonitorInfo],
-        selected: &[bool],
+        selected: &BTreeSet<MonitorId>,
         opacity: u8,
+        monitor_settings: &BTreeMap<MonitorId, MonitorSettings>,
         hwnd_tx: &mpsc::Sender<(usize, usize)>,
     ) {
-        for i in 0..monitors.len() {
-            if selected[i] && self.states[i].hwnd.is_none() {
-                let mon = &monitors[i];
-                let cfg = OverlayConfig {
-                    opacity,
-                    x: mon.x,
-                    y: mon.y,
-                    width: mon.width,
-                    height: mon.height,
-                };
+        for (i, mon) in monitors.iter().enumerate() {
+            if !selected.contains(&mon.id) || self.has_overlay(&mon.id) {
+                continue;
+            }
 
-                let idx = i;
+            let ms = monitor_settings.get(&mon.id).cloned().unwrap_or_default();
+            for (key, cfg, region_opacity) in overlay_plan(mon, &ms, ms.opacity_or(opacity)) {
                 let tx = hwnd_tx.clone();
                 let (inner_tx, inner_rx) = mpsc::channel::<usize>();
-                let handle = spawn_overlay(cfg, inner_tx);
+                let shown = Arc::new(AtomicU8::new(cfg.opacity));
+                let handle = spawn_overlay(cfg, shown.clone(), inner_tx);
+                let mut state = OverlayState {
+                    region_opacity,
+                    shown,
+                    ..OverlayState::default()
+                };
 
                 // Wait briefly for the HWND so we can reference it immediately.
                 if let Ok(ptr) = inner_rx.recv_timeout(std::time::Duration::from_secs(2)) {
-                    self.states[i].hwnd = Some(HWND(ptr as *mut c_void));
-                    let _ = tx.send((idx, ptr));
+                    state.hwnd = Some(HWND(ptr as *mut c_void));
+                    let _ = tx.send((i, ptr));
                 }
 
-                self.states[i].handle = Some(Arc::new(handle));
+                state.handle = Some(Arc::new(handle));
+                self.states.insert(key, state);
             }
         }
     }
 
+    /// Whether `id` has at least one tracked overlay window.
+    pub fn has_overlay(&self, id: &MonitorId) -> bool {
+        self.states.keys().any(|k| &k.monitor == id)
+    }
+
+    /// Ids of the monitors with at least one live overlay window.
+    pub fn alive_monitors(&self) -> BTreeSet<MonitorId> {
+        self.states
+            .iter()
+            .filter(|(_, st)| st.hwnd.is_some())
+            .map(|(key, _)| key.monitor.clone())
+            .collect()
+    }
+
+    /// The opacity each monitor's overlays currently show, for monitors
+    /// with live overlay windows.  A monitor with several region overlays
+    /// reports the strongest; the faint orbit pattern does not count.
+    pub fn shown_opacities(&self) -> BTreeMap<MonitorId, u8> {
+        let mut shown = BTreeMap::new();
+        let live = self
+            .states
+            .iter()
+            .filter(|(key, st)| !key.is_orbit() && st.hwnd.is_some());
+        for (key, st) in live {
+            let opacity = st.shown.load(Ordering::Relaxed);
+            let entry = shown.entry(key.monitor.clone()).or_insert(opacity);
+            *entry = (*entry).max(opacity);
+        }
+        shown
+    }
+
+    /// Overlay windows belonging to monitor `id`.
+    fn windows_of<'a>(
+        &'a self,
+        id: &'a MonitorId,
+    ) -> impl Iterator<Item = (&'a OverlayKey, &'a OverlayState)> {
+        self.states.iter().filter(move |(k, _)| &k.monitor == id)
+    }
+
     /// Close every active overlay window and clear all tracked state.
     pub fn deactivate(&mut self) {
-        for state in &mut self.states {
-            if let Some(hwnd) = state.hwnd {
-                unsafe {
-                    let _ = PostMessageW(Some(hwnd), WM_CLOSE, WPARAM(0), LPARAM(0));
-                }
+        for state in self.states.values() {
+            close_window(state);
+        }
+        self.states.clear();
+    }
+
+    /// Close every overlay on a single monitor.
+    ///
+    /// Used when a monitor disappears from the topology, and before its
+    /// overlays are rebuilt after a region change.
+    pub fn close(&mut self, id: &MonitorId) {
+        self.states.retain(|key, state| {
+            if &key.monitor == id {
+                close_window(state);
+                false
+            } else {
+                true
             }
-            state.hwnd = None;
-            state.handle = None;
+        });
+    }
+
+    /// Forget overlays whose window thread has exited on its own (window
+    /// creation failed, or the window was destroyed externally) and return
+    /// their keys.  The monitor's other overlays are left alone.
+    ///
+    /// Overlays closed through [`deactivate`](Self::deactivate) or
+    /// [`close`](Self::close) are never reported, as they are untracked first.
+    pub fn reap_dead(&mut self) -> Vec<OverlayKey> {
+        let dead: Vec<OverlayKey> = self
+            .states
+            .iter()
+            .filter(|(_, st)| st.handle.as_ref().is_some_and(|h| h.is_finished()))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &dead {
+            self.states.remove(key);
         }
+        dead
     }
 
-    /// Send an opacity update to every currently-active overlay window.
+    /// Move and resize a monitor's overlays to match its new geometry.
+    /// Region and reserved-area overlays are re-resolved against it.
     ///
-    /// This is non-blocking — it posts a custom `WM_UPDATE_OPACITY` message to
-    /// each overlay's message loop which applies the change asynchronously.
-    pub fn update_opacity(&self, opacity: u8) {
-        for state in &self.states {
-            if let Some(hwnd) = state.hwnd {
+    /// Non-blocking: posts `WM_UPDATE_MONITOR` and `WM_UPDATE_BOUNDS` so each
+    /// overlay thread updates the window, its cursor hit-test rectangle and
+    /// the monitor its fill spans.
+    ///
+    /// Returns `false` if the new geometry calls for a different *set* of
+    /// overlays (e.g. the taskbar moved to another edge); the caller should
+    /// then [`close`](Self::close) the monitor and activate it again.
+    pub fn reposition(&self, mon: &MonitorInfo, ms: &MonitorSettings) -> bool {
+        // Opacity is irrelevant here; only the rectangles are used.
+        let plan = overlay_plan(mon, ms, 0);
+        let planned: BTreeSet<&OverlayKey> = plan.iter().map(|(key, _, _)| key).collect();
+        let current: BTreeSet<&OverlayKey> = self.windows_of(&mon.id).map(|(key, _)| key).collect();
+        if planned != current {
+            return false;
+        }
+
+        for (key, cfg, _) in &plan {
+            if let Some(hwnd) = self.states.get(key).and_then(|s| s.hwnd) {
                 unsafe {
                     let _ = PostMessageW(
                         Some(hwnd),
-                        WM_UPDATE_OPACITY,
-                        WPARAM(opacity as usize),
-                        LPARAM(0),
+                        WM_UPDATE_MONITOR,
+                        WPARAM(pack_xy(mon.x, mon.y)),
+                        LPARAM(pack_xy(mon.width, mon.height) as isize),
+                    );
+                    let _ = PostMessageW(
+                        Some(hwnd),
+                        WM_UPDATE_BOUNDS,
+                        WPARAM(pack_xy(cfg.x, cfg.y)),
+                        LPARAM(pack_xy(cfg.width, cfg.height) as isize),
                     );
                 }
             }
         }
+        true
+    }
+
+    /// Send a new monitor opacity to one monitor's overlay windows.  Regions
+    /// with an opacity of their own keep it.
+    ///
+    /// This is non-blocking — it posts a custom `WM_UPDATE_OPACITY` message to
+    /// the overlay's message loop which applies the change asynchronously.
+    pub fn update_opacity(&self, id: &MonitorId, opacity: u8) {
+        let targets = self
+            .windows_of(id)
+            .filter(|(_, st)| st.region_opacity.is_none())
+            .filter_map(|(_, st)| st.hwnd);
+        for hwnd in targets {
+            unsafe {
+                let _ = PostMessageW(
+                    Some(hwnd),
+                    WM_UPDATE_OPACITY,
+                    WPARAM(opacity as usize),
+                    LPARAM(0),
+                );
+            }
+        }
+    }
+
+    /// Send new hover behaviour (fade-on-hover and focus-follow flags, hover
+    /// opacity, curve, duration and cursor reveal) to one monitor's overlay
+    /// windows.  Non-blocking, like [`update_opacity`].
+    ///
+    /// [`update_opacity`]: Self::update_opacity
+    pub fn update_fade(&self, id: &MonitorId, settings: &MonitorSettings) {
+        for (key, st) in self.windows_of(id) {
+            let Some(hwnd) = st.hwnd else {
+                continue;
+            };
+            // Hovering never darkens the orbit beyond its strength.
+            let hover_opacity = match st.region_opacity {
+                Some(strength) if key.is_orbit() => settings.hover_opacity.min(strength),
+                _ => settings.hover_opacity,
+            };
+            unsafe {
+                let _ = PostMessageW(
+                    Some(hwnd),
+                    WM_UPDATE_FADE,
+                    WPARAM(pack_fade(
+                        settings.fade_on_hover,
+                        settings.focus_follow,
+                        hover_opacity,
+                        settings.fade_curve,
+                    )),
+                    LPARAM(settings.fade_ms as isize),
+                );
+                let (wparam, lparam) = pack_reveal(settings.active_reveal());
+                let _ = PostMessageW(Some(hwnd), WM_UPDATE_REVEAL, WPARAM(wparam), LPARAM(lparam));
+            }
+        }
+    }
+
+    /// Ramp every overlay to `opacity` (`Some`) while the user is idle, or
+    /// back to its normal behaviour (`None`).  Orbit overlays keep their
+    /// faint pattern.  Non-blocking.
+    pub fn set_idle(&self, opacity: Option<u8>) {
+        let targets = self
+            .states
+            .iter()
+            .filter(|(key, _)| !key.is_orbit())
+            .filter_map(|(_, st)| st.hwnd);
+        for hwnd in targets {
+            unsafe {
+                let _ = PostMessageW(
+                    Some(hwnd),
+                    WM_UPDATE_IDLE,
+                    WPARAM(opacity.unwrap_or(0) as usize),
+                    LPARAM(opacity.is_some() as isize),
+                );
+            }
+        }
+    }
+}
+
+/// Ask an overlay window to close itself (its thread then exits).
+fn close_window(state: &OverlayState) {
+    if let Some(hwnd) = state.hwnd {
+        unsafe {
+            let _ = PostMessageW(Some(hwnd), WM_CLOSE, WPARAM(0), LPARAM(0));
+        }
     }
 }
 
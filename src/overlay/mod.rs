@@ -1,6 +1,18 @@
+pub mod animator;
 pub mod config;
+pub mod fill;
+pub mod focus;
 pub mod manager;
+pub mod orbit;
+pub mod region;
+pub mod reveal;
 pub mod window;
 
+pub use animator::FadeCurve;
+pub use config::MonitorSettings;
+pub use fill::Fill;
 pub use manager::OverlayManager;
+pub use orbit::OrbitSettings;
+pub use region::Region;
+pub use reveal::RevealSettings;
 pub use window::register_overlay_class;
@@ -0,0 +1,274 @@
+//! Pixel orbit.
+//!
+//! Dimming lowers how hard static UI is driven, but still drives the same
+//! subpixels identically all day.  An orbit overlay lays a faint pattern —
+//! a checker or diagonal stripes whose dark cells sit a few percent below
+//! the rest — over the whole monitor and moves it by one pixel every
+//! `step_secs`, wandering within `amplitude` pixels of its origin, so the
+//! edges of static content are not driven the same way forever.
+//!
+//! The position on the path follows from the wall clock rather than from
+//! when the overlay appeared, so overlays on different monitors move in step
+//! and restarting the daemon does not send the pattern back to the start
+//! of its path, which would wear the first few offsets the most.
+//!
+//! The overlay window tiles [`tile`] with a pattern brush and shifts it by
+//! [`offset`]; both are pure so the pattern and path can be checked without
+//! a window.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the orbit overlay among a monitor's overlay windows; not
+/// available as a region name.
+pub const ORBIT_OVERLAY: &str = "orbit";
+
+/// Largest pattern cell accepted, in pixels.
+pub const MAX_ORBIT_CELL: u32 = 64;
+
+/// Largest distance from the origin accepted, in pixels.
+pub const MAX_ORBIT_AMPLITUDE: u32 = 16;
+
+/// Pattern drawn by an orbit overlay.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrbitPattern {
+    /// Alternating dark and clear squares.
+    #[default]
+    Checker,
+    /// Alternating dark and clear diagonal bands.
+    Stripes,
+}
+
+/// How the orbit overlay looks and moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrbitSettings {
+    pub pattern: OrbitPattern,
+    /// Size of one pattern cell (or band width), in pixels.
+    pub cell: u32,
+    /// How far the pattern may wander from its origin, in pixels.
+    pub amplitude: u32,
+    /// Seconds between one-pixel moves.
+    pub step_secs: u32,
+    /// Opacity of the dark cells (0 = invisible, 255 = black).
+    pub strength: u8,
+}
+
+impl Default for OrbitSettings {
+    fn default() -> Self {
+        Self {
+            pattern: OrbitPattern::Checker,
+            cell: 2,
+            amplitude: 2,
+            step_secs: 60,
+            strength: 16,
+        }
+    }
+}
+
+impl OrbitSettings {
+    /// Why these settings cannot be used, if anything.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cell == 0 || self.cell > MAX_ORBIT_CELL {
+            return Err(format!(
+                "orbit cell must be between 1 and {} pixels",
+                MAX_ORBIT_CELL
+            ));
+        }
+        if self.amplitude == 0 || self.amplitude > MAX_ORBIT_AMPLITUDE {
+            return Err(format!(
+                "orbit amplitude must be between 1 and {} pixels",
+                MAX_ORBIT_AMPLITUDE
+            ));
+        }
+        if self.step_secs == 0 {
+            return Err("orbit step must be at least one second".into());
+        }
+        if self.strength == 0 {
+            return Err("orbit strength must be above 0".into());
+        }
+        Ok(())
+    }
+
+    /// Offset of the pattern at wall-clock time `now`.
+    pub fn offset_at(&self, now: SystemTime) -> (i32, i32) {
+        let secs = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        offset(self.amplitude, secs / self.step_secs.max(1) as u64)
+    }
+}
+
+/// Offset of the pattern after `step` one-pixel moves.
+///
+/// The path snakes row by row through every offset within `amplitude` of
+/// the origin — a (2a+1)² square — and back again, so each move is to a
+/// neighbouring pixel and the path closes on itself.  It starts and ends at
+/// `(-amplitude, -amplitude)`.
+pub fn offset(amplitude: u32, step: u64) -> (i32, i32) {
+    let side = 2 * amplitude as u64 + 1;
+    let cells = side * side;
+    if cells == 1 {
+        return (0, 0);
+    }
+    // Out along the snake, then back along it.
+    let period = 2 * cells - 2;
+    let k = step % period;
+    let i = if k < cells { k } else { period - k };
+    let row = i / side;
+    let col = if row.is_multiple_of(2) {
+        i % side
+    } else {
+        side - 1 - i % side
+    };
+    let a = amplitude as i32;
+    (col as i32 - a, row as i32 - a)
+}
+
+/// Side of the square tile that repeats to form `cell`-sized patterns.
+pub fn tile_size(cell: u32) -> u32 {
+    2 * cell.max(1)
+}
+
+/// One tile of `pattern`: [`tile_size`]² cells in rows from the top, `true`
+/// where the pattern is dark.
+pub fn tile(pattern: OrbitPattern, cell: u32) -> Vec<bool> {
+    let cell = cell.max(1);
+    let side = tile_size(cell);
+    (0..side)
+        .flat_map(|y| (0..side).map(move |x| (x, y)))
+        .map(|(x, y)| match pattern {
+            OrbitPattern::Checker => (x / cell + y / cell).is_multiple_of(2),
+            // Bands run down-left; the tile repeats every `side` both ways.
+            OrbitPattern::Stripes => (x + y) % side < cell,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::time::Duration;
+
+    /// Number of steps before the path repeats.
+    fn period(amplitude: u32) -> u64 {
+        let side = 2 * amplitude as u64 + 1;
+        2 * side * side - 2
+    }
+
+    #[test]
+    fn path_starts_in_the_corner() {
+        assert_eq!(offset(2, 0), (-2, -2));
+        assert_eq!(offset(2, 1), (-1, -2));
+    }
+
+    #[test]
+    fn path_moves_one_pixel_at_a_time_and_closes() {
+        for amplitude in 1..=4 {
+            let p = period(amplitude);
+            for step in 0..=p {
+                let (x0, y0) = offset(amplitude, step);
+                let (x1, y1) = offset(amplitude, step + 1);
+                assert_eq!(
+                    (x1 - x0).abs() + (y1 - y0).abs(),
+                    1,
+                    "amplitude {amplitude}, step {step}"
+                );
+            }
+            assert_eq!(offset(amplitude, p), offset(amplitude, 0));
+        }
+    }
+
+    #[test]
+    fn path_visits_every_offset_within_the_amplitude() {
+        for amplitude in 1..=4 {
+            let a = amplitude as i32;
+            let visited: BTreeSet<_> = (0..period(amplitude))
+                .map(|step| offset(amplitude, step))
+                .collect();
+            assert!(visited.iter().all(|&(x, y)| x.abs() <= a && y.abs() <= a));
+            assert_eq!(visited.len(), ((2 * a + 1) * (2 * a + 1)) as usize);
+        }
+    }
+
+    #[test]
+    fn zero_amplitude_stays_put() {
+        assert_eq!(offset(0, 0), (0, 0));
+        assert_eq!(offset(0, 12345), (0, 0));
+    }
+
+    #[test]
+    fn offset_follows_the_wall_clock() {
+        let settings = OrbitSettings {
+            amplitude: 1,
+            step_secs: 60,
+            ..Default::default()
+        };
+        let at = |secs| settings.offset_at(UNIX_EPOCH + Duration::from_secs(secs));
+
+        // One move per step, whenever the overlay was created.
+        assert_eq!(at(0), offset(1, 0));
+        assert_eq!(at(59), offset(1, 0));
+        assert_eq!(at(60), offset(1, 1));
+        let now = 1_700_000_000;
+        assert_eq!(at(now), offset(1, now / 60));
+        assert_eq!(at(now + 60 * period(1)), at(now));
+    }
+
+    #[test]
+    fn clock_before_the_epoch_is_the_start_of_the_path() {
+        let settings = OrbitSettings::default();
+        let before = UNIX_EPOCH - Duration::from_secs(3600);
+        assert_eq!(settings.offset_at(before), offset(settings.amplitude, 0));
+    }
+
+    #[test]
+    fn checker_tile_alternates_cells() {
+        // cell 2: a 4x4 tile of four 2x2 cells.
+        let tile = tile(OrbitPattern::Checker, 2);
+        #[rustfmt::skip]
+        let expected = [
+            true,  true,  false, false,
+            true,  true,  false, false,
+            false, false, true,  true,
+            false, false, true,  true,
+        ];
+        assert_eq!(tile, expected);
+    }
+
+    #[test]
+    fn stripes_tile_repeats_seamlessly() {
+        for cell in 1..=4 {
+            let side = tile_size(cell) as usize;
+            let tile = tile(OrbitPattern::Stripes, cell);
+            // Half of every row is dark, and each row is the previous one
+            // shifted by a pixel, so the bands continue across tiles.
+            for y in 0..side {
+                let row = &tile[y * side..(y + 1) * side];
+                assert_eq!(row.iter().filter(|&&d| d).count(), cell as usize);
+                if y > 0 {
+                    let above = &tile[(y - 1) * side..y * side];
+                    for x in 0..side {
+                        assert_eq!(row[x], above[(x + 1) % side], "cell {cell}, ({x}, {y})");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn validate_bounds() {
+        assert!(OrbitSettings::default().validate().is_ok());
+        let with = |f: fn(&mut OrbitSettings)| {
+            let mut s = OrbitSettings::default();
+            f(&mut s);
+            s.validate()
+        };
+        assert!(with(|s| s.cell = 0).is_err());
+        assert!(with(|s| s.cell = MAX_ORBIT_CELL + 1).is_err());
+        assert!(with(|s| s.amplitude = 0).is_err());
+        assert!(with(|s| s.amplitude = MAX_ORBIT_AMPLITUDE + 1).is_err());
+        assert!(with(|s| s.step_secs = 0).is_err());
+        assert!(with(|s| s.strength = 0).is_err());
+    }
+}
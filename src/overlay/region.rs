@@ -0,0 +1,271 @@
+//! Named partial-monitor regions.
+//!
+//! By default an overlay covers its whole monitor.  A monitor with regions
+//! instead gets one overlay window per [`Region`] — e.g. just the taskbar, an
+//! IDE sidebar or a game HUD strip — each with its own opacity.
+//!
+//! Region geometry is given relative to the monitor, in pixels or percent,
+//! and resolved against the monitor's current size by [`RegionRect::resolve`]
+//! so regions follow resolution and layout changes.
+//!
+//! [`reserved_strips`] derives the areas Windows reserves for the taskbar
+//! and app bars from a monitor's work area, for the "reserved areas only"
+//! mode.
+
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::orbit::ORBIT_OVERLAY;
+
+/// Prefix of the [`reserved_strips`] overlay names; not available for
+/// regions.
+pub const RESERVED_PREFIX: &str = "reserved-";
+
+/// Unit of a [`RegionRect`]'s coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RegionUnit {
+    /// Physical pixels from the monitor's top-left corner.
+    Pixels,
+    /// Percent of the monitor's width (x, width) or height (y, height).
+    Percent,
+}
+
+/// A rectangle relative to its monitor's top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RegionRect {
+    pub unit: RegionUnit,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl RegionRect {
+    /// Pixel rectangle `(x, y, width, height)` relative to a monitor of the
+    /// given size, clipped to the monitor.  `None` if nothing of it is left.
+    pub fn resolve(&self, mon_w: i32, mon_h: i32) -> Option<(i32, i32, i32, i32)> {
+        let (sx, sy) = match self.unit {
+            RegionUnit::Pixels => (1.0, 1.0),
+            RegionUnit::Percent => (mon_w as f32 / 100.0, mon_h as f32 / 100.0),
+        };
+        let left = ((self.x * sx).round() as i32).clamp(0, mon_w);
+        let top = ((self.y * sy).round() as i32).clamp(0, mon_h);
+        let right = (((self.x + self.width) * sx).round() as i32).clamp(0, mon_w);
+        let bottom = (((self.y + self.height) * sy).round() as i32).clamp(0, mon_h);
+        (right > left && bottom > top).then_some((left, top, right - left, bottom - top))
+    }
+}
+
+/// A named overlay region on one monitor.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Region {
+    /// Unique per monitor; used to edit or remove the region.
+    pub name: String,
+    pub rect: RegionRect,
+    /// Opacity of this region; `None` follows the monitor's opacity.
+    #[serde(default)]
+    pub opacity: Option<u8>,
+}
+
+impl Region {
+    /// Why this region cannot be used, if anything.
+    pub fn validate(&self) -> Result<(), String> {
+        let r = &self.rect;
+        if self.name.trim().is_empty() {
+            return Err("a region needs a name".into());
+        }
+        if self.name == ORBIT_OVERLAY {
+            return Err(format!(
+                "'{}' is reserved for the pixel orbit",
+                ORBIT_OVERLAY
+            ));
+        }
+        if self.name.starts_with(RESERVED_PREFIX) {
+            return Err(format!(
+                "names starting with '{}' are reserved for taskbar strips",
+                RESERVED_PREFIX
+            ));
+        }
+        if ![r.x, r.y, r.width, r.height].iter().all(|v| v.is_finite()) {
+            return Err(format!(
+                "region '{}' has a non-numeric coordinate",
+                self.name
+            ));
+        }
+        if r.x < 0.0 || r.y < 0.0 || r.width <= 0.0 || r.height <= 0.0 {
+            return Err(format!(
+                "region '{}' must have a non-negative origin and a positive size",
+                self.name
+            ));
+        }
+        if r.unit == RegionUnit::Percent && (r.x + r.width > 100.0 || r.y + r.height > 100.0) {
+            return Err(format!("region '{}' extends past 100%", self.name));
+        }
+        Ok(())
+    }
+}
+
+// ── Reserved areas ───────────────────────────────────────────────────────────
+
+/// Rectangle `(x, y, width, height)` in some shared coordinate space.
+pub type Rect = (i32, i32, i32, i32);
+
+/// The parts of `monitor` outside `work` — where the taskbar and app bars
+/// sit — as up to four named, non-overlapping strips, relative to the
+/// monitor's top-left corner.
+///
+/// Top and bottom strips span the full width; left and right strips fill
+/// the height between them.  `work` is clipped to `monitor` first, so a
+/// work area that equals the monitor (e.g. an auto-hiding taskbar) yields
+/// no strips.
+pub fn reserved_strips(monitor: Rect, work: Rect) -> Vec<(&'static str, Rect)> {
+    let (mx, my, mw, mh) = monitor;
+    let (wx, wy, ww, wh) = work;
+
+    // Work-area edges relative to the monitor, clipped to it.
+    let left = (wx - mx).clamp(0, mw);
+    let top = (wy - my).clamp(0, mh);
+    let right = (wx + ww - mx).clamp(left, mw);
+    let bottom = (wy + wh - my).clamp(top, mh);
+
+    let strips = [
+        ("reserved-top", (0, 0, mw, top)),
+        ("reserved-bottom", (0, bottom, mw, mh - bottom)),
+        ("reserved-left", (0, top, left, bottom - top)),
+        ("reserved-right", (right, top, mw - right, bottom - top)),
+    ];
+    strips
+        .into_iter()
+        .filter(|(_, (_, _, w, h))| *w > 0 && *h > 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(unit: RegionUnit, x: f32, y: f32, width: f32, height: f32) -> RegionRect {
+        RegionRect {
+            unit,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn region(name: &str, rect: RegionRect) -> Region {
+        Region {
+            name: name.to_string(),
+            rect,
+            opacity: None,
+        }
+    }
+
+    #[test]
+    fn pixels_resolve_as_given() {
+        let r = rect(RegionUnit::Pixels, 0.0, 1032.0, 1920.0, 48.0);
+        assert_eq!(r.resolve(1920, 1080), Some((0, 1032, 1920, 48)));
+    }
+
+    #[test]
+    fn percent_scales_with_the_monitor() {
+        let r = rect(RegionUnit::Percent, 75.0, 0.0, 25.0, 100.0);
+        assert_eq!(r.resolve(1920, 1080), Some((1440, 0, 480, 1080)));
+        assert_eq!(r.resolve(2560, 1440), Some((1920, 0, 640, 1440)));
+    }
+
+    #[test]
+    fn percent_rounds_to_whole_pixels() {
+        let r = rect(RegionUnit::Percent, 0.0, 0.0, 33.3, 50.0);
+        assert_eq!(r.resolve(1000, 999), Some((0, 0, 333, 500)));
+    }
+
+    #[test]
+    fn overhang_is_clipped_to_the_monitor() {
+        let r = rect(RegionUnit::Pixels, 1800.0, 1000.0, 400.0, 400.0);
+        assert_eq!(r.resolve(1920, 1080), Some((1800, 1000, 120, 80)));
+    }
+
+    #[test]
+    fn region_off_the_monitor_resolves_to_nothing() {
+        let r = rect(RegionUnit::Pixels, 2000.0, 0.0, 100.0, 100.0);
+        assert_eq!(r.resolve(1920, 1080), None);
+    }
+
+    #[test]
+    fn valid_region_passes() {
+        let r = region("sidebar", rect(RegionUnit::Percent, 0.0, 0.0, 20.0, 100.0));
+        assert_eq!(r.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_bad_names() {
+        let r = rect(RegionUnit::Pixels, 0.0, 0.0, 10.0, 10.0);
+        assert!(region("  ", r).validate().is_err());
+        assert!(region(ORBIT_OVERLAY, r).validate().is_err());
+        assert!(region("reserved-bottom", r).validate().is_err());
+        assert!(region("reserved-mine", r).validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_bad_geometry() {
+        let bad = [
+            rect(RegionUnit::Pixels, f32::NAN, 0.0, 10.0, 10.0),
+            rect(RegionUnit::Pixels, 0.0, 0.0, f32::INFINITY, 10.0),
+            rect(RegionUnit::Pixels, -1.0, 0.0, 10.0, 10.0),
+            rect(RegionUnit::Pixels, 0.0, 0.0, 0.0, 10.0),
+            rect(RegionUnit::Percent, 50.0, 0.0, 60.0, 10.0),
+            rect(RegionUnit::Percent, 0.0, 90.0, 10.0, 20.0),
+        ];
+        for r in bad {
+            assert!(region("r", r).validate().is_err(), "{:?}", r);
+        }
+    }
+
+    const MONITOR: Rect = (1920, 0, 1920, 1080);
+
+    #[test]
+    fn bottom_taskbar_is_one_strip() {
+        let work = (1920, 0, 1920, 1032);
+        assert_eq!(
+            reserved_strips(MONITOR, work),
+            vec![("reserved-bottom", (0, 1032, 1920, 48))]
+        );
+    }
+
+    #[test]
+    fn side_bars_fill_between_top_and_bottom() {
+        // Taskbar at the top, app bars on both sides.
+        let work = (1920 + 100, 40, 1920 - 100 - 60, 1080 - 40);
+        assert_eq!(
+            reserved_strips(MONITOR, work),
+            vec![
+                ("reserved-top", (0, 0, 1920, 40)),
+                ("reserved-left", (0, 40, 100, 1040)),
+                ("reserved-right", (1860, 40, 60, 1040)),
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_hiding_taskbar_reserves_nothing() {
+        assert!(reserved_strips(MONITOR, MONITOR).is_empty());
+    }
+
+    #[test]
+    fn work_area_outside_the_monitor_is_clipped() {
+        // A stale work area that overhangs the monitor on every side.
+        let work = (1800, -50, 2200, 1200);
+        assert!(reserved_strips(MONITOR, work).is_empty());
+    }
+
+    #[test]
+    fn negative_origin_monitor() {
+        let monitor = (-1080, -200, 1080, 1920);
+        let work = (-1080, -200, 1080, 1872);
+        assert_eq!(
+            reserved_strips(monitor, work),
+            vec![("reserved-bottom", (0, 1872, 1080, 48))]
+        );
+    }
+}
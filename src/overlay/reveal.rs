@@ -0,0 +1,434 @@
+//! Cursor-proximity reveal.
+//!
+//! Instead of fading the whole overlay while the cursor is on it, an overlay
+//! can reveal only a soft-edged circle or rectangle around the cursor and
+//! keep the rest of the panel dimmed.  That needs per-pixel alpha: the
+//! overlay window renders a premultiplied 32-bit mask with [`RevealMask`]
+//! and hands it to `UpdateLayeredWindow`.  The same mask cuts the focused
+//! window out of the overlay (see [`focus`](crate::overlay::focus)) and
+//! shows gradient and vignette [fills](crate::overlay::fill).
+//!
+//! Everything here works on plain pixel buffers, so the mask maths can be
+//! checked without a window.
+
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::fill::{Fill, Shading};
+use crate::overlay::region::Rect;
+
+/// Largest radius / width / height accepted, in pixels.
+pub const MAX_REVEAL_SIZE: u32 = 8192;
+
+/// Widest soft edge accepted, in pixels.
+pub const MAX_REVEAL_FEATHER: u32 = 2048;
+
+/// Shape of the revealed area, centred on the cursor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RevealShape {
+    Circle { radius: u32 },
+    Rectangle { width: u32, height: u32 },
+}
+
+/// How the area around the cursor is revealed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevealSettings {
+    pub shape: RevealShape,
+    /// Width of the soft edge, in pixels, over which the reveal blends into
+    /// the surrounding overlay (0 = hard edge).
+    pub feather: u32,
+}
+
+impl RevealSettings {
+    /// Why these settings cannot be used, if anything.
+    pub fn validate(&self) -> Result<(), String> {
+        let sizes = match self.shape {
+            RevealShape::Circle { radius } => [radius, radius],
+            RevealShape::Rectangle { width, height } => [width, height],
+        };
+        if sizes.iter().any(|&s| s == 0 || s > MAX_REVEAL_SIZE) {
+            return Err(format!(
+                "reveal size must be between 1 and {} pixels",
+                MAX_REVEAL_SIZE
+            ));
+        }
+        if self.feather > MAX_REVEAL_FEATHER {
+            return Err(format!(
+                "reveal feather must be at most {} pixels",
+                MAX_REVEAL_FEATHER
+            ));
+        }
+        Ok(())
+    }
+
+    /// Signed distance from the shape's edge (negative inside) of a point
+    /// offset by `(dx, dy)` from the cursor.
+    fn edge_distance(&self, dx: f32, dy: f32) -> f32 {
+        match self.shape {
+            RevealShape::Circle { radius } => (dx * dx + dy * dy).sqrt() - radius as f32,
+            RevealShape::Rectangle { width, height } => {
+                let qx = dx.abs() - width as f32 / 2.0;
+                let qy = dy.abs() - height as f32 / 2.0;
+                let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+                outside + qx.max(qy).min(0.0)
+            }
+        }
+    }
+
+    /// How strongly the reveal applies at offset `(dx, dy)` from the cursor:
+    /// 1.0 inside the shape, falling smoothly to 0.0 across the feather.
+    pub fn strength(&self, dx: f32, dy: f32) -> f32 {
+        let d = self.edge_distance(dx, dy);
+        if d <= 0.0 {
+            return 1.0;
+        }
+        // A zero feather gives t = ∞: a hard edge.
+        let t = d / self.feather as f32;
+        if t >= 1.0 {
+            return 0.0;
+        }
+        1.0 - t * t * (3.0 - 2.0 * t)
+    }
+
+    /// Half-extents of the area the reveal can touch around the cursor.
+    fn half_extent(&self) -> (i32, i32) {
+        let (hw, hh) = match self.shape {
+            RevealShape::Circle { radius } => (radius, radius),
+            RevealShape::Rectangle { width, height } => (width.div_ceil(2), height.div_ceil(2)),
+        };
+        ((hw + self.feather) as i32, (hh + self.feather) as i32)
+    }
+
+    /// Pixels the reveal can touch with the cursor at `(cx, cy)`, clipped to
+    /// a `width` × `height` surface.  `None` if nothing of it is on the
+    /// surface.
+    pub fn bounds(&self, cx: i32, cy: i32, width: i32, height: i32) -> Option<Rect> {
+        let (ex, ey) = self.half_extent();
+        clip((cx - ex, cy - ey, 2 * ex + 1, 2 * ey + 1), width, height)
+    }
+}
+
+/// Smallest rectangle containing both (either may be absent).
+fn union(a: Option<Rect>, b: Option<Rect>) -> Option<Rect> {
+    match (a, b) {
+        (Some((ax, ay, aw, ah)), Some((bx, by, bw, bh))) => {
+            let left = ax.min(bx);
+            let top = ay.min(by);
+            let right = (ax + aw).max(bx + bw);
+            let bottom = (ay + ah).max(by + bh);
+            Some((left, top, right - left, bottom - top))
+        }
+        (a, b) => a.or(b),
+    }
+}
+
+/// Clip `rect` to a `width` × `height` surface.  `None` if nothing is left.
+fn clip(rect: Rect, width: i32, height: i32) -> Option<Rect> {
+    let (x, y, w, h) = rect;
+    let left = x.clamp(0, width);
+    let top = y.clamp(0, height);
+    let right = (x + w).clamp(0, width);
+    let bottom = (y + h).clamp(0, height);
+    (right > left && bottom > top).then_some((left, top, right - left, bottom - top))
+}
+
+/// The part of a [`RevealMask`] shown at the inside opacity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hole {
+    /// A reveal shape centred on a point (the cursor), relative to the
+    /// surface.
+    Around {
+        reveal: RevealSettings,
+        x: i32,
+        y: i32,
+    },
+    /// Exactly this rectangle, relative to the surface, with hard edges.
+    Rect(Rect),
+}
+
+impl Hole {
+    /// Pixels this hole can touch on a `width` × `height` surface.
+    fn bounds(&self, width: i32, height: i32) -> Option<Rect> {
+        match *self {
+            Hole::Around { reveal, x, y } => reveal.bounds(x, y, width, height),
+            Hole::Rect(rect) => clip(rect, width, height),
+        }
+    }
+
+    /// How strongly the hole applies at pixel `(x, y)`, 0.0–1.0.
+    fn strength(&self, px: i32, py: i32) -> f32 {
+        match *self {
+            Hole::Around { reveal, x, y } => reveal.strength((px - x) as f32, (py - y) as f32),
+            Hole::Rect((x, y, w, h)) => {
+                let inside = px >= x && px < x + w && py >= y && py < y + h;
+                if inside { 1.0 } else { 0.0 }
+            }
+        }
+    }
+}
+
+/// One rendered state of the mask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Frame {
+    hole: Option<Hole>,
+    /// Opacity away from the hole.
+    outside: u8,
+    /// Opacity inside the hole.
+    inside: u8,
+}
+
+/// Per-pixel alpha mask of one overlay surface, painted with its fill.
+///
+/// Tracks what was last rendered so each update only repaints the pixels
+/// that actually change: the old and new hole while it moves, the whole
+/// surface when the surrounding opacity changes.
+#[derive(Clone, Debug)]
+pub struct RevealMask {
+    width: i32,
+    height: i32,
+    fill: Fill,
+    shading: Shading,
+    last: Option<Frame>,
+}
+
+impl RevealMask {
+    /// A mask for `surface`, with `fill` laid out across `monitor`; both in
+    /// virtual-screen coordinates.
+    pub fn new(fill: Fill, monitor: Rect, surface: Rect) -> Self {
+        Self {
+            width: surface.2,
+            height: surface.3,
+            shading: Shading::new(&fill, monitor, surface),
+            fill,
+            last: None,
+        }
+    }
+
+    /// Size of the surface, in pixels.
+    pub fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// Follow a moved or resized surface or monitor; the next update
+    /// repaints everything.
+    pub fn resize(&mut self, monitor: Rect, surface: Rect) {
+        self.width = surface.2;
+        self.height = surface.3;
+        self.shading = Shading::new(&self.fill, monitor, surface);
+        self.last = None;
+    }
+
+    /// Record the state to show next and return the area that must be
+    /// repainted for it, or `None` if nothing changed.
+    pub fn update(&mut self, hole: Option<Hole>, outside: u8, inside: u8) -> Option<Rect> {
+        let frame = Frame {
+            hole,
+            outside,
+            inside,
+        };
+        let (w, h) = (self.width, self.height);
+        let bounds = |hole: Option<Hole>| hole?.bounds(w, h);
+        match self.last.replace(frame) {
+            Some(prev) if prev == frame => None,
+            Some(prev) if prev.outside == outside => union(bounds(prev.hole), bounds(hole)),
+            _ => (w > 0 && h > 0).then_some((0, 0, w, h)),
+        }
+    }
+
+    /// Render `area` of the last [`update`](Self::update)d state into `buf`,
+    /// a top-down `width` × `height` buffer of premultiplied BGRA pixels.
+    pub fn render(&mut self, buf: &mut [u32], area: Rect) {
+        let Some(frame) = self.last else {
+            return;
+        };
+        let (ax, ay, aw, ah) = area;
+        let hole = frame
+            .hole
+            .and_then(|hole| Some((hole, hole.bounds(self.width, self.height)?)));
+
+        for y in ay..ay + ah {
+            let start = (y * self.width + ax) as usize;
+            let row = &mut buf[start..start + aw as usize];
+            self.shading.fill_row(row, ax, y, frame.outside);
+
+            let Some((hole, (hx, hy, hw, hh))) = hole else {
+                continue;
+            };
+            if y < hy || y >= hy + hh {
+                continue;
+            }
+            // Only the columns shared by the repaint area and the hole.
+            let from = hx.max(ax);
+            let to = (hx + hw).min(ax + aw);
+            for x in from..to {
+                let s = hole.strength(x, y);
+                if s > 0.0 {
+                    let alpha =
+                        frame.outside as f32 + (frame.inside as f32 - frame.outside as f32) * s;
+                    row[(x - ax) as usize] = self.shading.pixel(x, y, alpha.round() as u8);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CIRCLE: RevealSettings = RevealSettings {
+        shape: RevealShape::Circle { radius: 10 },
+        feather: 10,
+    };
+
+    const RECT: RevealSettings = RevealSettings {
+        shape: RevealShape::Rectangle {
+            width: 20,
+            height: 10,
+        },
+        feather: 4,
+    };
+
+    /// Alpha of pixel `(x, y)` in a rendered `width`-wide buffer.
+    fn alpha(buf: &[u32], width: i32, x: i32, y: i32) -> u8 {
+        (buf[(y * width + x) as usize] >> 24) as u8
+    }
+
+    /// Render `hole` on a fresh `w` × `h` black mask.
+    fn render(w: i32, h: i32, hole: Hole, outside: u8, inside: u8) -> Vec<u32> {
+        let mut mask = RevealMask::new(Fill::default(), (0, 0, w, h), (0, 0, w, h));
+        let mut buf = vec![0u32; (w * h) as usize];
+        let dirty = mask.update(Some(hole), outside, inside).unwrap();
+        mask.render(&mut buf, dirty);
+        buf
+    }
+
+    #[test]
+    fn circle_is_full_inside_and_falls_off_across_the_feather() {
+        assert_eq!(CIRCLE.strength(0.0, 0.0), 1.0);
+        assert_eq!(CIRCLE.strength(10.0, 0.0), 1.0);
+        assert_eq!(CIRCLE.strength(0.0, -15.0), 0.5);
+        assert_eq!(CIRCLE.strength(20.0, 0.0), 0.0);
+        assert_eq!(CIRCLE.strength(30.0, 30.0), 0.0);
+        let falloff: Vec<f32> = (10..=20).map(|d| CIRCLE.strength(d as f32, 0.0)).collect();
+        assert!(falloff.windows(2).all(|w| w[0] >= w[1]), "{:?}", falloff);
+    }
+
+    #[test]
+    fn zero_feather_is_a_hard_edge() {
+        let hard = RevealSettings {
+            feather: 0,
+            ..CIRCLE
+        };
+        assert_eq!(hard.strength(10.0, 0.0), 1.0);
+        assert_eq!(hard.strength(10.01, 0.0), 0.0);
+    }
+
+    #[test]
+    fn rectangle_follows_its_own_half_extents() {
+        assert_eq!(RECT.strength(10.0, 5.0), 1.0);
+        assert_eq!(RECT.strength(12.0, 0.0), 0.5);
+        assert_eq!(RECT.strength(0.0, 7.0), 0.5);
+        assert_eq!(RECT.strength(14.0, 0.0), 0.0);
+        // Beyond a corner the distance is diagonal.
+        assert!(RECT.strength(12.0, 7.0) < RECT.strength(12.0, 0.0));
+    }
+
+    #[test]
+    fn bounds_are_clipped_to_the_surface() {
+        assert_eq!(CIRCLE.bounds(50, 50, 100, 100), Some((30, 30, 41, 41)));
+        assert_eq!(CIRCLE.bounds(0, 0, 100, 100), Some((0, 0, 21, 21)));
+        assert_eq!(CIRCLE.bounds(99, 99, 100, 100), Some((79, 79, 21, 21)));
+        assert_eq!(CIRCLE.bounds(-25, 50, 100, 100), None);
+    }
+
+    #[test]
+    fn render_blends_inside_and_outside() {
+        let (w, h) = (100, 60);
+        let hole = Hole::Around {
+            reveal: CIRCLE,
+            x: 50,
+            y: 30,
+        };
+        let buf = render(w, h, hole, 200, 0);
+        assert_eq!(alpha(&buf, w, 50, 30), 0);
+        assert_eq!(alpha(&buf, w, 60, 30), 0);
+        assert_eq!(alpha(&buf, w, 65, 30), 100);
+        assert_eq!(alpha(&buf, w, 70, 30), 200);
+        assert_eq!(alpha(&buf, w, 0, 0), 200);
+        assert_eq!(alpha(&buf, w, 99, 59), 200);
+    }
+
+    #[test]
+    fn rectangle_hole_renders_its_shape() {
+        let (w, h) = (100, 60);
+        let hole = Hole::Around {
+            reveal: RECT,
+            x: 50,
+            y: 30,
+        };
+        let buf = render(w, h, hole, 255, 40);
+        assert_eq!(alpha(&buf, w, 41, 26), 40);
+        assert_eq!(alpha(&buf, w, 59, 34), 40);
+        assert_eq!(alpha(&buf, w, 50, 40), 255);
+        assert_eq!(alpha(&buf, w, 65, 30), 255);
+    }
+
+    #[test]
+    fn hole_at_the_edge_is_clipped() {
+        let (w, h) = (40, 30);
+        let hole = Hole::Around {
+            reveal: CIRCLE,
+            x: 0,
+            y: 29,
+        };
+        let buf = render(w, h, hole, 255, 0);
+        assert_eq!(alpha(&buf, w, 0, 29), 0);
+        assert_eq!(alpha(&buf, w, 39, 0), 255);
+
+        let buf = render(w, h, Hole::Rect((-10, 20, 20, 20)), 255, 0);
+        assert_eq!(alpha(&buf, w, 9, 29), 0);
+        assert_eq!(alpha(&buf, w, 10, 29), 255);
+        assert_eq!(alpha(&buf, w, 0, 19), 255);
+    }
+
+    #[test]
+    fn update_repaints_only_what_changed() {
+        let full = (0, 0, 100, 100);
+        let mut mask = RevealMask::new(Fill::default(), full, full);
+        let at = |x, y| {
+            Some(Hole::Around {
+                reveal: CIRCLE,
+                x,
+                y,
+            })
+        };
+        // First frame: everything.
+        assert_eq!(mask.update(at(50, 50), 200, 0), Some((0, 0, 100, 100)));
+        // Nothing changed: nothing to do.
+        assert_eq!(mask.update(at(50, 50), 200, 0), None);
+        // The hole moved: its old and new bounds.
+        assert_eq!(mask.update(at(55, 50), 200, 0), Some((30, 30, 46, 41)));
+        // The hole vanished: its old bounds.
+        assert_eq!(mask.update(None, 200, 0), Some((35, 30, 41, 41)));
+        // The surrounding opacity changed: everything again.
+        assert_eq!(mask.update(None, 180, 0), Some((0, 0, 100, 100)));
+    }
+
+    #[test]
+    fn validate_limits_sizes() {
+        assert!(CIRCLE.validate().is_ok());
+        let zero = RevealSettings {
+            shape: RevealShape::Rectangle {
+                width: 0,
+                height: 10,
+            },
+            feather: 0,
+        };
+        assert!(zero.validate().is_err());
+        let wide = RevealSettings {
+            feather: MAX_REVEAL_FEATHER + 1,
+            ..CIRCLE
+        };
+        assert!(wide.validate().is_err());
+    }
+}
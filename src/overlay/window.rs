@@ -1,29 +1,74 @@
-use std::sync::mpsc;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant, SystemTime};
 
-use windows::Win32::Foundation::{COLORREF, HWND, LPARAM, LRESULT, POINT, WPARAM};
+use windows::Win32::Foundation::{COLORREF, HWND, LPARAM, LRESULT, POINT, RECT, SIZE, WPARAM};
 use windows::Win32::Graphics::Gdi::{
-    BeginPaint, CreateSolidBrush, EndPaint, FillRect, HBRUSH, PAINTSTRUCT, UpdateWindow,
+    AC_SRC_ALPHA, AC_SRC_OVER, BI_RGB, BITMAPINFO, BITMAPINFOHEADER, BLENDFUNCTION, BeginPaint,
+    CreateBitmap, CreateCompatibleDC, CreateDIBSection, CreatePatternBrush, CreateSolidBrush,
+    DIB_RGB_COLORS, DeleteDC, DeleteObject, EndPaint, FillRect, HBITMAP, HBRUSH, HDC, HGDIOBJ,
+    InvalidateRect, PAINTSTRUCT, SelectObject, SetBrushOrgEx, UpdateWindow,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-    CS_HREDRAW, CS_VREDRAW, CreateWindowExW, DefWindowProcW, DispatchMessageW, GWLP_USERDATA,
-    GetCursorPos, GetMessageW, GetWindowLongPtrW, HWND_TOPMOST, KillTimer, LWA_ALPHA, MSG,
-    PostQuitMessage, RegisterClassW, SW_SHOW, SWP_NOACTIVATE, SWP_SHOWWINDOW,
-    SetLayeredWindowAttributes, SetTimer, SetWindowLongPtrW, SetWindowPos, ShowWindow,
-    TranslateMessage, WINDOW_EX_STYLE, WM_DESTROY, WM_PAINT, WM_TIMER, WM_USER, WNDCLASSW,
-    WS_DISABLED, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST,
-    WS_EX_TRANSPARENT, WS_POPUP,
+    CS_HREDRAW, CS_VREDRAW, CreateWindowExW, DefWindowProcW, DispatchMessageW, GWL_EXSTYLE,
+    GWLP_USERDATA, GetCursorPos, GetMessageW, GetWindowLongPtrW, HWND_TOPMOST, KillTimer,
+    LWA_ALPHA, LWA_COLORKEY, MSG, PostQuitMessage, RegisterClassW, SW_SHOW, SWP_NOACTIVATE,
+    SWP_SHOWWINDOW, SetLayeredWindowAttributes, SetTimer, SetWindowLongPtrW, SetWindowPos,
+    ShowWindow, TranslateMessage, ULW_ALPHA, UPDATELAYEREDWINDOWINFO, UpdateLayeredWindowIndirect,
+    WINDOW_EX_STYLE, WM_DESTROY, WM_PAINT, WM_TIMER, WM_USER, WNDCLASSW, WS_DISABLED,
+    WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_EX_TRANSPARENT, WS_POPUP,
 };
 use windows::core::PCWSTR;
 
-use super::config::OverlayConfig;
+use super::animator::{Animator, FadeCurve};
+use super::config::{OverlayConfig, OverlayKind};
+use super::fill::{Fill, Rgb};
+use super::focus::FocusCut;
+use super::orbit::{OrbitSettings, tile, tile_size};
+use super::region::Rect;
+use super::reveal::{Hole, RevealMask, RevealSettings, RevealShape};
+use crate::foreground::shared_focus;
 
 // ─── Constants ───────────────────────────────────────────────────────────────
 
 pub const WM_UPDATE_OPACITY: u32 = WM_USER + 1;
 
+/// Change the hover behaviour: `wparam` carries the fade-on-hover and
+/// focus-follow flags, hover opacity and curve packed with [`pack_fade`],
+/// `lparam` the fade duration in milliseconds.
+pub const WM_UPDATE_FADE: u32 = WM_USER + 3;
+
+/// Move / resize the overlay after a display topology change.
+///
+/// `wparam` carries the new top-left corner and `lparam` the new size, each
+/// packed with [`pack_xy`].
+pub const WM_UPDATE_BOUNDS: u32 = WM_USER + 2;
+
+/// Enter or leave idle dimming: `lparam` is 1 while idle, in which case
+/// `wparam` is the idle opacity.  The overlay ramps to it at its fade speed
+/// (unless its own opacity is higher), ignoring hover, and ramps back on
+/// leaving.
+pub const WM_UPDATE_IDLE: u32 = WM_USER + 4;
+
+/// Change the cursor reveal: `wparam` / `lparam` as packed by
+/// [`pack_reveal`].  Switches between the uniform-alpha and per-pixel-alpha
+/// paths as needed.
+pub const WM_UPDATE_REVEAL: u32 = WM_USER + 5;
+
+/// Follow the monitor a fill is laid out across after a display topology
+/// change: `wparam` / `lparam` as for [`WM_UPDATE_BOUNDS`].  Posted just
+/// before the overlay's own `WM_UPDATE_BOUNDS`, which repaints.
+pub const WM_UPDATE_MONITOR: u32 = WM_USER + 6;
+
 const TIMER_ID: usize = 1;
 
-const FADE_STEP: u8 = 12;
+/// Interval of the cursor-tracking / fade timer (~60 fps).
+const TIMER_MS: u32 = 16;
+
+/// Colour of the see-through gaps in an orbit pattern (magenta), keyed out
+/// with `LWA_COLORKEY`.
+const ORBIT_KEY: u32 = 0x00FF00FF;
 
 static mut WINDOW_CLASS_ATOM: u16 = 0;
 
@@ -34,10 +79,40 @@ static mut WINDOW_CLASS_ATOM: u16 = 0;
 /// Freed inside `WM_DESTROY`.
 struct FadeState {
     base_opacity: u8,
-    /// Opacity currently applied to the Win32 layered window.
-    current_opacity: u8,
-    /// Bounding rectangle of the monitor this overlay covers (used to hit-test
-    /// the cursor position without any Win32 region API).
+    /// Whether the overlay fades to `hover_opacity` while the cursor is on it.
+    fade_on_hover: bool,
+    /// Cut the foreground window out of the overlay instead of reacting to
+    /// the cursor.
+    focus_follow: bool,
+    /// Opacity while hovered (or of the focus cut-out).
+    hover_opacity: u8,
+    /// Area revealed around the cursor while hovered, instead of fading the
+    /// whole overlay.
+    reveal: Option<RevealSettings>,
+    /// Drives the opacity applied to the Win32 layered window.
+    animator: Animator,
+    /// Where the animator's opacity is published for the exposure ledger.
+    shown: Arc<AtomicU8>,
+    /// Fades the focus cut-out in focus-follow mode.
+    focus: FocusCut,
+    /// Time of the previous timer tick, to step the animator by real time.
+    last_tick: Instant,
+    /// Cursor position at the previous timer tick.
+    last_cursor: POINT,
+    /// Opacity to ramp to while the user is idle; overrides hover.
+    idle_opacity: Option<u8>,
+    /// Mask and surface of the per-pixel-alpha path, used for a cursor
+    /// reveal, focus cut-out or non-uniform fill; `None` uses uniform
+    /// `LWA_ALPHA`.
+    pixels: Option<(RevealMask, Surface)>,
+    /// Pattern of an orbit overlay; `None` for a filled one.
+    orbit: Option<Orbit>,
+    /// Colour, gradient or vignette of a filled overlay.
+    fill: Fill,
+    /// Bounds of the whole monitor, which the fill is laid out across.
+    monitor: Rect,
+    /// Rectangle this overlay covers — its whole monitor or one region of it
+    /// (used to hit-test the cursor position without any Win32 region API).
     mon_x: i32,
     mon_y: i32,
     mon_w: i32,
@@ -45,7 +120,13 @@ struct FadeState {
 }
 
 impl FadeState {
-    /// Returns `true` when `pt` falls inside this overlay's monitor.
+    /// Rectangle this overlay covers, in virtual-screen coordinates.
+    #[inline]
+    fn bounds(&self) -> Rect {
+        (self.mon_x, self.mon_y, self.mon_w, self.mon_h)
+    }
+
+    /// Returns `true` when `pt` falls inside this overlay's rectangle.
     #[inline]
     fn cursor_on_monitor(&self, pt: POINT) -> bool {
         pt.x >= self.mon_x
@@ -53,6 +134,399 @@ impl FadeState {
             && pt.y >= self.mon_y
             && pt.y < self.mon_y + self.mon_h
     }
+
+    /// Returns `true` when the overlay should currently be faded out for `pt`.
+    #[inline]
+    fn hovered(&self, pt: POINT) -> bool {
+        self.fade_on_hover && self.cursor_on_monitor(pt)
+    }
+
+    /// Whether hovering only changes a hole around the cursor or focused
+    /// window, rather than the whole overlay.
+    #[inline]
+    fn hover_cuts_hole(&self) -> bool {
+        self.pixels.is_some() && (self.focus_follow || self.reveal.is_some())
+    }
+
+    /// Opacity the fade animation is heading for with the cursor at `pt`.
+    /// Idle dimming only ever darkens: an overlay already above the idle
+    /// opacity keeps its own.
+    #[inline]
+    fn target(&self, pt: POINT) -> u8 {
+        match self.idle_opacity {
+            Some(idle) => idle.max(self.base_opacity),
+            None if self.hovered(pt) && !self.hover_cuts_hole() => self.hover_opacity,
+            None => self.base_opacity,
+        }
+    }
+
+    /// Apply `opacity` to the whole window with `SetLayeredWindowAttributes`,
+    /// keying out the gaps of an orbit pattern.
+    unsafe fn set_uniform(&self, hwnd: HWND, opacity: u8) {
+        let (key, flags) = match self.orbit {
+            Some(_) => (COLORREF(ORBIT_KEY), LWA_ALPHA | LWA_COLORKEY),
+            None => (COLORREF(0), LWA_ALPHA),
+        };
+        unsafe {
+            let _ = SetLayeredWindowAttributes(hwnd, key, opacity, flags);
+        }
+    }
+
+    /// Switch between the uniform-alpha and per-pixel-alpha paths as the
+    /// current reveal, focus-follow and fill settings require.  Stays on
+    /// uniform alpha if the surface cannot be created; orbit overlays always
+    /// do.
+    unsafe fn sync_pixel_path(&mut self, hwnd: HWND) {
+        unsafe {
+            let wanted = self.orbit.is_none()
+                && (self.focus_follow || self.reveal.is_some() || !self.fill.is_uniform());
+            if !wanted {
+                if self.pixels.take().is_some() {
+                    self.set_uniform(hwnd, self.animator.current());
+                }
+                return;
+            }
+            let bounds = self.bounds();
+            match &mut self.pixels {
+                // Settings changed: draw everything afresh.
+                Some((mask, _)) => mask.resize(self.monitor, bounds),
+                None => {
+                    let Some(surface) = Surface::new(self.mon_w, self.mon_h) else {
+                        eprintln!(
+                            "[overlay] could not create the per-pixel surface; fading uniformly"
+                        );
+                        return;
+                    };
+                    reset_layering(hwnd);
+                    let mask = RevealMask::new(self.fill.clone(), self.monitor, self.bounds());
+                    self.pixels = Some((mask, surface));
+                }
+            }
+            let mut pt = POINT::default();
+            let _ = GetCursorPos(&mut pt);
+            self.repaint_pixels(hwnd, pt, 0);
+        }
+    }
+
+    /// Bring the per-pixel surface up to date — the focus cut-out advanced
+    /// by `dt_ms`, or the reveal around the cursor at `pt` — and push the
+    /// changed pixels to the window.  No-op on the uniform-alpha path.
+    unsafe fn repaint_pixels(&mut self, hwnd: HWND, pt: POINT, dt_ms: u32) {
+        if self.pixels.is_none() {
+            return;
+        }
+        let idle = self.idle_opacity.is_some();
+        let outside = self.animator.current();
+        let (hole, inside) = if self.focus_follow {
+            let overlay = self.bounds();
+            let fg = if idle {
+                None
+            } else {
+                shared_focus(Duration::from_millis(TIMER_MS as u64))
+            };
+            match self
+                .focus
+                .step(overlay, fg.as_ref(), outside, self.hover_opacity, dt_ms)
+            {
+                Some((rect, opacity)) => (Some(Hole::Rect(rect)), opacity),
+                None => (None, outside),
+            }
+        } else {
+            let hole = self
+                .reveal
+                .filter(|_| !idle && self.hovered(pt))
+                .map(|reveal| Hole::Around {
+                    reveal,
+                    x: pt.x - self.mon_x,
+                    y: pt.y - self.mon_y,
+                });
+            (hole, self.hover_opacity)
+        };
+
+        let origin = (self.mon_x, self.mon_y);
+        let Some((mask, surface)) = &mut self.pixels else {
+            return;
+        };
+        if let Some(dirty) = mask.update(hole, outside, inside) {
+            mask.render(surface.pixels(), dirty);
+            unsafe { surface.present(hwnd, origin, mask.size(), dirty) };
+        }
+    }
+}
+
+/// Win32 form of `color`.
+fn colorref(color: Rgb) -> COLORREF {
+    let Rgb(r, g, b) = color;
+    COLORREF(r as u32 | ((g as u32) << 8) | ((b as u32) << 16))
+}
+
+// ─── Orbit pattern ───────────────────────────────────────────────────────────
+
+/// Pattern brush and position of an orbit overlay.
+struct Orbit {
+    settings: OrbitSettings,
+    /// Tiles the pattern: black cells and [`ORBIT_KEY`] gaps.
+    brush: HBRUSH,
+    /// Offset the pattern was last painted at.
+    offset: (i32, i32),
+}
+
+impl Orbit {
+    unsafe fn new(settings: OrbitSettings) -> Option<Self> {
+        let side = tile_size(settings.cell);
+        let pixels: Vec<u32> = tile(settings.pattern, settings.cell)
+            .into_iter()
+            .map(|dark| if dark { 0 } else { ORBIT_KEY })
+            .collect();
+        unsafe {
+            let bitmap = CreateBitmap(
+                side as i32,
+                side as i32,
+                1,
+                32,
+                Some(pixels.as_ptr() as *const c_void),
+            );
+            if bitmap.is_invalid() {
+                return None;
+            }
+            // The brush keeps its own copy of the bitmap.
+            let brush = CreatePatternBrush(bitmap);
+            let _ = DeleteObject(bitmap.into());
+            if brush.is_invalid() {
+                return None;
+            }
+            Some(Self {
+                settings,
+                brush,
+                offset: settings.offset_at(SystemTime::now()),
+            })
+        }
+    }
+
+    /// Move along the path; returns `true` when the pattern must be redrawn.
+    fn advance(&mut self) -> bool {
+        let offset = self.settings.offset_at(SystemTime::now());
+        std::mem::replace(&mut self.offset, offset) != offset
+    }
+
+    /// Fill `rect` of `hdc` with the pattern at its current offset.
+    unsafe fn paint(&self, hdc: HDC, rect: &RECT) {
+        unsafe {
+            let _ = SetBrushOrgEx(hdc, self.offset.0, self.offset.1, None);
+            let _ = FillRect(hdc, rect, self.brush);
+        }
+    }
+}
+
+impl Drop for Orbit {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = DeleteObject(self.brush.into());
+        }
+    }
+}
+
+// ─── Per-pixel alpha surface ─────────────────────────────────────────────────
+
+/// Backing store of the per-pixel-alpha path: a top-down 32-bit DIB section
+/// selected into a memory DC, pushed to the window with
+/// `UpdateLayeredWindowIndirect`.
+struct Surface {
+    dc: HDC,
+    bitmap: HBITMAP,
+    /// Bitmap the DC held before ours, restored before deleting it.
+    previous: HGDIOBJ,
+    bits: *mut u32,
+    len: usize,
+}
+
+impl Surface {
+    unsafe fn new(width: i32, height: i32) -> Option<Self> {
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+        unsafe {
+            let dc = CreateCompatibleDC(None);
+            if dc.is_invalid() {
+                return None;
+            }
+            let info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: width,
+                    // Negative height: top-down rows, as RevealMask renders them.
+                    biHeight: -height,
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut bits: *mut c_void = std::ptr::null_mut();
+            let bitmap = match CreateDIBSection(Some(dc), &info, DIB_RGB_COLORS, &mut bits, None, 0)
+            {
+                Ok(bitmap) if !bits.is_null() => bitmap,
+                _ => {
+                    let _ = DeleteDC(dc);
+                    return None;
+                }
+            };
+            let previous = SelectObject(dc, bitmap.into());
+            Some(Self {
+                dc,
+                bitmap,
+                previous,
+                bits: bits.cast(),
+                len: width as usize * height as usize,
+            })
+        }
+    }
+
+    fn pixels(&mut self) -> &mut [u32] {
+        // SAFETY: the DIB section holds exactly `len` pixels and lives as
+        // long as `self`.
+        unsafe { std::slice::from_raw_parts_mut(self.bits, self.len) }
+    }
+
+    /// Show the surface as the window's content at `origin` with `size`,
+    /// redrawing only `dirty`.
+    unsafe fn present(&self, hwnd: HWND, origin: (i32, i32), size: (i32, i32), dirty: Rect) {
+        let dst = POINT {
+            x: origin.0,
+            y: origin.1,
+        };
+        let size = SIZE {
+            cx: size.0,
+            cy: size.1,
+        };
+        let src = POINT::default();
+        let blend = BLENDFUNCTION {
+            BlendOp: AC_SRC_OVER as u8,
+            BlendFlags: 0,
+            SourceConstantAlpha: 255,
+            AlphaFormat: AC_SRC_ALPHA as u8,
+        };
+        let (x, y, w, h) = dirty;
+        let dirty = RECT {
+            left: x,
+            top: y,
+            right: x + w,
+            bottom: y + h,
+        };
+        let info = UPDATELAYEREDWINDOWINFO {
+            cbSize: std::mem::size_of::<UPDATELAYEREDWINDOWINFO>() as u32,
+            hdcDst: HDC::default(),
+            pptDst: &dst,
+            psize: &size,
+            hdcSrc: self.dc,
+            pptSrc: &src,
+            crKey: COLORREF(0),
+            pblend: &blend,
+            dwFlags: ULW_ALPHA,
+            prcDirty: &dirty,
+        };
+        unsafe {
+            let _ = UpdateLayeredWindowIndirect(hwnd, &info);
+        }
+    }
+}
+
+impl Drop for Surface {
+    fn drop(&mut self) {
+        unsafe {
+            SelectObject(self.dc, self.previous);
+            let _ = DeleteObject(self.bitmap.into());
+            let _ = DeleteDC(self.dc);
+        }
+    }
+}
+
+/// Clear and set `WS_EX_LAYERED` again.  Once `SetLayeredWindowAttributes`
+/// has been used on a window, `UpdateLayeredWindow` fails until its
+/// layering is reset.
+unsafe fn reset_layering(hwnd: HWND) {
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        let layered = WS_EX_LAYERED.0 as isize;
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style & !layered);
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | layered);
+    }
+}
+
+// ─── Message packing ─────────────────────────────────────────────────────────
+
+/// Pack two signed 16-bit values into the low 32 bits of a message parameter,
+/// the same layout Windows uses for `WM_MOVE` / `WM_SIZE`.
+///
+/// Virtual-screen coordinates and monitor sizes always fit in 16 bits.
+pub fn pack_xy(a: i32, b: i32) -> usize {
+    ((a as i16 as u16 as u32) | ((b as i16 as u16 as u32) << 16)) as usize
+}
+
+/// Inverse of [`pack_xy`].
+fn unpack_xy(v: usize) -> (i32, i32) {
+    let v = v as u32;
+    (
+        (v & 0xFFFF) as u16 as i16 as i32,
+        (v >> 16) as u16 as i16 as i32,
+    )
+}
+
+/// Pack the hover settings of a [`WM_UPDATE_FADE`] into its `wparam`.
+pub fn pack_fade(
+    fade_on_hover: bool,
+    focus_follow: bool,
+    hover_opacity: u8,
+    curve: FadeCurve,
+) -> usize {
+    (fade_on_hover as usize)
+        | ((focus_follow as usize) << 1)
+        | ((curve.to_code() as usize) << 8)
+        | ((hover_opacity as usize) << 16)
+}
+
+/// Inverse of [`pack_fade`].
+fn unpack_fade(v: usize) -> (bool, bool, u8, FadeCurve) {
+    (
+        v & 1 != 0,
+        v & 2 != 0,
+        (v >> 16) as u8,
+        FadeCurve::from_code((v >> 8) as u8),
+    )
+}
+
+/// Pack the cursor reveal of a [`WM_UPDATE_REVEAL`] into its `wparam`
+/// (shape size, via [`pack_xy`]) and `lparam` (shape code and feather).
+pub fn pack_reveal(reveal: Option<RevealSettings>) -> (usize, isize) {
+    let Some(reveal) = reveal else {
+        return (0, 0);
+    };
+    let (code, a, b) = match reveal.shape {
+        RevealShape::Circle { radius } => (1, radius, radius),
+        RevealShape::Rectangle { width, height } => (2, width, height),
+    };
+    (
+        pack_xy(a as i32, b as i32),
+        code | (reveal.feather as isize) << 8,
+    )
+}
+
+/// Inverse of [`pack_reveal`].
+fn unpack_reveal(wparam: usize, lparam: isize) -> Option<RevealSettings> {
+    let (a, b) = unpack_xy(wparam);
+    let shape = match lparam & 0xFF {
+        1 => RevealShape::Circle { radius: a as u32 },
+        2 => RevealShape::Rectangle {
+            width: a as u32,
+            height: b as u32,
+        },
+        _ => return None,
+    };
+    Some(RevealSettings {
+        shape,
+        feather: (lparam >> 8) as u32,
+    })
 }
 
 // ─── Window procedure ────────────────────────────────────────────────────────
@@ -65,14 +539,24 @@ unsafe extern "system" fn wnd_proc(
 ) -> LRESULT {
     unsafe {
         match msg {
-            // ── Repaint: fill the window with opaque black ─────────────
+            // ── Repaint: the fill colour, or the orbit pattern ─────────
             WM_PAINT => {
                 let mut ps = PAINTSTRUCT::default();
                 let hdc = BeginPaint(hwnd, &mut ps);
                 if !hdc.is_invalid() {
-                    let brush = CreateSolidBrush(COLORREF(0x00000000));
-                    if !brush.is_invalid() {
-                        let _ = FillRect(hdc, &ps.rcPaint, brush);
+                    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FadeState;
+                    let state = ptr.as_ref();
+                    match state.and_then(|state| state.orbit.as_ref()) {
+                        Some(orbit) => orbit.paint(hdc, &ps.rcPaint),
+                        None => {
+                            // Gradients only show on the per-pixel path.
+                            let color = state.map_or(Rgb::BLACK, |state| state.fill.flat_color());
+                            let brush = CreateSolidBrush(colorref(color));
+                            if !brush.is_invalid() {
+                                let _ = FillRect(hdc, &ps.rcPaint, brush);
+                                let _ = DeleteObject(brush.into());
+                            }
+                        }
                     }
                     let _ = EndPaint(hwnd, &ps);
                 }
@@ -87,14 +571,18 @@ unsafe extern "system" fn wnd_proc(
                 if !ptr.is_null() {
                     (*ptr).base_opacity = new_base;
 
-                    // Only apply immediately when the cursor is NOT on the
-                    // monitor; otherwise the fade timer will pick it up once
-                    // the cursor leaves.
+                    // Only apply immediately when the new base is what the
+                    // overlay should show (cursor away, not idle); otherwise
+                    // the fade timer will pick it up later.
                     let mut pt = POINT::default();
                     let _ = GetCursorPos(&mut pt);
-                    if !(*ptr).cursor_on_monitor(pt) {
-                        (*ptr).current_opacity = new_base;
-                        let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), new_base, LWA_ALPHA);
+                    if (*ptr).target(pt) == new_base {
+                        (*ptr).animator.jump(new_base);
+                        if (*ptr).pixels.is_some() {
+                            (*ptr).repaint_pixels(hwnd, pt, 0);
+                        } else {
+                            (*ptr).set_uniform(hwnd, new_base);
+                        }
                     }
                 } else {
                     // Fallback: no fade state yet, apply directly.
@@ -104,6 +592,101 @@ unsafe extern "system" fn wnd_proc(
                 LRESULT(0)
             }
 
+            // ── User request: change hover behaviour ───────────────────
+            WM_UPDATE_FADE => {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FadeState;
+                if !ptr.is_null() {
+                    let (fade_on_hover, focus_follow, hover_opacity, curve) = unpack_fade(wparam.0);
+                    let state = &mut *ptr;
+                    state.fade_on_hover = fade_on_hover;
+                    state.hover_opacity = hover_opacity;
+                    state.animator.configure(curve, lparam.0 as u32);
+                    state.focus.configure(curve, lparam.0 as u32);
+                    if state.focus_follow != focus_follow {
+                        state.focus_follow = focus_follow;
+                        state.sync_pixel_path(hwnd);
+                    } else {
+                        // A new hover opacity changes the hole.
+                        let mut pt = POINT::default();
+                        let _ = GetCursorPos(&mut pt);
+                        state.repaint_pixels(hwnd, pt, 0);
+                    }
+                }
+                // The timer fades towards the new target on its next tick.
+                LRESULT(0)
+            }
+
+            // ── User request: change the cursor reveal ─────────────────
+            WM_UPDATE_REVEAL => {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FadeState;
+                if !ptr.is_null() {
+                    (*ptr).reveal = unpack_reveal(wparam.0, lparam.0);
+                    (*ptr).sync_pixel_path(hwnd);
+                }
+                LRESULT(0)
+            }
+
+            // ── Idle dimming on / off ──────────────────────────────────
+            WM_UPDATE_IDLE => {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FadeState;
+                if !ptr.is_null() {
+                    (*ptr).idle_opacity = (lparam.0 != 0).then_some(wparam.0 as u8);
+                    // Idleness hides the reveal hole even if the opacity
+                    // stays put.
+                    let mut pt = POINT::default();
+                    let _ = GetCursorPos(&mut pt);
+                    (*ptr).repaint_pixels(hwnd, pt, 0);
+                }
+                // The timer ramps towards the new target on its next tick.
+                LRESULT(0)
+            }
+
+            // ── Topology change: the monitor a fill spans moved ────────
+            WM_UPDATE_MONITOR => {
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FadeState;
+                if !ptr.is_null() {
+                    let (x, y) = unpack_xy(wparam.0);
+                    let (w, h) = unpack_xy(lparam.0 as usize);
+                    (*ptr).monitor = (x, y, w, h);
+                }
+                LRESULT(0)
+            }
+
+            // ── Topology change: follow the monitor's new geometry ─────
+            WM_UPDATE_BOUNDS => {
+                let (x, y) = unpack_xy(wparam.0);
+                let (w, h) = unpack_xy(lparam.0 as usize);
+
+                let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FadeState;
+                if !ptr.is_null() {
+                    (*ptr).mon_x = x;
+                    (*ptr).mon_y = y;
+                    (*ptr).mon_w = w;
+                    (*ptr).mon_h = h;
+
+                    // The per-pixel surface must match the window's size.
+                    let (monitor, bounds) = ((*ptr).monitor, (*ptr).bounds());
+                    if let Some((mask, surface)) = &mut (*ptr).pixels {
+                        match Surface::new(w, h) {
+                            Some(resized) => {
+                                *surface = resized;
+                                mask.resize(monitor, bounds);
+                            }
+                            None => {
+                                (*ptr).pixels = None;
+                                (*ptr).set_uniform(hwnd, (*ptr).animator.current());
+                            }
+                        }
+                    }
+                    let mut pt = POINT::default();
+                    let _ = GetCursorPos(&mut pt);
+                    (*ptr).repaint_pixels(hwnd, pt, 0);
+                }
+
+                let _ = SetWindowPos(hwnd, Some(HWND_TOPMOST), x, y, w, h, SWP_NOACTIVATE);
+                LRESULT(0)
+            }
+
             // ── Cursor-tracking / fade animation tick ─────────────────
             WM_TIMER if wparam.0 == TIMER_ID => {
                 let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut FadeState;
@@ -116,27 +699,36 @@ unsafe extern "system" fn wnd_proc(
                 let mut pt = POINT::default();
                 let _ = GetCursorPos(&mut pt);
 
-                // Cursor on our monitor → fade to 0; cursor away → fade back.
-                let target: u8 = if state.cursor_on_monitor(pt) {
-                    0
-                } else {
-                    state.base_opacity
-                };
+                // Idle → ramp to the idle opacity; cursor on our overlay →
+                // fade to the hover opacity (unless only a hole around the
+                // cursor or focused window changes); cursor away → fade back.
+                state.animator.set_target(state.target(pt));
+
+                // Advance by the real time since the last tick, so a late
+                // timer does not slow the fade down.
+                let now = Instant::now();
+                let dt = now.duration_since(state.last_tick).as_millis() as u32;
+                state.last_tick = now;
+
+                let before = state.animator.current();
+                let opacity = state.animator.step(dt);
+                state.shown.store(opacity, Ordering::Relaxed);
+                let moved = pt != state.last_cursor;
+                state.last_cursor = pt;
+                if state.pixels.is_some() {
+                    // Follows the cursor / focus even when the opacity is
+                    // settled, but a still cursor over a settled overlay
+                    // leaves the mask as it is.
+                    if opacity != before || moved || state.focus_follow {
+                        state.repaint_pixels(hwnd, pt, dt);
+                    }
+                } else if opacity != before {
+                    state.set_uniform(hwnd, opacity);
+                }
 
-                // Step current_opacity one FADE_STEP closer to target.
-                if state.current_opacity != target {
-                    let new_opacity = if state.current_opacity > target {
-                        state.current_opacity.saturating_sub(FADE_STEP).max(target)
-                    } else {
-                        state.current_opacity.saturating_add(FADE_STEP).min(target)
-                    };
-                    state.current_opacity = new_opacity;
-                    let _ = SetLayeredWindowAttributes(
-                        hwnd,
-                        COLORREF(0),
-                        state.current_opacity,
-                        LWA_ALPHA,
-                    );
+                // Drift the orbit pattern once its next step is due.
+                if state.orbit.as_mut().is_some_and(Orbit::advance) {
+                    let _ = InvalidateRect(Some(hwnd), None, false);
                 }
 
                 LRESULT(0)
@@ -200,19 +792,33 @@ pub unsafe fn register_overlay_class() -> Result<(), Box<dyn std::error::Error>>
 /// Create a Win32 overlay window and run its message loop **on the current thread**.
 ///
 /// Properties:
-/// * Layered (`WS_EX_LAYERED`) — alpha transparency via `SetLayeredWindowAttributes`.
+/// * Layered (`WS_EX_LAYERED`) — uniform alpha via `SetLayeredWindowAttributes`,
+///   or per-pixel alpha while revealing around the cursor, following focus or
+///   showing a gradient fill.
 /// * Click-through (`WS_EX_TRANSPARENT`, `WS_DISABLED`).
 /// * Always on top (`WS_EX_TOPMOST`).
 /// * Hidden from the taskbar (`WS_EX_TOOLWINDOW`).
 /// * Never steals focus (`WS_EX_NOACTIVATE`).
 ///
-/// A 16 ms `WM_TIMER` drives a fade animation: the overlay fades to opacity 0
-/// while the cursor is on its monitor and fades back to `config.opacity` when
-/// the cursor moves away.
+/// A 16 ms `WM_TIMER` drives a fade animation: when `config.fade_on_hover` is
+/// set the overlay fades to `config.hover_opacity` while the cursor is on it
+/// and back to `config.opacity` when the cursor moves away, each fade taking
+/// `config.fade_ms` along `config.fade_curve` (see [`Animator`]).  With
+/// `config.reveal` set, only the area around the cursor goes to the hover
+/// opacity; with `config.focus_follow` set, the foreground window's
+/// rectangle does instead (see [`FocusCut`]).  Both, and gradient or
+/// vignette fills, switch the window to per-pixel alpha via
+/// `UpdateLayeredWindowIndirect`.
 ///
-/// Once the window is ready its `HWND` (as `usize`) is sent through `hwnd_tx`.
+/// An [`OverlayKind::Orbit`] overlay paints its pattern instead of black,
+/// with the gaps keyed out, and moves it along the orbit path (see
+/// [`orbit`](super::orbit)); it always uses uniform alpha.
+///
+/// The opacity the overlay shows is kept in `shown`.  Once the window is
+/// ready its `HWND` (as `usize`) is sent through `hwnd_tx`.
 fn create_win32_overlay(
     config: OverlayConfig,
+    shown: Arc<AtomicU8>,
     hwnd_tx: mpsc::Sender<usize>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     unsafe {
@@ -252,28 +858,63 @@ fn create_win32_overlay(
 
         // ── Determine initial opacity based on current cursor position ──
         // If the cursor is already on this monitor when protection is
-        // activated, start transparent so there is no jarring flash.
+        // activated, start at the hover opacity so there is no jarring flash.
         let mut cursor_pt = POINT::default();
         let _ = GetCursorPos(&mut cursor_pt);
-        let cursor_on = cursor_pt.x >= config.x
+        let cursor_on = config.fade_on_hover
+            && cursor_pt.x >= config.x
             && cursor_pt.x < config.x + config.width
             && cursor_pt.y >= config.y
             && cursor_pt.y < config.y + config.height;
-        let initial_opacity: u8 = if cursor_on { 0 } else { config.opacity };
+        let orbit = match config.kind {
+            OverlayKind::Dim => None,
+            OverlayKind::Orbit(settings) => {
+                let orbit = Orbit::new(settings);
+                if orbit.is_none() {
+                    // A uniform tint at the orbit strength is still harmless.
+                    eprintln!("[overlay] could not create the orbit pattern; tinting instead");
+                }
+                orbit
+            }
+        };
+        let uniform = config.reveal.is_none() && !config.focus_follow;
+        let initial_opacity: u8 = if cursor_on && uniform {
+            config.hover_opacity
+        } else {
+            config.opacity
+        };
 
         // ── Attach per-window fade state ────────────────────────────────
+        shown.store(initial_opacity, Ordering::Relaxed);
         let fade_state = Box::new(FadeState {
             base_opacity: config.opacity,
-            current_opacity: initial_opacity,
+            fade_on_hover: config.fade_on_hover,
+            focus_follow: config.focus_follow,
+            hover_opacity: config.hover_opacity,
+            reveal: config.reveal,
+            animator: Animator::new(initial_opacity, config.fade_curve, config.fade_ms),
+            shown,
+            focus: FocusCut::new(config.fade_curve, config.fade_ms),
+            last_tick: Instant::now(),
+            last_cursor: POINT::default(),
+            idle_opacity: None,
+            pixels: None,
+            orbit,
+            fill: config.fill,
+            monitor: config.monitor,
             mon_x: config.x,
             mon_y: config.y,
             mon_w: config.width,
             mon_h: config.height,
         });
-        SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(fade_state) as isize);
+        let state = Box::into_raw(fade_state);
+        SetWindowLongPtrW(hwnd, GWLP_USERDATA, state as isize);
 
-        // ── Apply initial opacity and show the window ───────────────────
-        let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), initial_opacity, LWA_ALPHA);
+        // ── Apply initial opacity (or mask) and show the window ─────────
+        (*state).sync_pixel_path(hwnd);
+        if (*state).pixels.is_none() {
+            (*state).set_uniform(hwnd, initial_opacity);
+        }
         let _ = ShowWindow(hwnd, SW_SHOW);
         let _ = SetWindowPos(
             hwnd,
@@ -287,7 +928,7 @@ fn create_win32_overlay(
         let _ = UpdateWindow(hwnd);
 
         // ── Start the cursor-tracking / fade timer (~60 fps) ───────────
-        SetTimer(Some(hwnd), TIMER_ID, 16, None);
+        SetTimer(Some(hwnd), TIMER_ID, TIMER_MS, None);
 
         let mut msg = MSG::default();
         while GetMessageW(&mut msg, None, 0, 0).as_bool() {
@@ -303,9 +944,10 @@ fn create_win32_overlay(
 
 pub fn spawn_overlay(
     config: OverlayConfig,
+    shown: Arc<AtomicU8>,
     hwnd_tx: mpsc::Sender<usize>,
 ) -> std::thread::JoinHandle<()> {
-    std::thread::spawn(move || match create_win32_overlay(config, hwnd_tx) {
+    std::thread::spawn(move || match create_win32_overlay(config, shown, hwnd_tx) {
         Ok(()) => {}
         Err(e) => eprintln!("Overlay thread error: {:?}", e),
     })
@@ -0,0 +1,385 @@
+//! Time-of-day protection schedules.
+//!
+//! A [`ScheduleRule`] such as "weekdays 09:00–18:00: monitors A, B at 30 %"
+//! protects its monitors while local time is inside its window.  The daemon
+//! applies it as an override on top of the user's own selection, opacity and
+//! active flag, which it leaves untouched.  The [`Scheduler`] evaluates the
+//! rules against a [`Clock`] and reports a [`ScheduleAction`] only when the
+//! combined target changes, so switching protection off inside a window
+//! holds until the next rule boundary.
+//!
+//! Everything here is free of Win32 calls except [`SystemClock`]; tests
+//! drive the scheduler with their own [`Clock`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::monitor::MonitorId;
+
+// ── Time ─────────────────────────────────────────────────────────────────────
+
+/// Day of the week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl Weekday {
+    /// All days, Monday first.
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ];
+
+    /// Day from a 0-based index counted from Monday (wraps).
+    pub fn from_monday(index: u32) -> Self {
+        Self::ALL[(index % 7) as usize]
+    }
+
+    /// The day before this one.
+    pub fn prev(self) -> Self {
+        Self::from_monday(self as u32 + 6)
+    }
+}
+
+/// Minutes since local midnight, written as `"HH:MM"` in JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay(u16);
+
+impl TimeOfDay {
+    /// `hour:minute`, or `None` if out of range.
+    pub fn new(hour: u16, minute: u16) -> Option<Self> {
+        (hour < 24 && minute < 60).then_some(Self(hour * 60 + minute))
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, String> {
+        s.parse()
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(t: TimeOfDay) -> String {
+        t.to_string()
+    }
+}
+
+impl std::str::FromStr for TimeOfDay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (h, m) = s
+            .split_once(':')
+            .ok_or_else(|| format!("invalid time '{}' (expected HH:MM)", s))?;
+        h.parse()
+            .ok()
+            .zip(m.parse().ok())
+            .and_then(|(h, m)| Self::new(h, m))
+            .ok_or_else(|| format!("invalid time '{}' (expected HH:MM)", s))
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.0 / 60, self.0 % 60)
+    }
+}
+
+/// A point in local wall-clock time, at minute resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub weekday: Weekday,
+    pub time: TimeOfDay,
+}
+
+/// Source of the current local time.
+pub trait Clock {
+    fn now(&self) -> LocalTime;
+}
+
+/// The system's local time.
+///
+/// Non-Windows builds have no time-zone database available without extra
+/// dependencies and use UTC instead.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[cfg(windows)]
+    fn now(&self) -> LocalTime {
+        use windows::Win32::System::SystemInformation::GetLocalTime;
+        let st = unsafe { GetLocalTime() };
+        LocalTime {
+            // SYSTEMTIME counts days from Sunday.
+            weekday: Weekday::from_monday(st.wDayOfWeek as u32 + 6),
+            time: TimeOfDay(st.wHour * 60 + st.wMinute),
+        }
+    }
+
+    #[cfg(not(windows))]
+    fn now(&self) -> LocalTime {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let days = secs / 86_400;
+        LocalTime {
+            // 1970-01-01 was a Thursday.
+            weekday: Weekday::from_monday(((days + 3) % 7) as u32),
+            time: TimeOfDay(((secs % 86_400) / 60) as u16),
+        }
+    }
+}
+
+// ── Rules ────────────────────────────────────────────────────────────────────
+
+/// "On `days`, from `start` to `end`, protect `monitors` at `opacity`."
+///
+/// A window whose `end` is earlier than its `start` runs past midnight; the
+/// part after midnight belongs to the day it started on.  `start == end`
+/// covers the whole day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleRule {
+    /// Assigned by the daemon when the rule is added.
+    #[serde(default)]
+    pub id: u32,
+    pub days: BTreeSet<Weekday>,
+    pub start: TimeOfDay,
+    pub end: TimeOfDay,
+    pub monitors: BTreeSet<MonitorId>,
+    /// Overlay opacity (0 = transparent, 255 = opaque).
+    pub opacity: u8,
+}
+
+impl ScheduleRule {
+    /// Why this rule cannot be used, if anything.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.days.is_empty() {
+            return Err("a schedule needs at least one day".into());
+        }
+        if self.monitors.is_empty() {
+            return Err("a schedule needs at least one monitor".into());
+        }
+        Ok(())
+    }
+
+    /// Whether `now` falls inside this rule's window.
+    pub fn matches(&self, now: LocalTime) -> bool {
+        let (start, end, t) = (self.start, self.end, now.time);
+        if start == end {
+            self.days.contains(&now.weekday)
+        } else if start < end {
+            self.days.contains(&now.weekday) && start <= t && t < end
+        } else {
+            (self.days.contains(&now.weekday) && t >= start)
+                || (self.days.contains(&now.weekday.prev()) && t < end)
+        }
+    }
+}
+
+/// Opacity per monitor that the rules call for at `now`.  When several rules
+/// cover a monitor the first one in `rules` wins.  Empty means the schedule
+/// wants protection off.
+pub fn evaluate(rules: &[ScheduleRule], now: LocalTime) -> BTreeMap<MonitorId, u8> {
+    let mut target = BTreeMap::new();
+    for rule in rules.iter().filter(|r| r.matches(now)) {
+        for id in &rule.monitors {
+            target.entry(id.clone()).or_insert(rule.opacity);
+        }
+    }
+    target
+}
+
+// ── Scheduler ────────────────────────────────────────────────────────────────
+
+/// What the daemon should do after a [`Scheduler::tick`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleAction {
+    /// Protect exactly these monitors at these opacities.
+    Apply(BTreeMap<MonitorId, u8>),
+    /// The last window closed: lift the override.
+    Deactivate,
+}
+
+/// Edge-triggered rule evaluation.
+pub struct Scheduler<C: Clock> {
+    clock: C,
+    /// Target of the previous tick; `None` before the first one.
+    last: Option<BTreeMap<MonitorId, u8>>,
+}
+
+impl<C: Clock> Scheduler<C> {
+    pub fn new(clock: C) -> Self {
+        Self { clock, last: None }
+    }
+
+    /// Evaluate `rules` now and report a change of target, if any.
+    ///
+    /// The first tick only acts if a window is open, so starting the daemon
+    /// outside every window keeps the state restored from settings.
+    pub fn tick(&mut self, rules: &[ScheduleRule]) -> Option<ScheduleAction> {
+        let target = evaluate(rules, self.clock.now());
+        let action = match &self.last {
+            Some(last) if *last == target => None,
+            _ if target.is_empty() => self.last.is_some().then_some(ScheduleAction::Deactivate),
+            _ => Some(ScheduleAction::Apply(target.clone())),
+        };
+        self.last = Some(target);
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake::Fake;
+
+    impl Clock for Fake<LocalTime> {
+        fn now(&self) -> LocalTime {
+            self.get()
+        }
+    }
+
+    fn at(weekday: Weekday, time: &str) -> LocalTime {
+        LocalTime {
+            weekday,
+            time: time.parse().unwrap(),
+        }
+    }
+
+    fn mon(name: &str) -> MonitorId {
+        MonitorId(name.to_string())
+    }
+
+    fn rule(
+        days: &[Weekday],
+        start: &str,
+        end: &str,
+        monitors: &[&str],
+        opacity: u8,
+    ) -> ScheduleRule {
+        ScheduleRule {
+            id: 0,
+            days: days.iter().copied().collect(),
+            start: start.parse().unwrap(),
+            end: end.parse().unwrap(),
+            monitors: monitors.iter().map(|m| mon(m)).collect(),
+            opacity,
+        }
+    }
+
+    fn apply(pairs: &[(&str, u8)]) -> Option<ScheduleAction> {
+        Some(ScheduleAction::Apply(
+            pairs.iter().map(|(m, o)| (mon(m), *o)).collect(),
+        ))
+    }
+
+    #[test]
+    fn window_opens_and_closes_once() {
+        let clock = Fake::new(at(Weekday::Mon, "08:59"));
+        let mut scheduler = Scheduler::new(clock.clone());
+        let rules = [rule(&[Weekday::Mon], "09:00", "18:00", &["A"], 80)];
+
+        assert_eq!(scheduler.tick(&rules), None);
+        clock.set(at(Weekday::Mon, "09:00"));
+        assert_eq!(scheduler.tick(&rules), apply(&[("A", 80)]));
+        clock.set(at(Weekday::Mon, "12:00"));
+        assert_eq!(scheduler.tick(&rules), None);
+        clock.set(at(Weekday::Mon, "18:00"));
+        assert_eq!(scheduler.tick(&rules), Some(ScheduleAction::Deactivate));
+        clock.set(at(Weekday::Mon, "19:00"));
+        assert_eq!(scheduler.tick(&rules), None);
+    }
+
+    #[test]
+    fn first_tick_inside_a_window_applies_it() {
+        let clock = Fake::new(at(Weekday::Tue, "10:00"));
+        let mut scheduler = Scheduler::new(clock);
+        let rules = [rule(&[Weekday::Tue], "09:00", "18:00", &["A"], 80)];
+        assert_eq!(scheduler.tick(&rules), apply(&[("A", 80)]));
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_its_start_day() {
+        let clock = Fake::new(at(Weekday::Fri, "21:59"));
+        let mut scheduler = Scheduler::new(clock.clone());
+        let rules = [rule(&[Weekday::Fri], "22:00", "06:00", &["A"], 120)];
+
+        assert_eq!(scheduler.tick(&rules), None);
+        clock.set(at(Weekday::Fri, "22:00"));
+        assert_eq!(scheduler.tick(&rules), apply(&[("A", 120)]));
+        clock.set(at(Weekday::Sat, "05:59"));
+        assert_eq!(scheduler.tick(&rules), None);
+        clock.set(at(Weekday::Sat, "06:00"));
+        assert_eq!(scheduler.tick(&rules), Some(ScheduleAction::Deactivate));
+        // Saturday evening is not part of the rule.
+        clock.set(at(Weekday::Sat, "23:00"));
+        assert_eq!(scheduler.tick(&rules), None);
+    }
+
+    #[test]
+    fn overlapping_rules_merge_with_the_first_winning() {
+        let clock = Fake::new(at(Weekday::Wed, "10:00"));
+        let mut scheduler = Scheduler::new(clock.clone());
+        let rules = [
+            rule(&[Weekday::Wed], "09:00", "12:00", &["A"], 50),
+            rule(&[Weekday::Wed], "11:00", "14:00", &["A", "B"], 200),
+        ];
+
+        assert_eq!(scheduler.tick(&rules), apply(&[("A", 50)]));
+        clock.set(at(Weekday::Wed, "11:00"));
+        assert_eq!(scheduler.tick(&rules), apply(&[("A", 50), ("B", 200)]));
+        clock.set(at(Weekday::Wed, "12:00"));
+        assert_eq!(scheduler.tick(&rules), apply(&[("A", 200), ("B", 200)]));
+        clock.set(at(Weekday::Wed, "14:00"));
+        assert_eq!(scheduler.tick(&rules), Some(ScheduleAction::Deactivate));
+    }
+
+    #[test]
+    fn edited_rules_take_effect_on_the_next_tick() {
+        let clock = Fake::new(at(Weekday::Thu, "10:00"));
+        let mut scheduler = Scheduler::new(clock);
+        let mut rules = vec![rule(&[Weekday::Thu], "09:00", "18:00", &["A"], 80)];
+
+        assert_eq!(scheduler.tick(&rules), apply(&[("A", 80)]));
+        rules[0].opacity = 90;
+        assert_eq!(scheduler.tick(&rules), apply(&[("A", 90)]));
+        rules.clear();
+        assert_eq!(scheduler.tick(&rules), Some(ScheduleAction::Deactivate));
+    }
+
+    #[test]
+    fn whole_day_rule() {
+        let rule = rule(&[Weekday::Sun], "00:00", "00:00", &["A"], 80);
+        let at = |weekday, time: &str| LocalTime {
+            weekday,
+            time: time.parse().unwrap(),
+        };
+        assert!(rule.matches(at(Weekday::Sun, "00:00")));
+        assert!(rule.matches(at(Weekday::Sun, "23:59")));
+        assert!(!rule.matches(at(Weekday::Mon, "00:00")));
+    }
+
+    #[test]
+    fn parses_and_prints_times() {
+        assert_eq!("07:05".parse::<TimeOfDay>().unwrap().to_string(), "07:05");
+        assert!("24:00".parse::<TimeOfDay>().is_err());
+        assert!("7".parse::<TimeOfDay>().is_err());
+    }
+}
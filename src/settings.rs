@@ -0,0 +1,408 @@
+//! Persistent daemon settings.
+//!
+//! The daemon stores its user-facing state (opacity, monitor selection,
+//! per-monitor overlay behaviour, schedules, idle dimming, application rules,
+//! fullscreen pausing, content analysis and whether protection is enabled)
+//! in `%APPDATA%\OLED Care\settings.json` so that it survives restarts.
+//!
+//! The file carries a `version` field.  Older files are upgraded by
+//! [`migrate`] before being deserialized; unreadable or corrupt files are
+//! moved aside and replaced by defaults so a bad file can never keep the
+//! daemon from starting.  A file written by a newer version is not corrupt:
+//! it is left in place, and the daemon runs on defaults without saving over
+//! it (see [`Settings::is_from_newer_version`]).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::apps::AppRule;
+use crate::idle::IdleSettings;
+use crate::monitor::MonitorId;
+use crate::overlay::MonitorSettings;
+use crate::schedule::ScheduleRule;
+
+/// Current on-disk schema version.  Bump this and add a step to [`migrate`]
+/// whenever the layout of [`Settings`] changes incompatibly.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Directory (below the per-user config root) that holds our files.
+const APP_DIR: &str = "OLED Care";
+
+/// File name of the settings document inside [`APP_DIR`].
+const FILE_NAME: &str = "settings.json";
+
+// ── Settings document ────────────────────────────────────────────────────────
+
+/// Everything the daemon persists between runs.
+///
+/// Missing fields fall back to their [`Default`] values, so adding a field
+/// with a sensible default does not require a schema bump.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Schema version of this document (see [`SCHEMA_VERSION`]).
+    pub version: u32,
+    /// Overlay opacity (0 = transparent, 255 = opaque).
+    pub opacity: u8,
+    /// Stable ids of the monitors selected for protection.
+    pub selected: BTreeSet<MonitorId>,
+    /// Index-based selection carried over from a v1 file.  The daemon maps it
+    /// onto the monitors it enumerates on the first start after upgrading and
+    /// then drops it, since ids cannot be recovered from indices offline.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub legacy_selected: Vec<bool>,
+    /// Per-monitor overlay behaviour, keyed by monitor id.
+    pub monitor_settings: BTreeMap<MonitorId, MonitorSettings>,
+    /// Whether overlay protection was enabled when the daemon last saved.
+    pub overlays_active: bool,
+    /// Time-of-day protection rules, in priority order.
+    pub schedules: Vec<ScheduleRule>,
+    /// Idle dimming thresholds.
+    pub idle: IdleSettings,
+    /// Per-application rules, in priority order.
+    pub app_rules: Vec<AppRule>,
+    /// Pause a monitor's overlays while a fullscreen application covers it.
+    /// Off unless the user opts in, so upgrading never drops protection.
+    pub pause_on_fullscreen: bool,
+    /// Sample the screen to suggest regions over static content.
+    pub analyze_content: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            opacity: 50,
+            selected: BTreeSet::new(),
+            legacy_selected: Vec::new(),
+            monitor_settings: BTreeMap::new(),
+            overlays_active: false,
+            schedules: Vec::new(),
+            idle: IdleSettings::default(),
+            app_rules: Vec::new(),
+            pause_on_fullscreen: false,
+            analyze_content: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Whether the document was written by a newer version of OLED Care.
+    /// Such a file must not be overwritten, or going back to the newer
+    /// version would lose its settings.
+    pub fn is_from_newer_version(&self) -> bool {
+        self.version > SCHEMA_VERSION
+    }
+}
+
+// ── Location ─────────────────────────────────────────────────────────────────
+
+/// Per-user directory that holds the settings file and other daemon data.
+///
+/// `%APPDATA%` on Windows; `$XDG_CONFIG_HOME` or `~/.config` elsewhere.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("APPDATA")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(base.join(APP_DIR))
+}
+
+/// Full path of the settings file, or `None` if no config directory exists.
+pub fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|d| d.join(FILE_NAME))
+}
+
+// ── Load ─────────────────────────────────────────────────────────────────────
+
+/// Load settings from the default location.
+///
+/// Never fails: a missing file yields defaults, and a corrupt one is renamed
+/// to `settings.json.corrupt` before defaults are returned.  A file from a
+/// newer version is left alone; the defaults returned for it carry its
+/// version.
+pub fn load() -> Settings {
+    match settings_path() {
+        Some(path) => load_from(&path),
+        None => Settings::default(),
+    }
+}
+
+/// Load settings from `path`, see [`load`].
+pub fn load_from(path: &Path) -> Settings {
+    let bytes = match fs::read(path) {
+        Ok(b) => b,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Settings::default(),
+        Err(e) => {
+            eprintln!("[settings] Cannot read {}: {:?}", path.display(), e);
+            return Settings::default();
+        }
+    };
+
+    if let Some(version) = newer_version(&bytes) {
+        eprintln!(
+            "[settings] {} is from a newer version (schema {}); using defaults and leaving it untouched",
+            path.display(),
+            version
+        );
+        return Settings {
+            version,
+            ..Settings::default()
+        };
+    }
+
+    match parse(&bytes) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!(
+                "[settings] {} is unusable ({}); falling back to defaults",
+                path.display(),
+                e
+            );
+            // Keep the bad file around for inspection instead of silently
+            // overwriting it on the next save.
+            let _ = fs::rename(path, path.with_extension("json.corrupt"));
+            Settings::default()
+        }
+    }
+}
+
+/// The schema version of a document newer than [`SCHEMA_VERSION`], if it
+/// is one.
+fn newer_version(bytes: &[u8]) -> Option<u32> {
+    let value: Value = serde_json::from_slice(bytes).ok()?;
+    let version = value.get("version")?.as_u64()?;
+    (version > SCHEMA_VERSION as u64).then_some(version as u32)
+}
+
+/// Decode a settings document, applying migrations first.
+fn parse(bytes: &[u8]) -> Result<Settings, String> {
+    let mut value: Value = serde_json::from_slice(bytes).map_err(|e| e.to_string())?;
+    migrate(&mut value)?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Upgrade a raw settings document to [`SCHEMA_VERSION`] in place.
+///
+/// Each step handles exactly one version bump so the chain can be extended
+/// without touching earlier steps.
+fn migrate(value: &mut Value) -> Result<(), String> {
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| "settings root is not an object".to_string())?;
+
+    let mut version = obj.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "schema version {} is newer than supported version {}",
+            version, SCHEMA_VERSION
+        ));
+    }
+
+    // v0 → v1: files written before versioning had the same layout but no
+    // `version` field.
+    if version == 0 {
+        version = 1;
+    }
+
+    // v1 → v2: `selected` changed from per-index flags to a set of monitor
+    // ids.  Park the flags in `legacy_selected` for the daemon to resolve.
+    if version == 1 {
+        if let Some(old) = obj.remove("selected") {
+            obj.insert("legacy_selected".into(), old);
+        }
+        version = 2;
+    }
+
+    // v2 → v3: the per-tick `fade_speed` became a duration, `fade_ms`.  A
+    // full 0–255 fade took ceil(255 / speed) ticks of 16 ms.
+    if version == 2 {
+        if let Some(monitors) = obj
+            .get_mut("monitor_settings")
+            .and_then(Value::as_object_mut)
+        {
+            for ms in monitors.values_mut().filter_map(Value::as_object_mut) {
+                if let Some(speed) = ms.remove("fade_speed").and_then(|v| v.as_u64()) {
+                    let ticks = 255u64.div_ceil(speed.max(1));
+                    ms.insert("fade_ms".into(), Value::from(ticks * 16));
+                }
+            }
+        }
+        version = 3;
+    }
+
+    obj.insert("version".into(), Value::from(version));
+    Ok(())
+}
+
+// ── Save ─────────────────────────────────────────────────────────────────────
+
+/// Save settings to the default location.
+pub fn save(settings: &Settings) -> io::Result<()> {
+    let path = settings_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    save_to(&path, settings)
+}
+
+/// Atomically write `settings` to `path`.
+///
+/// The document is written to a sibling temporary file, flushed to disk and
+/// then renamed over the target, so a crash mid-write never leaves a
+/// truncated settings file behind.
+pub fn save_to(path: &Path, settings: &Settings) -> io::Result<()> {
+    let mut doc = settings.clone();
+    doc.version = SCHEMA_VERSION;
+    let body = serde_json::to_vec_pretty(&doc)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write_atomic(path, &body)
+}
+
+/// Write `body` to `path` through a sibling temporary file (see
+/// [`save_to`]), creating the directory if needed.
+pub fn write_atomic(path: &Path, body: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tmp = path.with_extension("json.tmp");
+    {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(body)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test's files.
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "oled-care-settings-{}-{}",
+            test,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn parse_json(value: Value) -> Settings {
+        parse(value.to_string().as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn unversioned_files_are_upgraded() {
+        let settings = parse_json(serde_json::json!({
+            "opacity": 80,
+            "selected": [true, false],
+        }));
+        assert_eq!(settings.version, SCHEMA_VERSION);
+        assert_eq!(settings.opacity, 80);
+        assert_eq!(settings.legacy_selected, [true, false]);
+        assert!(settings.selected.is_empty());
+    }
+
+    #[test]
+    fn v1_selection_flags_become_legacy_selected() {
+        let settings = parse_json(serde_json::json!({
+            "version": 1,
+            "selected": [false, true, true],
+        }));
+        assert_eq!(settings.version, SCHEMA_VERSION);
+        assert_eq!(settings.legacy_selected, [false, true, true]);
+        assert!(settings.selected.is_empty());
+    }
+
+    #[test]
+    fn v2_fade_speed_becomes_fade_ms() {
+        let settings = parse_json(serde_json::json!({
+            "version": 2,
+            "selected": ["DEL4321-ABC"],
+            "monitor_settings": {
+                "DEL4321-ABC": { "fade_speed": 15 },
+                "GSM5B7F-XYZ": { "fade_speed": 0 },
+            },
+        }));
+        let id = |s: &str| MonitorId(s.to_string());
+        assert_eq!(settings.selected, BTreeSet::from([id("DEL4321-ABC")]));
+        // 255 / 15 = 17 ticks of 16 ms; a speed of 0 is treated as 1.
+        assert_eq!(
+            settings.monitor_settings[&id("DEL4321-ABC")].fade_ms,
+            17 * 16
+        );
+        assert_eq!(
+            settings.monitor_settings[&id("GSM5B7F-XYZ")].fade_ms,
+            255 * 16
+        );
+    }
+
+    #[test]
+    fn corrupt_files_are_moved_aside() {
+        let dir = temp_dir("corrupt");
+        let path = dir.join(FILE_NAME);
+        fs::write(&path, b"{ not json").unwrap();
+
+        let settings = load_from(&path);
+        assert_eq!(settings.opacity, Settings::default().opacity);
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read(path.with_extension("json.corrupt")).unwrap(),
+            b"{ not json"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn newer_files_are_left_in_place() {
+        let dir = temp_dir("newer");
+        let path = dir.join(FILE_NAME);
+        let body = format!(r#"{{ "version": {}, "opacity": 90 }}"#, SCHEMA_VERSION + 1);
+        fs::write(&path, &body).unwrap();
+
+        let settings = load_from(&path);
+        assert!(settings.is_from_newer_version());
+        assert_eq!(settings.opacity, Settings::default().opacity);
+        assert_eq!(fs::read_to_string(&path).unwrap(), body);
+        assert!(!path.with_extension("json.corrupt").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let dir = temp_dir("round-trip");
+        let path = dir.join("nested").join(FILE_NAME);
+        let id = MonitorId("DEL4321-ABC".to_string());
+        let settings = Settings {
+            opacity: 120,
+            selected: BTreeSet::from([id.clone()]),
+            monitor_settings: BTreeMap::from([(
+                id,
+                MonitorSettings {
+                    opacity: Some(200),
+                    ..MonitorSettings::default()
+                },
+            )]),
+            overlays_active: true,
+            pause_on_fullscreen: true,
+            ..Settings::default()
+        };
+
+        save_to(&path, &settings).unwrap();
+        let loaded = load_from(&path);
+        assert!(!loaded.is_from_newer_version());
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&settings).unwrap()
+        );
+        assert!(!path.with_extension("json.tmp").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,687 @@
+//! Burn-in exposure accounting.
+//!
+//! To tell how long each panel has spent unprotected versus protected, and
+//! how strongly, the daemon samples every connected monitor's [`Exposure`]
+//! on each tick of its main loop and hands the samples to an
+//! [`ExposureTracker`].  Samples come from the opacity the overlay windows
+//! report showing, so hover fades, idle dimming and fade animations are
+//! counted as the panel saw them.  The tracker charges the time since the
+//! previous tick to the previous samples in a [`Ledger`], keyed by stable
+//! monitor id: totals since the monitor was first seen, one [`Totals`] per
+//! local day, and finer slots covering the last 24 hours.
+//!
+//! Time comes from a [`StatsClock`], so tests can drive the tracker with
+//! their own clock.  The summaries the statistics page draws ([`day_bars`],
+//! [`timeline`]) are pure functions of a [`MonitorStats`].  The ledger
+//! lives in `stats.json` next to the settings file and is flushed every
+//! [`FLUSH_MS`] while it changes.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::monitor::MonitorId;
+use crate::schedule::SystemClock;
+use crate::settings;
+
+/// File name of the ledger inside the config directory.
+const FILE_NAME: &str = "stats.json";
+
+/// One local day, in milliseconds.
+pub const DAY_MS: u64 = 86_400_000;
+
+/// Width of one slot of the recent timeline (15 minutes).
+pub const SLOT_MS: u64 = 15 * 60 * 1000;
+
+/// How far back the recent timeline reaches.
+pub const RECENT_MS: u64 = DAY_MS;
+
+/// Days of per-day totals kept per monitor.
+pub const MAX_DAYS: u64 = 366;
+
+/// Longest gap between two ticks that is still charged.  Anything longer
+/// means the machine slept or the clock jumped, and is skipped.
+pub const MAX_GAP_MS: u64 = 30_000;
+
+/// How often a changed ledger is written to disk.
+pub const FLUSH_MS: u64 = 60_000;
+
+// ── Clock ────────────────────────────────────────────────────────────────────
+
+/// Source of the current local time.
+pub trait StatsClock {
+    /// Milliseconds since 1970-01-01 00:00 local time.
+    fn now_ms(&self) -> u64;
+}
+
+/// Days from 1970-01-01 to the given proleptic Gregorian date.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Proleptic Gregorian `(year, month, day)` of the given days since
+/// 1970-01-01; the inverse of [`days_from_civil`].
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl StatsClock for SystemClock {
+    #[cfg(windows)]
+    fn now_ms(&self) -> u64 {
+        use windows::Win32::System::SystemInformation::GetLocalTime;
+        let st = unsafe { GetLocalTime() };
+        let days = days_from_civil(st.wYear as i64, st.wMonth as u32, st.wDay as u32);
+        let ms_of_day = ((st.wHour as u64 * 60 + st.wMinute as u64) * 60 + st.wSecond as u64)
+            * 1000
+            + st.wMilliseconds as u64;
+        days.max(0) as u64 * DAY_MS + ms_of_day
+    }
+
+    /// UTC, as for the schedules.
+    #[cfg(not(windows))]
+    fn now_ms(&self) -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+// ── Samples ──────────────────────────────────────────────────────────────────
+
+/// What a monitor's panel was showing during one sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exposure {
+    /// No overlay: the panel is unprotected.
+    Off,
+    /// Covered by an overlay at this opacity.
+    Protected(u8),
+    /// Overlay shown fully transparent, e.g. faded out while the cursor is
+    /// on it with a hover opacity of 0.
+    Faded,
+}
+
+impl Exposure {
+    /// Exposure of a monitor whose overlays show `opacity`, or that has
+    /// none (`None`).
+    pub fn from_shown(opacity: Option<u8>) -> Self {
+        match opacity {
+            None => Exposure::Off,
+            Some(0) => Exposure::Faded,
+            Some(opacity) => Exposure::Protected(opacity),
+        }
+    }
+}
+
+// ── Ledger ───────────────────────────────────────────────────────────────────
+
+/// Time spent in each [`Exposure`], in milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Totals {
+    pub off_ms: u64,
+    pub faded_ms: u64,
+    /// Protected time by overlay opacity.
+    pub protected_ms: BTreeMap<u8, u64>,
+}
+
+impl Totals {
+    fn add(&mut self, exposure: Exposure, ms: u64) {
+        match exposure {
+            Exposure::Off => self.off_ms += ms,
+            Exposure::Faded => self.faded_ms += ms,
+            Exposure::Protected(opacity) => *self.protected_ms.entry(opacity).or_default() += ms,
+        }
+    }
+
+    /// Time covered by an overlay, at any opacity.
+    pub fn protected_total_ms(&self) -> u64 {
+        self.protected_ms.values().sum()
+    }
+
+    /// Time the panel was driven unattenuated: no overlay, or faded out.
+    pub fn unprotected_ms(&self) -> u64 {
+        self.off_ms + self.faded_ms
+    }
+
+    /// Sum of opacity × milliseconds over the protected time.
+    fn opacity_ms(&self) -> u64 {
+        self.protected_ms
+            .iter()
+            .map(|(&opacity, &ms)| opacity as u64 * ms)
+            .sum()
+    }
+
+    /// Mean overlay opacity while protected, or `None` if never protected.
+    pub fn mean_protected_opacity(&self) -> Option<u8> {
+        let ms = self.protected_total_ms();
+        (ms > 0).then(|| (self.opacity_ms() / ms) as u8)
+    }
+
+    /// Mean overlay opacity over all accounted time, unprotected time
+    /// counting as 0, or `None` if nothing was accounted.
+    pub fn mean_opacity(&self) -> Option<u8> {
+        let ms = self.protected_total_ms() + self.unprotected_ms();
+        (ms > 0).then(|| (self.opacity_ms() / ms) as u8)
+    }
+}
+
+/// Exposure history of one monitor.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorStats {
+    /// When the monitor was first accounted for (local milliseconds).
+    pub since_ms: u64,
+    /// Everything since `since_ms`.
+    pub totals: Totals,
+    /// Per local day (days since 1970-01-01), for the last [`MAX_DAYS`].
+    pub days: BTreeMap<u64, Totals>,
+    /// Per [`SLOT_MS`] slot (slots since 1970-01-01), for the last
+    /// [`RECENT_MS`].
+    pub recent: BTreeMap<u64, Totals>,
+}
+
+impl MonitorStats {
+    /// Charge `from..to` to `exposure`, split at slot (and so day)
+    /// boundaries.
+    fn record(&mut self, exposure: Exposure, from: u64, to: u64) {
+        let mut t = from;
+        while t < to {
+            let end = to.min((t / SLOT_MS + 1) * SLOT_MS);
+            let ms = end - t;
+            self.totals.add(exposure, ms);
+            self.days.entry(t / DAY_MS).or_default().add(exposure, ms);
+            self.recent
+                .entry(t / SLOT_MS)
+                .or_default()
+                .add(exposure, ms);
+            t = end;
+        }
+    }
+
+    /// Drop days and slots that fell out of their windows by `now`.
+    fn prune(&mut self, now: u64) {
+        let first_day = (now / DAY_MS).saturating_sub(MAX_DAYS - 1);
+        self.days.retain(|&day, _| day >= first_day);
+        let first_slot = now.saturating_sub(RECENT_MS) / SLOT_MS;
+        self.recent.retain(|&slot, _| slot >= first_slot);
+    }
+}
+
+/// Exposure history of every monitor ever accounted for.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ledger {
+    pub monitors: BTreeMap<MonitorId, MonitorStats>,
+}
+
+impl Ledger {
+    /// Charge `from..to` on monitor `id` to `exposure`.
+    pub fn record(&mut self, id: &MonitorId, exposure: Exposure, from: u64, to: u64) {
+        if to <= from {
+            return;
+        }
+        self.monitors
+            .entry(id.clone())
+            .or_insert_with(|| MonitorStats {
+                since_ms: from,
+                ..MonitorStats::default()
+            })
+            .record(exposure, from, to);
+    }
+
+    /// Drop history that fell out of its window by `now`.
+    pub fn prune(&mut self, now: u64) {
+        for stats in self.monitors.values_mut() {
+            stats.prune(now);
+        }
+    }
+}
+
+// ── Summaries ────────────────────────────────────────────────────────────────
+
+/// Number of [`SLOT_MS`] slots in the recent timeline.
+pub const TIMELINE_SLOTS: u64 = RECENT_MS / SLOT_MS;
+
+/// Days of daily bars in a [`StatsReport`].
+pub const REPORT_DAYS: u64 = 14;
+
+/// Protected and unprotected time of one local day.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayBar {
+    /// Days since 1970-01-01.
+    pub day: u64,
+    pub protected_ms: u64,
+    pub unprotected_ms: u64,
+}
+
+/// The `count` days up to and including `today`, oldest first.  Days
+/// without history are empty bars.
+pub fn day_bars(stats: &MonitorStats, today: u64, count: u64) -> Vec<DayBar> {
+    (today.saturating_sub(count.saturating_sub(1))..=today)
+        .map(|day| {
+            let totals = stats.days.get(&day);
+            DayBar {
+                day,
+                protected_ms: totals.map_or(0, Totals::protected_total_ms),
+                unprotected_ms: totals.map_or(0, Totals::unprotected_ms),
+            }
+        })
+        .collect()
+}
+
+/// Mean overlay opacity of each of the last [`TIMELINE_SLOTS`] slots up to
+/// and including the one holding `now_ms`, oldest first.  Unprotected time
+/// counts as 0; slots without history are `None`.
+pub fn timeline(stats: &MonitorStats, now_ms: u64) -> Vec<Option<u8>> {
+    let last = now_ms / SLOT_MS;
+    (last.saturating_sub(TIMELINE_SLOTS - 1)..=last)
+        .map(|slot| stats.recent.get(&slot).and_then(Totals::mean_opacity))
+        .collect()
+}
+
+/// Reply to a statistics request: per monitor, only what the statistics
+/// page draws, so the reply stays small however much history the ledger
+/// holds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsReport {
+    /// When the report was taken (local milliseconds since 1970-01-01).
+    pub now_ms: u64,
+    pub monitors: BTreeMap<MonitorId, MonitorReport>,
+}
+
+/// Summary of one monitor's history.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorReport {
+    /// When the monitor was first accounted for (local milliseconds).
+    pub since_ms: u64,
+    /// Everything since `since_ms`.
+    pub totals: Totals,
+    /// The last [`REPORT_DAYS`] days, see [`day_bars`].
+    pub days: Vec<DayBar>,
+    /// The last 24 hours, see [`timeline`].
+    pub timeline: Vec<Option<u8>>,
+}
+
+impl MonitorReport {
+    /// Summarise `stats` as of `now_ms`.
+    pub fn new(stats: &MonitorStats, now_ms: u64) -> Self {
+        Self {
+            since_ms: stats.since_ms,
+            totals: stats.totals.clone(),
+            days: day_bars(stats, now_ms / DAY_MS, REPORT_DAYS),
+            timeline: timeline(stats, now_ms),
+        }
+    }
+}
+
+// ── Tracker ──────────────────────────────────────────────────────────────────
+
+/// Charges elapsed time to sampled exposures.
+pub struct ExposureTracker<C: StatsClock> {
+    clock: C,
+    ledger: Ledger,
+    /// Time of the previous tick.
+    last: Option<u64>,
+    /// Samples of the previous tick, in effect until the next one.
+    current: Vec<(MonitorId, Exposure)>,
+    /// Time of the last flush, and whether anything was charged since.
+    flushed: u64,
+    dirty: bool,
+}
+
+impl<C: StatsClock> ExposureTracker<C> {
+    pub fn new(clock: C, ledger: Ledger) -> Self {
+        let now = clock.now_ms();
+        Self {
+            clock,
+            ledger,
+            last: None,
+            current: Vec::new(),
+            flushed: now,
+            dirty: false,
+        }
+    }
+
+    /// Charge the time since the previous tick to the previous samples and
+    /// make `samples` the current ones.  Gaps longer than [`MAX_GAP_MS`] and
+    /// clocks going backwards charge nothing.
+    pub fn tick(&mut self, samples: Vec<(MonitorId, Exposure)>) {
+        let now = self.clock.now_ms();
+        let charged = self
+            .last
+            .filter(|&last| now > last && now - last <= MAX_GAP_MS);
+        if let Some(last) = charged {
+            for (id, exposure) in &self.current {
+                self.ledger.record(id, *exposure, last, now);
+            }
+            self.dirty |= !self.current.is_empty();
+        }
+        self.last = Some(now);
+        self.current = samples;
+    }
+
+    /// The ledger, pruned, when it changed and was last flushed at least
+    /// [`FLUSH_MS`] ago.  The caller writes it out.
+    pub fn due(&mut self) -> Option<Ledger> {
+        let now = self.clock.now_ms();
+        if !self.dirty || now.saturating_sub(self.flushed) < FLUSH_MS {
+            return None;
+        }
+        Some(self.flush())
+    }
+
+    /// The ledger, pruned, to write out now (e.g. on shutdown).
+    pub fn flush(&mut self) -> Ledger {
+        let now = self.clock.now_ms();
+        self.ledger.prune(now);
+        self.flushed = now;
+        self.dirty = false;
+        self.ledger.clone()
+    }
+
+    /// Summary of everything accounted so far.
+    pub fn report(&mut self) -> StatsReport {
+        let now = self.clock.now_ms();
+        self.ledger.prune(now);
+        StatsReport {
+            now_ms: now,
+            monitors: self
+                .ledger
+                .monitors
+                .iter()
+                .map(|(id, stats)| (id.clone(), MonitorReport::new(stats, now)))
+                .collect(),
+        }
+    }
+}
+
+// ── Persistence ──────────────────────────────────────────────────────────────
+
+/// Full path of the ledger file, or `None` if no config directory exists.
+pub fn stats_path() -> Option<PathBuf> {
+    settings::config_dir().map(|d| d.join(FILE_NAME))
+}
+
+/// Load the ledger from the default location.
+///
+/// Never fails: a missing file yields an empty ledger, and a corrupt one is
+/// renamed to `stats.json.corrupt` first.
+pub fn load() -> Ledger {
+    let Some(path) = stats_path() else {
+        return Ledger::default();
+    };
+    let bytes = match fs::read(&path) {
+        Ok(b) => b,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ledger::default(),
+        Err(e) => {
+            eprintln!("[stats] Cannot read {}: {:?}", path.display(), e);
+            return Ledger::default();
+        }
+    };
+    match serde_json::from_slice(&bytes) {
+        Ok(ledger) => ledger,
+        Err(e) => {
+            eprintln!(
+                "[stats] {} is unusable ({}); starting afresh",
+                path.display(),
+                e
+            );
+            let _ = fs::rename(&path, path.with_extension("json.corrupt"));
+            Ledger::default()
+        }
+    }
+}
+
+/// Save the ledger to the default location.
+pub fn save(ledger: &Ledger) -> io::Result<()> {
+    let path = stats_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    save_to(&path, ledger)
+}
+
+/// Atomically write `ledger` to `path`.
+pub fn save_to(path: &Path, ledger: &Ledger) -> io::Result<()> {
+    let body =
+        serde_json::to_vec(ledger).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    settings::write_atomic(path, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake::Fake;
+
+    impl StatsClock for Fake<u64> {
+        fn now_ms(&self) -> u64 {
+            self.get()
+        }
+    }
+
+    fn advance(clock: &Fake<u64>, ms: u64) {
+        clock.set(clock.get() + ms);
+    }
+
+    fn mon(name: &str) -> MonitorId {
+        MonitorId(name.to_string())
+    }
+
+    /// Noon on 2024-03-01, local time.
+    fn noon() -> u64 {
+        days_from_civil(2024, 3, 1) as u64 * DAY_MS + DAY_MS / 2
+    }
+
+    fn tracker(clock: &Fake<u64>) -> ExposureTracker<Fake<u64>> {
+        ExposureTracker::new(clock.clone(), Ledger::default())
+    }
+
+    fn totals<'a>(report: &'a StatsReport, name: &str) -> &'a Totals {
+        &report.monitors[&mon(name)].totals
+    }
+
+    #[test]
+    fn civil_dates_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        for days in [-1, 0, 59, 11_016, 19_783, 40_000] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn shown_opacity_maps_to_exposure() {
+        assert_eq!(Exposure::from_shown(None), Exposure::Off);
+        assert_eq!(Exposure::from_shown(Some(0)), Exposure::Faded);
+        assert_eq!(Exposure::from_shown(Some(40)), Exposure::Protected(40));
+    }
+
+    #[test]
+    fn ticks_charge_elapsed_time_to_the_previous_samples() {
+        let clock = Fake::new(noon());
+        let mut tracker = tracker(&clock);
+
+        tracker.tick(vec![(mon("a"), Exposure::Protected(100))]);
+        advance(&clock, 1_000);
+        // Hovered at a hover opacity of 30: still protected, less strongly.
+        tracker.tick(vec![(mon("a"), Exposure::Protected(30))]);
+        advance(&clock, 500);
+        tracker.tick(vec![(mon("a"), Exposure::Off)]);
+        advance(&clock, 250);
+        tracker.tick(vec![(mon("a"), Exposure::Faded)]);
+        advance(&clock, 100);
+        tracker.tick(vec![]);
+
+        let report = tracker.report();
+        let a = totals(&report, "a");
+        assert_eq!(a.protected_ms, BTreeMap::from([(30, 500), (100, 1_000)]));
+        assert_eq!(a.off_ms, 250);
+        assert_eq!(a.faded_ms, 100);
+        assert_eq!(a.unprotected_ms(), 350);
+        assert_eq!(a.mean_protected_opacity(), Some(76));
+        assert_eq!(report.monitors[&mon("a")].since_ms, noon());
+    }
+
+    #[test]
+    fn the_first_tick_charges_nothing() {
+        let clock = Fake::new(noon());
+        let mut tracker = tracker(&clock);
+        tracker.tick(vec![(mon("a"), Exposure::Protected(100))]);
+        assert!(tracker.report().monitors.is_empty());
+        assert_eq!(tracker.due(), None);
+    }
+
+    #[test]
+    fn long_gaps_and_backward_clocks_charge_nothing() {
+        let clock = Fake::new(noon());
+        let mut tracker = tracker(&clock);
+        let samples = || vec![(mon("a"), Exposure::Protected(100))];
+
+        tracker.tick(samples());
+        advance(&clock, MAX_GAP_MS + 1);
+        tracker.tick(samples());
+        clock.set(noon());
+        tracker.tick(samples());
+        advance(&clock, MAX_GAP_MS);
+        tracker.tick(samples());
+
+        let report = tracker.report();
+        assert_eq!(totals(&report, "a").protected_total_ms(), MAX_GAP_MS);
+    }
+
+    #[test]
+    fn time_is_split_across_slots_and_days() {
+        let midnight = days_from_civil(2024, 3, 2) as u64 * DAY_MS;
+        let clock = Fake::new(midnight - 10_000);
+        let mut tracker = tracker(&clock);
+
+        tracker.tick(vec![(mon("a"), Exposure::Protected(50))]);
+        advance(&clock, 25_000);
+        tracker.tick(vec![]);
+
+        let ledger = tracker.flush();
+        let a = &ledger.monitors[&mon("a")];
+        let day = midnight / DAY_MS;
+        assert_eq!(a.days[&(day - 1)].protected_total_ms(), 10_000);
+        assert_eq!(a.days[&day].protected_total_ms(), 15_000);
+        let slot = midnight / SLOT_MS;
+        assert_eq!(a.recent[&(slot - 1)].protected_total_ms(), 10_000);
+        assert_eq!(a.recent[&slot].protected_total_ms(), 15_000);
+    }
+
+    #[test]
+    fn ledger_is_due_for_a_flush_only_after_changes() {
+        let clock = Fake::new(noon());
+        let mut tracker = tracker(&clock);
+
+        advance(&clock, FLUSH_MS);
+        assert_eq!(tracker.due(), None, "nothing charged yet");
+
+        tracker.tick(vec![(mon("a"), Exposure::Off)]);
+        advance(&clock, 1_000);
+        tracker.tick(vec![(mon("a"), Exposure::Off)]);
+        let ledger = tracker.due().expect("changed and FLUSH_MS passed");
+        assert_eq!(ledger.monitors[&mon("a")].totals.off_ms, 1_000);
+
+        advance(&clock, 1_000);
+        tracker.tick(vec![(mon("a"), Exposure::Off)]);
+        assert_eq!(tracker.due(), None, "flushed too recently");
+        advance(&clock, FLUSH_MS);
+        assert!(tracker.due().is_some());
+    }
+
+    #[test]
+    fn old_history_is_pruned() {
+        let clock = Fake::new(noon());
+        let mut ledger = Ledger::default();
+        let old = noon() - MAX_DAYS * DAY_MS;
+        ledger.record(&mon("a"), Exposure::Off, old, old + 1_000);
+        ledger.record(
+            &mon("a"),
+            Exposure::Off,
+            noon() - 2 * DAY_MS,
+            noon() - 2 * DAY_MS + 1,
+        );
+        let mut tracker = ExposureTracker::new(clock, ledger);
+
+        let ledger = tracker.flush();
+        let a = &ledger.monitors[&mon("a")];
+        assert_eq!(a.days.len(), 1, "the oldest day fell out");
+        assert!(a.recent.is_empty(), "nothing in the last 24 hours");
+        // Lifetime totals keep everything.
+        assert_eq!(a.totals.off_ms, 1_001);
+    }
+
+    #[test]
+    fn summaries_cover_days_and_slots() {
+        let clock = Fake::new(noon());
+        let mut tracker = tracker(&clock);
+        tracker.tick(vec![(mon("a"), Exposure::Protected(200))]);
+        advance(&clock, 10_000);
+        tracker.tick(vec![(mon("a"), Exposure::Off)]);
+        advance(&clock, 10_000);
+        tracker.tick(vec![]);
+
+        let report = tracker.report();
+        let ledger = tracker.flush();
+        let a = &ledger.monitors[&mon("a")];
+        let today = report.now_ms / DAY_MS;
+        let bars = day_bars(a, today, 7);
+        assert_eq!(bars.len(), 7);
+        assert_eq!(bars[6].day, today);
+        assert_eq!(
+            (bars[6].protected_ms, bars[6].unprotected_ms),
+            (10_000, 10_000)
+        );
+        assert_eq!(bars[0].protected_ms + bars[0].unprotected_ms, 0);
+
+        let line = timeline(a, report.now_ms);
+        assert_eq!(line.len(), TIMELINE_SLOTS as usize);
+        assert_eq!(line.last(), Some(&Some(100)));
+        assert_eq!(line[0], None);
+
+        // The report carries the same summaries, not the history.
+        let summary = &report.monitors[&mon("a")];
+        assert_eq!(summary.days, day_bars(a, today, REPORT_DAYS));
+        assert_eq!(summary.timeline, line);
+        assert_eq!(summary.totals, a.totals);
+    }
+
+    #[test]
+    fn report_size_does_not_grow_with_history() {
+        let clock = Fake::new(noon());
+        let mut ledger = Ledger::default();
+        for day in 0..MAX_DAYS {
+            for opacity in 1..=255u8 {
+                let from = noon() - day * DAY_MS - opacity as u64 * 1_000;
+                ledger.record(&mon("a"), Exposure::Protected(opacity), from, from + 1);
+            }
+        }
+        let mut tracker = ExposureTracker::new(clock, ledger);
+
+        let report = tracker.report();
+        let a = &report.monitors[&mon("a")];
+        assert_eq!(a.days.len(), REPORT_DAYS as usize);
+        assert_eq!(a.timeline.len(), TIMELINE_SLOTS as usize);
+        assert_eq!(a.totals.protected_total_ms(), MAX_DAYS * 255);
+    }
+}
@@ -3,10 +3,14 @@ use std::rc::Rc;
 use std::time::{Duration, Instant};
 
 use gpui::prelude::*;
-use gpui::{Bounds, FontWeight, MouseButton, Pixels, div, px, rgb};
+use gpui::{
+    Bounds, FocusHandle, FontWeight, KeyDownEvent, MouseButton, Pixels, div, px, rgb,
+    transparent_black,
+};
 
 use crate::ipc::UiMsg;
 use crate::ui::controller::Controller;
+use crate::ui::keyboard::{FOCUS_RING, opacity_after_key};
 
 /// Width of the slider track in pixels.
 const SLIDER_WIDTH: f32 = 400.0;
@@ -14,11 +18,14 @@ const SLIDER_WIDTH: f32 = 400.0;
 /// Build the complete opacity slider section.
 ///
 /// Includes a label with the current percentage, a draggable track with a knob,
-/// min/max labels, and a row of preset buttons for quick selection.
+/// min/max labels, and a row of preset buttons for quick selection.  The track
+/// takes `focus`; while focused the arrow keys, Page Up / Page Down and
+/// Home / End adjust the opacity.
 pub fn opacity_slider(
     opacity: u8,
     slider_bounds: &Rc<Cell<Option<Bounds<Pixels>>>>,
     overlays_active: bool,
+    focus: &FocusHandle,
     cx: &mut gpui::Context<Controller>,
 ) -> impl IntoElement + use<> {
     let opacity_pct = ((opacity as f32 / 255.0) * 100.0).round() as u8;
@@ -71,6 +78,7 @@ pub fn opacity_slider(
                     knob_position,
                     slider_bounds,
                     overlays_active,
+                    focus,
                     cx,
                 ))
                 .child(div().text_sm().text_color(rgb(0x666666)).child("100%")),
@@ -98,6 +106,7 @@ fn slider_track(
     knob_position: f32,
     slider_bounds: &Rc<Cell<Option<Bounds<Pixels>>>>,
     _overlays_active: bool,
+    focus: &FocusHandle,
     cx: &mut gpui::Context<Controller>,
 ) -> impl IntoElement + use<> {
     div()
@@ -126,6 +135,22 @@ fn slider_track(
                 .flex()
                 .items_center()
                 .cursor_pointer()
+                // Keyboard: the border only shows as a focus ring.
+                .rounded(px(6.0))
+                .border_1()
+                .border_color(transparent_black())
+                .track_focus(focus)
+                .focus(|style| style.border_color(rgb(FOCUS_RING)))
+                .on_key_down(cx.listener(|this, ev: &KeyDownEvent, _window, cx| {
+                    if let Some(new_opacity) = opacity_after_key(this.opacity, &ev.keystroke.key) {
+                        cx.stop_propagation();
+                        if this.opacity != new_opacity {
+                            this.opacity = new_opacity;
+                            let _ = this.cmd_tx.try_send(UiMsg::SetOpacity(this.opacity));
+                            cx.notify();
+                        }
+                    }
+                }))
                 // Click to set value
                 .on_mouse_down(
                     MouseButton::Left,
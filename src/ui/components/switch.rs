@@ -1,7 +1,13 @@
+use std::rc::Rc;
 use std::time::Duration;
 
 use gpui::prelude::*;
-use gpui::{Animation, AnimationExt, AnyElement, MouseButton, div, ease_in_out, px, rgb};
+use gpui::{
+    Animation, AnimationExt, AnyElement, FocusHandle, KeyDownEvent, MouseButton, div, ease_in_out,
+    px, rgb,
+};
+
+use crate::ui::keyboard::{FOCUS_RING, SPACE};
 
 // ─── Colour helpers ───────────────────────────────────────────────────────────
 
@@ -37,12 +43,25 @@ const ANIM_MS: u64 = 200;
 ///                   The animation restarts on each new value because the
 ///                   `ElementId` changes.  When `0` the element renders
 ///                   statically so there is no spurious slide on first paint.
-/// * `on_click`    — callback invoked on mouse-down.
+/// * `focus`       — focus handle; Space toggles the focused switch.
+/// * `on_click`    — callback invoked on mouse-down or Space.
 pub fn switch(
     checked: bool,
     click_count: u64,
+    focus: &FocusHandle,
     on_click: impl Fn(&bool, &mut gpui::Window, &mut gpui::App) + 'static,
 ) -> AnyElement {
+    let on_click = Rc::new(on_click);
+    let on_key = {
+        let on_click = on_click.clone();
+        move |ev: &KeyDownEvent, window: &mut gpui::Window, cx: &mut gpui::App| {
+            if ev.keystroke.key == SPACE {
+                cx.stop_propagation();
+                on_click(&checked, window, cx);
+            }
+        }
+    };
+
     if click_count == 0 {
         // ── Initial render: static, no animation ─────────────────────────────
         // Animating from delta=0 on the very first frame would show the knob
@@ -62,6 +81,9 @@ pub fn switch(
             .border_1()
             .border_color(rgb(border_clr))
             .cursor_pointer()
+            .track_focus(focus)
+            .focus(|style| style.border_color(rgb(FOCUS_RING)))
+            .on_key_down(on_key)
             .on_mouse_down(MouseButton::Left, move |_, window, cx| {
                 on_click(&checked, window, cx);
             })
@@ -117,6 +139,9 @@ pub fn switch(
             .rounded(px(12.0))
             .border_1()
             .cursor_pointer()
+            .track_focus(focus)
+            .focus(|style| style.border_color(rgb(FOCUS_RING)))
+            .on_key_down(on_key)
             .on_mouse_down(MouseButton::Left, move |_, window, cx| {
                 on_click(&checked, window, cx);
             })
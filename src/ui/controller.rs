@@ -1,43 +1,131 @@
 use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
 use std::rc::Rc;
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
+use futures::StreamExt;
+use futures::channel::mpsc::UnboundedReceiver;
 use gpui::prelude::*;
 use gpui::{
-    Animation, AnimationExt, AnyElement, Bounds, FontWeight, MouseButton, Pixels, div, px, rgb,
+    Animation, AnimationExt, AnyElement, Bounds, Div, FocusHandle, FontWeight, KeyDownEvent,
+    MouseButton, Pixels, div, px, rgb,
 };
 use std::f32::consts::PI;
 
 use crate::ipc::{DaemonState, UiMsg};
-use crate::monitor::MonitorInfo;
+use crate::monitor::{MonitorId, MonitorInfo};
+use crate::overlay::MonitorSettings;
+use crate::overlay::region::Region;
+use crate::stats::StatsReport;
 use crate::ui::components::{opacity_from_mouse, opacity_slider, switch};
-use crate::ui::monitor_list::monitor_list;
+use crate::ui::keyboard::{
+    ENTER, FOCUS_RING, SPACE, TAB, choice_label, focus_caption, monitor_label, slider_label,
+    suggestion_label, switch_label,
+};
+use crate::ui::monitor_list::{display_name, monitor_list};
+use crate::ui::monitor_map::monitor_map;
+use crate::ui::stats_view::{entries, shown_entry, stats_view};
+use crate::ui::suggestions::{area_text, pending, suggestions_panel};
+
+/// How often the statistics page asks the daemon for fresh numbers while it
+/// is shown, and the protection page for fresh region suggestions.
+const STATS_REFRESH: Duration = Duration::from_secs(30);
+
+/// Something the IPC reader thread received for the [`Controller`].
+pub enum DaemonUpdate {
+    /// A state snapshot: a command reply or a pushed event.
+    State(DaemonState),
+    /// Reply to [`UiMsg::GetStats`].
+    Stats(StatsReport),
+    /// Reply to [`UiMsg::GetRegionSuggestions`].
+    Suggestions(BTreeMap<MonitorId, Vec<Region>>),
+    /// The daemon refused a command, so the optimistic cache may show a
+    /// change that never happened.
+    Refused,
+}
+
+/// Page shown below the title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    /// Monitor selection, opacity and the activation switch.
+    Protection,
+    /// Per-monitor exposure history.
+    Statistics,
+}
+
+/// How the Protection page shows the monitors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorView {
+    /// A scrollable list of tiles with each monitor's details.
+    List,
+    /// The monitors drawn to scale at their place on the desktop.
+    Map,
+}
 
 /// Central application controller.
 ///
-/// Holds a local optimistic cache of daemon state, plus the IPC channels used
-/// to send commands to the daemon and receive state-update replies.
+/// Holds a local optimistic cache of daemon state, plus the IPC channel used
+/// to send commands to the daemon.  State pushed by the daemon is applied by
+/// a task spawned in [`Controller::new`].
 pub struct Controller {
     // ── State synced from daemon (local optimistic cache) ─────────────────
     /// Information about every connected monitor.
     pub monitors: Vec<MonitorInfo>,
-    /// Per-monitor selection flags (same length as `monitors`).
-    pub selected: Vec<bool>,
+    /// Ids of the selected monitors (may include disconnected ones).
+    pub selected: BTreeSet<MonitorId>,
     /// Whether overlay protection is currently enabled.
     pub overlays_active: bool,
-    /// Current overlay opacity (0–255).
+    /// Current global overlay opacity (0–255).
     pub opacity: u8,
-    /// Per-monitor flag: true if the daemon reports an active overlay window.
-    overlay_alive: Vec<bool>,
+    /// Per-monitor overlay behaviour reported by the daemon.
+    pub monitor_settings: BTreeMap<MonitorId, MonitorSettings>,
+    /// Ids of the monitors for which the daemon reports an active overlay window.
+    overlay_alive: BTreeSet<MonitorId>,
+    /// Opacity each monitor's overlays show, with every override applied.
+    effective_opacity: BTreeMap<MonitorId, u8>,
+    /// Whether the daemon samples the screen for static content.
+    pub analyze_content: bool,
 
     // ── IPC channels ──────────────────────────────────────────────────────
     /// Send commands to the background IPC thread (→ daemon).
     pub cmd_tx: mpsc::SyncSender<UiMsg>,
-    /// Receive state snapshots pushed by the background IPC thread (← daemon).
-    state_rx: mpsc::Receiver<DaemonState>,
+
+    // ── Statistics ────────────────────────────────────────────────────────
+    /// Latest statistics reply, `None` until the first one arrives.
+    pub stats: Option<StatsReport>,
+    /// Monitor whose history the statistics page shows; `None` picks the
+    /// first one.
+    pub stats_monitor: Option<MonitorId>,
+
+    // ── Region suggestions ────────────────────────────────────────────────
+    /// Latest region suggestions from content analysis, including those
+    /// already added.
+    pub suggestions: BTreeMap<MonitorId, Vec<Region>>,
+
+    // ── Keyboard focus ────────────────────────────────────────────────────
+    /// Focus of the window itself, so Tab and Enter arrive even while no
+    /// control has focus.
+    pub focus_handle: FocusHandle,
+    /// Page tabs, in [`Page`] order.
+    page_focus: [FocusHandle; 2],
+    /// List / map choice, in [`MonitorView`] order.
+    view_focus: [FocusHandle; 2],
+    /// Monitor tiles, added as monitors appear.
+    monitor_focus: BTreeMap<MonitorId, FocusHandle>,
+    /// Monitor picker of the statistics page, added as monitors appear.
+    stats_focus: BTreeMap<MonitorId, FocusHandle>,
+    /// Add buttons of the pending suggestions, in their order; grown as
+    /// needed.
+    suggestion_focus: Vec<FocusHandle>,
+    slider_focus: FocusHandle,
+    switch_focus: FocusHandle,
 
     // ── UI-only state (unchanged from before) ─────────────────────────────
+    /// Page shown below the title.
+    pub page: Page,
+    /// How the Protection page shows the monitors.
+    pub monitor_view: MonitorView,
     /// Monotonically-incrementing counter, advanced by one on every *effective*
     /// toggle (i.e. only when `overlays_active` actually changes).
     ///
@@ -67,24 +155,28 @@ impl Controller {
     pub fn new(
         initial: DaemonState,
         cmd_tx: mpsc::SyncSender<UiMsg>,
-        state_rx: mpsc::Receiver<DaemonState>,
+        mut state_rx: UnboundedReceiver<DaemonUpdate>,
         cx: &mut gpui::Context<Self>,
     ) -> Self {
-        // Spawn a background task that wakes up every 100 ms to pull the
-        // latest daemon state.  Sending GetState means the IPC thread will
-        // push a fresh DaemonState into state_rx, which render() then drains.
-        // This also catches state changes made outside the UI (e.g. the
-        // system-tray toggle) without requiring a daemon→UI push protocol.
+        // Apply every state snapshot the IPC reader thread receives — both
+        // command replies and events the daemon pushes for changes made
+        // outside the UI (e.g. the system-tray toggle) — and every
+        // statistics and suggestions reply.  The task sleeps until something arrives, so an
+        // idle window costs nothing.
         cx.spawn(async move |weak, cx| {
-            loop {
-                cx.background_executor()
-                    .timer(Duration::from_millis(100))
-                    .await;
+            while let Some(update) = state_rx.next().await {
                 if weak
                     .update(cx, |this, cx| {
-                        // try_send won't block; if the channel is full we
-                        // simply skip this tick and catch up on the next one.
-                        let _ = this.cmd_tx.try_send(UiMsg::GetState);
+                        match update {
+                            DaemonUpdate::State(state) => this.apply_state(state),
+                            DaemonUpdate::Stats(report) => this.stats = Some(report),
+                            DaemonUpdate::Suggestions(s) => this.suggestions = s,
+                            // Re-sync; the reply replaces the optimistic
+                            // values with the daemon's.
+                            DaemonUpdate::Refused => {
+                                let _ = this.cmd_tx.try_send(UiMsg::GetState);
+                            }
+                        }
                         cx.notify();
                     })
                     .is_err()
@@ -95,14 +187,61 @@ impl Controller {
         })
         .detach();
 
+        // Keep the statistics page and the suggestions current while they
+        // are shown.  The ledger and the heatmaps only grow by a few seconds
+        // per tick, so a slow refresh suffices.
+        cx.spawn(async move |weak, cx| {
+            loop {
+                cx.background_executor().timer(STATS_REFRESH).await;
+                let shown = weak.update(cx, |this, _cx| match this.page {
+                    Page::Statistics => {
+                        let _ = this.cmd_tx.try_send(UiMsg::GetStats);
+                    }
+                    Page::Protection if this.analyze_content => {
+                        let _ = this.cmd_tx.try_send(UiMsg::GetRegionSuggestions);
+                    }
+                    Page::Protection => {}
+                });
+                if shown.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+
+        if initial.analyze_content {
+            let _ = cmd_tx.try_send(UiMsg::GetRegionSuggestions);
+        }
+
         Self {
             monitors: initial.monitors,
             selected: initial.selected,
             overlays_active: initial.overlays_active,
             opacity: initial.opacity,
+            monitor_settings: initial.monitor_settings,
             overlay_alive: initial.overlay_alive,
+            effective_opacity: initial.effective_opacity,
+            analyze_content: initial.analyze_content,
             cmd_tx,
-            state_rx,
+            stats: None,
+            stats_monitor: None,
+            suggestions: BTreeMap::new(),
+            page: Page::Protection,
+            monitor_view: MonitorView::List,
+            focus_handle: cx.focus_handle(),
+            page_focus: [
+                cx.focus_handle().tab_stop(true),
+                cx.focus_handle().tab_stop(true),
+            ],
+            view_focus: [
+                cx.focus_handle().tab_stop(true),
+                cx.focus_handle().tab_stop(true),
+            ],
+            monitor_focus: BTreeMap::new(),
+            stats_focus: BTreeMap::new(),
+            suggestion_focus: Vec::new(),
+            slider_focus: cx.focus_handle().tab_stop(true),
+            switch_focus: cx.focus_handle().tab_stop(true),
             switch_click_count: 0,
             shake_count: 0,
             slider_bounds: Rc::new(Cell::new(None)),
@@ -110,48 +249,416 @@ impl Controller {
             last_drag_flush: None,
         }
     }
+
+    /// Replace the local cache with a snapshot from the daemon.
+    fn apply_state(&mut self, state: DaemonState) {
+        self.monitors = state.monitors;
+        self.selected = state.selected;
+        self.opacity = state.opacity;
+        self.monitor_settings = state.monitor_settings;
+        self.overlays_active = state.overlays_active;
+        self.overlay_alive = state.overlay_alive;
+        self.effective_opacity = state.effective_opacity;
+        // Turning analysis off forgets what it observed.
+        if self.analyze_content && !state.analyze_content {
+            self.suggestions.clear();
+        } else if !self.analyze_content && state.analyze_content {
+            let _ = self.cmd_tx.try_send(UiMsg::GetRegionSuggestions);
+        }
+        self.analyze_content = state.analyze_content;
+    }
+
+    /// Number of *connected* monitors that are selected.
+    fn selected_count(&self) -> usize {
+        self.monitors
+            .iter()
+            .filter(|m| self.selected.contains(&m.id))
+            .count()
+    }
+
+    /// Toggle a monitor's selection locally and tell the daemon.
+    ///
+    /// Ignored while protection is active (selection is locked).
+    pub fn toggle_monitor(&mut self, id: &MonitorId) {
+        if self.overlays_active {
+            return;
+        }
+        if !self.selected.remove(id) {
+            self.selected.insert(id.clone());
+        }
+        let _ = self.cmd_tx.try_send(UiMsg::ToggleMonitor(id.clone()));
+    }
+
+    /// Switch pages, fetching fresh statistics or suggestions for the page
+    /// opened.
+    pub fn show_page(&mut self, page: Page) {
+        if self.page != page {
+            match page {
+                Page::Statistics => {
+                    let _ = self.cmd_tx.try_send(UiMsg::GetStats);
+                }
+                Page::Protection if self.analyze_content => {
+                    let _ = self.cmd_tx.try_send(UiMsg::GetRegionSuggestions);
+                }
+                Page::Protection => {}
+            }
+        }
+        self.page = page;
+    }
+
+    /// Add a suggested region to a monitor locally and tell the daemon.
+    pub fn add_region(&mut self, id: &MonitorId, region: Region) {
+        self.monitor_settings
+            .entry(id.clone())
+            .or_default()
+            .regions
+            .push(region.clone());
+        let _ = self.cmd_tx.try_send(UiMsg::SetRegion(id.clone(), region));
+    }
+
+    /// Turn protection off, or on if any monitor is selected; with none
+    /// selected, shake the selection hint instead.
+    pub fn toggle_protection(&mut self) {
+        if self.overlays_active {
+            // Optimistic update + send command
+            self.overlays_active = false;
+            self.switch_click_count += 1;
+            self.shake_count = 0;
+            let _ = self.cmd_tx.try_send(UiMsg::SetActive(false));
+        } else if self.selected_count() > 0 {
+            self.overlays_active = true;
+            self.switch_click_count += 1;
+            self.shake_count = 0;
+            let _ = self.cmd_tx.try_send(UiMsg::SetActive(true));
+        } else {
+            // No monitors selected — shake the hint label.
+            self.shake_count += 1;
+        }
+    }
+
+    /// Window-wide keys: Tab / Shift+Tab move focus, Enter toggles
+    /// protection.  Controls handle their own keys first.
+    fn on_key_down(
+        &mut self,
+        ev: &KeyDownEvent,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        let key = ev.keystroke.key.as_str();
+        if key == TAB && ev.keystroke.modifiers.shift {
+            window.focus_prev();
+        } else if key == TAB {
+            window.focus_next();
+        } else if key == ENTER {
+            self.toggle_protection();
+        } else {
+            return;
+        }
+        cx.stop_propagation();
+        cx.notify();
+    }
+
+    /// Label of the focused control, for the focus caption.
+    fn focus_label(&self, window: &gpui::Window) -> Option<String> {
+        let pages = [
+            (Page::Protection, "Protection page"),
+            (Page::Statistics, "Statistics page"),
+        ];
+        for ((page, name), handle) in pages.into_iter().zip(&self.page_focus) {
+            if handle.is_focused(window) {
+                return Some(choice_label(name, page == self.page));
+            }
+        }
+        let views = [
+            (MonitorView::List, "List view"),
+            (MonitorView::Map, "Map view"),
+        ];
+        for ((view, name), handle) in views.into_iter().zip(&self.view_focus) {
+            if handle.is_focused(window) {
+                return Some(choice_label(name, view == self.monitor_view));
+            }
+        }
+        if self.slider_focus.is_focused(window) {
+            return Some(slider_label(self.opacity));
+        }
+        if self.switch_focus.is_focused(window) {
+            return Some(switch_label(self.overlays_active));
+        }
+        let focused = |handles: &BTreeMap<MonitorId, FocusHandle>, id: &MonitorId| {
+            handles.get(id).is_some_and(|h| h.is_focused(window))
+        };
+        for (i, mon) in self.monitors.iter().enumerate() {
+            if focused(&self.monitor_focus, &mon.id) {
+                return Some(monitor_label(
+                    &display_name(mon, i),
+                    self.selected.contains(&mon.id),
+                    self.overlays_active,
+                ));
+            }
+        }
+        let pending = pending(&self.suggestions, &self.monitors, &self.monitor_settings);
+        for (p, handle) in pending.iter().zip(&self.suggestion_focus) {
+            if handle.is_focused(window) {
+                return Some(suggestion_label(
+                    &p.monitor_name,
+                    &area_text(&p.region.rect),
+                ));
+            }
+        }
+        let entries = entries(self.stats.as_ref()?, &self.monitors);
+        let shown = shown_entry(&entries, self.stats_monitor.as_ref()).map(|(id, _)| id);
+        entries
+            .iter()
+            .find(|(id, _)| focused(&self.stats_focus, id))
+            .map(|(id, name)| choice_label(name, Some(id) == shown))
+    }
+}
+
+/// One tab of the page switcher.
+fn tab(
+    label: &'static str,
+    page: Page,
+    current: Page,
+    focus: &FocusHandle,
+    cx: &mut gpui::Context<Controller>,
+) -> Div {
+    let is_current = page == current;
+    div()
+        .px_4()
+        .py_2()
+        .text_sm()
+        .font_weight(FontWeight::MEDIUM)
+        .border_b_2()
+        .border_color(if is_current {
+            rgb(0x4CAF50)
+        } else {
+            rgb(0x0e0e0e)
+        })
+        .text_color(if is_current {
+            rgb(0xffffff)
+        } else {
+            rgb(0x888888)
+        })
+        .cursor_pointer()
+        .track_focus(focus)
+        .focus(|style| style.border_color(rgb(FOCUS_RING)))
+        .on_key_down(cx.listener(move |this, ev: &KeyDownEvent, _window, cx| {
+            if ev.keystroke.key == SPACE {
+                cx.stop_propagation();
+                this.show_page(page);
+                cx.notify();
+            }
+        }))
+        .on_mouse_down(
+            MouseButton::Left,
+            cx.listener(move |this, _, _window, cx| {
+                this.show_page(page);
+                cx.notify();
+            }),
+        )
+        .child(label)
+}
+
+/// One option of the list / map choice.
+fn view_option(
+    label: &'static str,
+    view: MonitorView,
+    current: MonitorView,
+    focus: &FocusHandle,
+    cx: &mut gpui::Context<Controller>,
+) -> Div {
+    let is_current = view == current;
+    div()
+        .px_2()
+        .py(px(2.0))
+        .rounded(px(6.0))
+        .text_xs()
+        .bg(if is_current {
+            rgb(0x1e3a1e)
+        } else {
+            rgb(0x1e1e1e)
+        })
+        .border_1()
+        .border_color(if is_current {
+            rgb(0x4CAF50)
+        } else {
+            rgb(0x333333)
+        })
+        .text_color(if is_current {
+            rgb(0xffffff)
+        } else {
+            rgb(0x888888)
+        })
+        .cursor_pointer()
+        .track_focus(focus)
+        .focus(|style| style.border_color(rgb(FOCUS_RING)))
+        .on_key_down(cx.listener(move |this, ev: &KeyDownEvent, _window, cx| {
+            if ev.keystroke.key == SPACE {
+                cx.stop_propagation();
+                this.monitor_view = view;
+                cx.notify();
+            }
+        }))
+        .on_mouse_down(
+            MouseButton::Left,
+            cx.listener(move |this, _, _window, cx| {
+                this.monitor_view = view;
+                cx.notify();
+            }),
+        )
+        .child(label)
 }
 
 impl Render for Controller {
     fn render(
         &mut self,
-        _window: &mut gpui::Window,
+        window: &mut gpui::Window,
         cx: &mut gpui::Context<Self>,
     ) -> impl IntoElement {
-        // ── Drain pending state updates from daemon ───────────────────────
-        while let Ok(state) = self.state_rx.try_recv() {
-            self.monitors = state.monitors;
-            self.selected = state.selected;
-            self.opacity = state.opacity;
-            self.overlays_active = state.overlays_active;
-            self.overlay_alive = state.overlay_alive;
-        }
-
         // ── Snapshot values for the closures / builders below ────────────
         let is_active = self.overlays_active;
         let opacity_val = self.opacity;
         let is_dragging = self.is_dragging;
-        let any_selected = self.selected.iter().any(|&s| s);
+        let any_selected = self.selected_count() > 0;
         let switch_click_count = self.switch_click_count;
         let shake_count = self.shake_count;
+        let page = self.page;
+        let monitor_view = self.monitor_view;
 
         // Pre-compute which monitors currently have a live overlay.
         let overlay_alive = self.overlay_alive.clone();
 
-        // ── Monitor list ─────────────────────────────────────────────────
-        let mon_list = monitor_list(
-            &self.monitors,
-            &self.selected,
-            &overlay_alive,
+        // Give monitors that appeared since the last frame a focus handle.
+        for mon in &self.monitors {
+            self.monitor_focus
+                .entry(mon.id.clone())
+                .or_insert_with(|| cx.focus_handle().tab_stop(true));
+        }
+        for id in self.stats.iter().flat_map(|r| r.monitors.keys()) {
+            self.stats_focus
+                .entry(id.clone())
+                .or_insert_with(|| cx.focus_handle().tab_stop(true));
+        }
+        let pending = pending(&self.suggestions, &self.monitors, &self.monitor_settings);
+        while self.suggestion_focus.len() < pending.len() {
+            self.suggestion_focus.push(cx.focus_handle().tab_stop(true));
+        }
+        let caption = self.focus_label(window);
+
+        // ── Monitor list / map ───────────────────────────────────────────
+        let monitors: AnyElement = match monitor_view {
+            MonitorView::List => monitor_list(
+                &self.monitors,
+                &self.selected,
+                &self.monitor_settings,
+                &overlay_alive,
+                is_active,
+                &self.monitor_focus,
+                cx,
+            )
+            .into_any_element(),
+            MonitorView::Map => monitor_map(
+                &self.monitors,
+                &self.selected,
+                &self.monitor_settings,
+                &overlay_alive,
+                &self.effective_opacity,
+                &self.monitor_focus,
+                cx,
+            )
+            .into_any_element(),
+        };
+
+        // ── Monitor list header ──────────────────────────────────────────
+        let list_header = div()
+            .flex()
+            .items_center()
+            .justify_between()
+            .w_full()
+            .max_w(px(500.0))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_lg()
+                            .font_weight(FontWeight::MEDIUM)
+                            .text_color(rgb(0xcccccc))
+                            .child(format!("Monitors ({})", self.monitors.len())),
+                    )
+                    .child(view_option(
+                        "☰ List",
+                        MonitorView::List,
+                        monitor_view,
+                        &self.view_focus[0],
+                        cx,
+                    ))
+                    .child(view_option(
+                        "▦ Map",
+                        MonitorView::Map,
+                        monitor_view,
+                        &self.view_focus[1],
+                        cx,
+                    )),
+            )
+            .child({
+                // Build the hint label.  When the user clicks the
+                // switch with no monitors selected we play a
+                // horizontal-shake + orange-to-grey colour fade.
+                let hint: AnyElement = if shake_count > 0 && !is_active {
+                    div()
+                        .text_sm()
+                        .child("Select monitors to protect")
+                        .with_animation(
+                            ("monitor_hint_shake", shake_count),
+                            Animation::new(Duration::from_millis(500)),
+                            |el, delta| {
+                                // Decaying sinusoidal offset (3 oscillations).
+                                let offset = (delta * PI * 6.0).sin() * 8.0 * (1.0 - delta);
+                                // Colour: orange (0xee6b2f) → grey (0x666666).
+                                let t = delta.clamp(0.0, 1.0);
+                                let r = (0xeeu8 as f32 + (0x66u8 as f32 - 0xeeu8 as f32) * t)
+                                    .round() as u32;
+                                let g = (0x6bu8 as f32 + (0x66u8 as f32 - 0x6bu8 as f32) * t)
+                                    .round() as u32;
+                                let b = (0x2fu8 as f32 + (0x66u8 as f32 - 0x2fu8 as f32) * t)
+                                    .round() as u32;
+                                let color = (r << 16) | (g << 8) | b;
+                                // relative() + left() supports negative offsets
+                                // (ml() clamps to 0 in the flex engine).
+                                el.text_color(rgb(color)).relative().left(px(offset))
+                            },
+                        )
+                        .into_any_element()
+                } else if is_active {
+                    div()
+                        .text_sm()
+                        .text_color(rgb(0x666666))
+                        .child("🔒 Selection locked")
+                        .into_any_element()
+                } else {
+                    div()
+                        .text_sm()
+                        .text_color(rgb(0x666666))
+                        .child("Select monitors to protect")
+                        .into_any_element()
+                };
+                hint
+            });
+
+        // ── Opacity slider ───────────────────────────────────────────────
+        let slider = opacity_slider(
+            opacity_val,
+            &self.slider_bounds,
             is_active,
+            &self.slider_focus,
             cx,
         );
 
-        // ── Opacity slider ───────────────────────────────────────────────
-        let slider = opacity_slider(opacity_val, &self.slider_bounds, is_active, cx);
-
         // ── Activation panel ─────────────────────────────────────────────
-        let active_count = self.overlay_alive.iter().filter(|&&a| a).count();
+        let active_count = self.overlay_alive.len();
 
         let activation_panel = div()
             .flex()
@@ -202,34 +709,35 @@ impl Render for Controller {
                             } else if !any_selected {
                                 "Select monitors to enable".to_string()
                             } else {
-                                let sel = self.selected.iter().filter(|&&s| s).count();
-                                format!("{} monitor(s) selected", sel)
+                                format!("{} monitor(s) selected", self.selected_count())
                             }),
                     ),
             )
             .child(switch(
                 is_active,
                 switch_click_count,
+                &self.switch_focus,
                 cx.listener(move |this, _, _window, cx| {
-                    if this.overlays_active {
-                        // Optimistic update + send command
-                        this.overlays_active = false;
-                        this.switch_click_count += 1;
-                        this.shake_count = 0;
-                        let _ = this.cmd_tx.try_send(UiMsg::SetActive(false));
-                    } else if this.selected.iter().any(|&s| s) {
-                        this.overlays_active = true;
-                        this.switch_click_count += 1;
-                        this.shake_count = 0;
-                        let _ = this.cmd_tx.try_send(UiMsg::SetActive(true));
-                    } else {
-                        // No monitors selected — shake the hint label.
-                        this.shake_count += 1;
-                    }
+                    this.toggle_protection();
                     cx.notify();
                 }),
             ));
 
+        // ── Region suggestions ───────────────────────────────────────────
+        let suggestions =
+            (!pending.is_empty()).then(|| suggestions_panel(&pending, &self.suggestion_focus, cx));
+
+        // ── Statistics page ──────────────────────────────────────────────
+        let stats_page = (page == Page::Statistics).then(|| {
+            stats_view(
+                self.stats.as_ref(),
+                &self.monitors,
+                self.stats_monitor.as_ref(),
+                &self.stats_focus,
+                cx,
+            )
+        });
+
         // ── Separator helper ─────────────────────────────────────────────
         let sep = || div().w_full().max_w(px(500.0)).h(px(1.0)).bg(rgb(0x333333));
 
@@ -243,6 +751,8 @@ impl Render for Controller {
             .p_6()
             .items_center()
             .bg(rgb(0x0e0e0e))
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(Self::on_key_down))
             // Title
             .child(
                 div()
@@ -264,81 +774,44 @@ impl Render for Controller {
                             .child("Protect your OLED display from burn-in"),
                     ),
             )
-            .child(sep())
-            // Monitor list header
+            // Page switcher
             .child(
                 div()
                     .flex()
-                    .items_center()
-                    .justify_between()
                     .w_full()
                     .max_w(px(500.0))
-                    .child(
-                        div()
-                            .text_lg()
-                            .font_weight(FontWeight::MEDIUM)
-                            .text_color(rgb(0xcccccc))
-                            .child(format!("Monitors ({})", self.monitors.len())),
-                    )
-                    .child({
-                        // Build the hint label.  When the user clicks the
-                        // switch with no monitors selected we play a
-                        // horizontal-shake + orange-to-grey colour fade.
-                        let hint: AnyElement = if shake_count > 0 && !is_active {
-                            div()
-                                .text_sm()
-                                .child("Select monitors to protect")
-                                .with_animation(
-                                    ("monitor_hint_shake", shake_count),
-                                    Animation::new(Duration::from_millis(500)),
-                                    |el, delta| {
-                                        // Decaying sinusoidal offset (3 oscillations).
-                                        let offset = (delta * PI * 6.0).sin() * 8.0 * (1.0 - delta);
-                                        // Colour: orange (0xee6b2f) → grey (0x666666).
-                                        let t = delta.clamp(0.0, 1.0);
-                                        let r = (0xeeu8 as f32
-                                            + (0x66u8 as f32 - 0xeeu8 as f32) * t)
-                                            .round()
-                                            as u32;
-                                        let g = (0x6bu8 as f32
-                                            + (0x66u8 as f32 - 0x6bu8 as f32) * t)
-                                            .round()
-                                            as u32;
-                                        let b = (0x2fu8 as f32
-                                            + (0x66u8 as f32 - 0x2fu8 as f32) * t)
-                                            .round()
-                                            as u32;
-                                        let color = (r << 16) | (g << 8) | b;
-                                        // relative() + left() supports negative offsets
-                                        // (ml() clamps to 0 in the flex engine).
-                                        el.text_color(rgb(color)).relative().left(px(offset))
-                                    },
-                                )
-                                .into_any_element()
-                        } else if is_active {
-                            div()
-                                .text_sm()
-                                .text_color(rgb(0x666666))
-                                .child("🔒 Selection locked")
-                                .into_any_element()
-                        } else {
-                            div()
-                                .text_sm()
-                                .text_color(rgb(0x666666))
-                                .child("Select monitors to protect")
-                                .into_any_element()
-                        };
-                        hint
-                    }),
+                    .border_b_1()
+                    .border_color(rgb(0x333333))
+                    .child(tab(
+                        "Protection",
+                        Page::Protection,
+                        page,
+                        &self.page_focus[0],
+                        cx,
+                    ))
+                    .child(tab(
+                        "Statistics",
+                        Page::Statistics,
+                        page,
+                        &self.page_focus[1],
+                        cx,
+                    )),
             )
-            // Monitor list
-            .child(mon_list)
-            .child(sep())
-            // Opacity slider
-            .child(slider)
-            .child(sep())
-            // Activation panel
-            .child(activation_panel)
+            .when(page == Page::Protection, |el| {
+                el.child(list_header)
+                    // Monitor list or map
+                    .child(monitors)
+                    // Suggested regions, if any
+                    .when_some(suggestions, |el, panel| el.child(sep()).child(panel))
+                    .child(sep())
+                    // Opacity slider
+                    .child(slider)
+                    .child(sep())
+                    // Activation panel
+                    .child(activation_panel)
+            })
+            .children(stats_page)
+            .children(caption.map(focus_caption))
             .when(is_dragging, |el| {
                 el.child(
                     div()
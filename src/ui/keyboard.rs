@@ -0,0 +1,174 @@
+//! Keyboard navigation and focus captions for the Controller window.
+//!
+//! Tab and Shift+Tab move focus through the page tabs, the list / map choice,
+//! the monitor tiles or map rectangles (or the statistics page's monitor
+//! picker), the opacity slider and the protection switch.  Space activates
+//! the focused control, the arrow keys, Page Up / Page Down and Home / End
+//! adjust the slider, and Enter toggles protection from anywhere in the
+//! window.  Suggested regions, when there are any, put their Add buttons
+//! between the monitors and the slider.
+//!
+//! Each focusable control has a label saying what it is, its state and its
+//! keys, which the window shows in a caption while the control has focus.
+//! The caption is only drawn text: GPUI does not expose an accessibility
+//! tree, so screen readers and other assistive technology get nothing from
+//! it.  Reaching them needs support in GPUI first.
+
+use gpui::prelude::*;
+use gpui::{Div, div, px, rgb};
+
+/// Border colour of the focused control.
+pub const FOCUS_RING: u32 = 0x90CAF9;
+
+/// Key names as GPUI reports them in `Keystroke::key`.
+pub const TAB: &str = "tab";
+pub const SPACE: &str = "space";
+pub const ENTER: &str = "enter";
+
+/// Opacity after pressing `key` on the slider at `opacity`, or `None` if
+/// `key` does not adjust it.  Steps are whole percent: 1 for the arrow keys,
+/// 10 for Page Up / Page Down.
+pub fn opacity_after_key(opacity: u8, key: &str) -> Option<u8> {
+    let pct = ((opacity as f32 / 255.0) * 100.0).round() as i32;
+    let pct = match key {
+        "left" | "down" => pct - 1,
+        "right" | "up" => pct + 1,
+        "pagedown" => pct - 10,
+        "pageup" => pct + 10,
+        "home" => 0,
+        "end" => 100,
+        _ => return None,
+    };
+    Some(((pct.clamp(0, 100) as f32 / 100.0) * 255.0).round() as u8)
+}
+
+// ── Labels ───────────────────────────────────────────────────────────────────
+
+/// Label of a monitor tile.
+pub fn monitor_label(name: &str, selected: bool, locked: bool) -> String {
+    let state = if selected { "selected" } else { "not selected" };
+    if locked {
+        format!(
+            "{}, {}. Selection is locked while protection is on.",
+            name, state
+        )
+    } else {
+        format!("{}, {}. Space toggles selection.", name, state)
+    }
+}
+
+/// Label of the opacity slider.
+pub fn slider_label(opacity: u8) -> String {
+    format!(
+        "Overlay intensity, {}%. Arrow keys change it by 1%, Page Up and Page Down by 10%.",
+        ((opacity as f32 / 255.0) * 100.0).round() as u8
+    )
+}
+
+/// Label of the protection switch.
+pub fn switch_label(active: bool) -> String {
+    format!(
+        "Protection, {}. Space or Enter toggles it.",
+        if active { "on" } else { "off" }
+    )
+}
+
+/// Label of the Add button of a suggested region.
+pub fn suggestion_label(monitor: &str, area: &str) -> String {
+    format!("Suggested region on {}, {}. Space adds it.", monitor, area)
+}
+
+/// Label of one of several choices, such as a page tab.
+pub fn choice_label(name: &str, current: bool) -> String {
+    if current {
+        format!("{}, shown.", name)
+    } else {
+        format!("{}. Space shows it.", name)
+    }
+}
+
+/// Caption with the focused control's label, pinned to the bottom of the
+/// window so it does not shift the layout.  Visible text only; nothing is
+/// announced to assistive technology.
+///
+/// TODO: screen-reader support is still open.  Once GPUI exposes an
+/// accessibility tree, publish each control's label there (UI Automation on
+/// Windows) instead of only drawing it.
+pub fn focus_caption(label: String) -> Div {
+    div()
+        .absolute()
+        .bottom(px(6.0))
+        .left(px(0.0))
+        .w_full()
+        .flex()
+        .justify_center()
+        .px_6()
+        .text_xs()
+        .text_color(rgb(0x888888))
+        .child(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Opacity at `pct` percent, as the slider stores it.
+    fn at(pct: u8) -> u8 {
+        ((pct as f32 / 100.0) * 255.0).round() as u8
+    }
+
+    #[test]
+    fn arrow_keys_step_by_one_percent() {
+        assert_eq!(opacity_after_key(at(50), "right"), Some(at(51)));
+        assert_eq!(opacity_after_key(at(50), "up"), Some(at(51)));
+        assert_eq!(opacity_after_key(at(50), "left"), Some(at(49)));
+        assert_eq!(opacity_after_key(at(50), "down"), Some(at(49)));
+    }
+
+    #[test]
+    fn page_keys_step_by_ten_percent() {
+        assert_eq!(opacity_after_key(at(50), "pageup"), Some(at(60)));
+        assert_eq!(opacity_after_key(at(50), "pagedown"), Some(at(40)));
+    }
+
+    #[test]
+    fn home_and_end_jump_to_the_ends() {
+        assert_eq!(opacity_after_key(at(37), "home"), Some(0));
+        assert_eq!(opacity_after_key(at(37), "end"), Some(255));
+    }
+
+    #[test]
+    fn steps_stop_at_the_ends() {
+        assert_eq!(opacity_after_key(0, "left"), Some(0));
+        assert_eq!(opacity_after_key(0, "pagedown"), Some(0));
+        assert_eq!(opacity_after_key(255, "right"), Some(255));
+        assert_eq!(opacity_after_key(at(95), "pageup"), Some(255));
+        assert_eq!(opacity_after_key(at(5), "pagedown"), Some(0));
+    }
+
+    #[test]
+    fn off_grid_opacity_snaps_to_the_nearest_percent_first() {
+        // 128 is 50.2%, so one step up lands on 51%.
+        assert_eq!(opacity_after_key(128, "right"), Some(at(51)));
+        assert_eq!(opacity_after_key(128, "left"), Some(at(49)));
+    }
+
+    #[test]
+    fn every_arrow_press_moves_the_slider() {
+        for opacity in 0..255u8 {
+            let up = opacity_after_key(opacity, "right").unwrap();
+            assert!(up > opacity, "right from {} gave {}", opacity, up);
+        }
+        for opacity in 1..=255u8 {
+            let down = opacity_after_key(opacity, "left").unwrap();
+            assert!(down < opacity, "left from {} gave {}", opacity, down);
+        }
+    }
+
+    #[test]
+    fn other_keys_are_ignored() {
+        for key in ["space", "enter", "tab", "a", "escape", ""] {
+            assert_eq!(opacity_after_key(at(50), key), None, "{:?}", key);
+        }
+    }
+}
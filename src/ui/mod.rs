@@ -1,5 +1,9 @@
 pub mod components;
 pub mod controller;
+pub mod keyboard;
 pub mod monitor_list;
+pub mod monitor_map;
+pub mod stats_view;
+pub mod suggestions;
 
-pub use controller::Controller;
+pub use controller::{Controller, DaemonUpdate};
@@ -1,10 +1,15 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use gpui::prelude::*;
-use gpui::{ElementId, FontWeight, MouseButton, ScrollHandle, div, px, rgb};
+use gpui::{
+    ElementId, FocusHandle, FontWeight, KeyDownEvent, MouseButton, ScrollHandle, div, px, rgb,
+};
 
-use crate::ipc::UiMsg;
-use crate::monitor::MonitorInfo;
+use crate::monitor::{MonitorId, MonitorInfo};
+use crate::overlay::MonitorSettings;
 use crate::ui::components::checkbox;
 use crate::ui::controller::Controller;
+use crate::ui::keyboard::{FOCUS_RING, SPACE};
 
 /// Approximate height of a single monitor tile (py_3 * 2 + content + gap).
 const TILE_HEIGHT: f32 = 64.0;
@@ -13,36 +18,49 @@ const TILE_GAP: f32 = 8.0;
 /// Maximum number of tiles visible without scrolling.
 const MAX_VISIBLE: usize = 3;
 
+/// Name to show for `mon`, the `index`-th connected monitor.
+pub fn display_name(mon: &MonitorInfo, index: usize) -> String {
+    if !mon.friendly_name.is_empty() {
+        mon.friendly_name.clone()
+    } else if !mon.name.is_empty() {
+        mon.name.replace("\\\\.\\\\.\\", "")
+    } else {
+        format!("Monitor {}", index + 1)
+    }
+}
+
 /// Build the monitor list section: a vertical stack of selectable monitor rows
 /// wrapped in a scrollable container that shows at most 3 tiles at a time.
 ///
-/// Each row displays the monitor's device name, resolution, position, and
-/// an activity indicator. Clicking a row (or its checkbox) toggles its
-/// selection — but only when overlays are **not** currently active (to
-/// prevent mid-flight changes).
+/// Each row displays the monitor's device name, resolution, position, any
+/// per-monitor opacity override, and an activity indicator. Clicking a row
+/// (or its checkbox) toggles its selection — but only when overlays are
+/// **not** currently active (to prevent mid-flight changes).  Rows take their
+/// handle from `focus`, and Space toggles the focused row.
 pub fn monitor_list(
     monitors: &[MonitorInfo],
-    selected: &[bool],
-    overlay_hwnds: &[bool],
+    selected: &BTreeSet<MonitorId>,
+    monitor_settings: &BTreeMap<MonitorId, MonitorSettings>,
+    overlay_hwnds: &BTreeSet<MonitorId>,
     overlays_active: bool,
+    focus: &BTreeMap<MonitorId, FocusHandle>,
     cx: &mut gpui::Context<Controller>,
 ) -> impl IntoElement + use<> {
     let mut inner = div().flex().flex_col().gap_2().w_full().pb(px(2.0));
 
     for (i, mon) in monitors.iter().enumerate() {
-        let is_selected = selected.get(i).copied().unwrap_or(false);
-        let has_overlay = overlay_hwnds.get(i).copied().unwrap_or(false);
+        let is_selected = selected.contains(&mon.id);
+        let has_overlay = overlay_hwnds.contains(&mon.id);
 
-        let display_name = if !mon.friendly_name.is_empty() {
-            mon.friendly_name.clone()
-        } else if !mon.name.is_empty() {
-            mon.name.replace("\\\\.\\\\.\\", "")
-        } else {
-            format!("Monitor {}", i + 1)
-        };
+        let display_name = display_name(mon, i);
 
         let resolution = format!("{}x{}", mon.width, mon.height);
         let position = format!("pos: ({}, {})", mon.x, mon.y);
+        let custom_opacity = monitor_settings
+            .get(&mon.id)
+            .and_then(|ms| ms.opacity)
+            .map(|o| format!("custom {}%", ((o as f32 / 255.0) * 100.0).round() as u8))
+            .unwrap_or_default();
 
         let status_text = if has_overlay && overlays_active {
             "● active"
@@ -50,7 +68,9 @@ pub fn monitor_list(
             ""
         };
 
-        let idx = i;
+        let row_id = mon.id.clone();
+        let check_id = mon.id.clone();
+        let key_id = mon.id.clone();
         let row = div()
             .flex()
             .items_center()
@@ -71,24 +91,27 @@ pub fn monitor_list(
                 rgb(0x333333)
             })
             .cursor_pointer()
+            .when_some(focus.get(&mon.id), |el, handle| el.track_focus(handle))
+            .focus(|style| style.border_color(rgb(FOCUS_RING)))
+            .on_key_down(cx.listener(move |this, ev: &KeyDownEvent, _window, cx| {
+                if ev.keystroke.key == SPACE {
+                    cx.stop_propagation();
+                    this.toggle_monitor(&key_id);
+                    cx.notify();
+                }
+            }))
             .on_mouse_down(
                 MouseButton::Left,
                 cx.listener(move |this, _, _window, cx| {
-                    if !this.overlays_active {
-                        this.selected[idx] = !this.selected[idx];
-                        let _ = this.cmd_tx.try_send(UiMsg::ToggleMonitor(idx));
-                        cx.notify();
-                    }
+                    this.toggle_monitor(&row_id);
+                    cx.notify();
                 }),
             )
             .child(checkbox(
                 is_selected,
                 cx.listener(move |this, _, _window, cx| {
-                    if !this.overlays_active {
-                        this.selected[idx] = !this.selected[idx];
-                        let _ = this.cmd_tx.try_send(UiMsg::ToggleMonitor(idx));
-                        cx.notify();
-                    }
+                    this.toggle_monitor(&check_id);
+                    cx.notify();
                 }),
             ))
             .child(
@@ -110,7 +133,13 @@ pub fn monitor_list(
                             .flex()
                             .gap_3()
                             .child(div().text_sm().text_color(rgb(0x888888)).child(resolution))
-                            .child(div().text_sm().text_color(rgb(0x666666)).child(position)),
+                            .child(div().text_sm().text_color(rgb(0x666666)).child(position))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(rgb(0x81C784))
+                                    .child(custom_opacity),
+                            ),
                     ),
             )
             .child(
@@ -0,0 +1,309 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use gpui::prelude::*;
+use gpui::{FocusHandle, FontWeight, KeyDownEvent, MouseButton, div, px, rgb, rgba};
+
+use crate::monitor::{MonitorId, MonitorInfo};
+use crate::overlay::MonitorSettings;
+use crate::overlay::region::Rect;
+use crate::ui::controller::Controller;
+use crate::ui::keyboard::{FOCUS_RING, SPACE};
+
+/// Size of the map area; the height matches the list's three visible tiles.
+const MAP_WIDTH: f32 = 480.0;
+const MAP_HEIGHT: f32 = 208.0;
+/// Space kept free around the arrangement.
+const MAP_MARGIN: f32 = 12.0;
+/// Space between neighbouring monitors.
+const MAP_GAP: f32 = 4.0;
+
+/// Where to draw monitors at `rects` (virtual-screen `(x, y, width, height)`)
+/// in an `area_w` × `area_h` box, as `(left, top, width, height)` in the
+/// same order.
+///
+/// One scale fits the arrangement's bounding box inside the box less
+/// `margin` on every side, keeping proportions, and the arrangement is
+/// centred.  Each monitor then shrinks by `gap / 2` on every side so
+/// neighbours stay apart.
+pub fn fit_layout(
+    rects: &[Rect],
+    area_w: f32,
+    area_h: f32,
+    margin: f32,
+    gap: f32,
+) -> Vec<(f32, f32, f32, f32)> {
+    let Some(left) = rects.iter().map(|r| r.0).min() else {
+        return Vec::new();
+    };
+    let top = rects.iter().map(|r| r.1).min().unwrap_or(0);
+    let right = rects.iter().map(|r| r.0 + r.2).max().unwrap_or(left);
+    let bottom = rects.iter().map(|r| r.1 + r.3).max().unwrap_or(top);
+    let (span_w, span_h) = ((right - left).max(1) as f32, (bottom - top).max(1) as f32);
+
+    let scale =
+        ((area_w - 2.0 * margin).max(0.0) / span_w).min((area_h - 2.0 * margin).max(0.0) / span_h);
+    let off_x = (area_w - span_w * scale) / 2.0;
+    let off_y = (area_h - span_h * scale) / 2.0;
+    let inset = gap / 2.0;
+
+    rects
+        .iter()
+        .map(|&(x, y, w, h)| {
+            let (w, h) = (w as f32 * scale, h as f32 * scale);
+            (
+                off_x + (x - left) as f32 * scale + inset.min(w / 2.0),
+                off_y + (y - top) as f32 * scale + inset.min(h / 2.0),
+                (w - gap).max(0.0),
+                (h - gap).max(0.0),
+            )
+        })
+        .collect()
+}
+
+/// Build the monitor map: every monitor drawn to scale at its place in the
+/// desktop arrangement, as in the Windows display settings.
+///
+/// Each rectangle shows the monitor's device name (e.g. `DISPLAY3`), its
+/// model name and its effective opacity (from `effective_opacity`, with schedules,
+/// application rules and idle dimming applied), and is darkened by that
+/// opacity while its overlay is live (its id is in `live`).  Clicking a rectangle
+/// (or Space while it has focus) toggles its selection, under the same lock
+/// as the list.
+pub fn monitor_map(
+    monitors: &[MonitorInfo],
+    selected: &BTreeSet<MonitorId>,
+    monitor_settings: &BTreeMap<MonitorId, MonitorSettings>,
+    live: &BTreeSet<MonitorId>,
+    effective_opacity: &BTreeMap<MonitorId, u8>,
+    focus: &BTreeMap<MonitorId, FocusHandle>,
+    cx: &mut gpui::Context<Controller>,
+) -> impl IntoElement + use<> {
+    let rects: Vec<Rect> = monitors
+        .iter()
+        .map(|m| (m.x, m.y, m.width, m.height))
+        .collect();
+    let placed = fit_layout(&rects, MAP_WIDTH, MAP_HEIGHT, MAP_MARGIN, MAP_GAP);
+
+    let mut map = div()
+        .relative()
+        .w(px(MAP_WIDTH))
+        .h(px(MAP_HEIGHT))
+        .rounded(px(8.0))
+        .bg(rgb(0x141414))
+        .border_1()
+        .border_color(rgb(0x333333));
+
+    for (i, (mon, &(left, top, width, height))) in monitors.iter().zip(&placed).enumerate() {
+        let is_selected = selected.contains(&mon.id);
+        let is_live = live.contains(&mon.id);
+        let custom = monitor_settings.get(&mon.id).and_then(|ms| ms.opacity);
+        let effective = effective_opacity.get(&mon.id).copied().unwrap_or_default();
+        let pct = ((effective as f32 / 255.0) * 100.0).round() as u8;
+        let short_name = if mon.name.is_empty() {
+            format!("Monitor {}", i + 1)
+        } else {
+            mon.name.trim_start_matches(r"\\.\").to_string()
+        };
+
+        let click_id = mon.id.clone();
+        let key_id = mon.id.clone();
+        let tile = div()
+            .absolute()
+            .left(px(left))
+            .top(px(top))
+            .w(px(width))
+            .h(px(height))
+            .overflow_hidden()
+            .rounded(px(4.0))
+            .bg(if is_selected {
+                rgb(0x1e3a1e)
+            } else {
+                rgb(0x1e1e1e)
+            })
+            .border_1()
+            .border_color(if is_selected {
+                rgb(0x4CAF50)
+            } else {
+                rgb(0x444444)
+            })
+            .cursor_pointer()
+            .when_some(focus.get(&mon.id), |el, handle| el.track_focus(handle))
+            .focus(|style| style.border_color(rgb(FOCUS_RING)))
+            .on_key_down(cx.listener(move |this, ev: &KeyDownEvent, _window, cx| {
+                if ev.keystroke.key == SPACE {
+                    cx.stop_propagation();
+                    this.toggle_monitor(&key_id);
+                    cx.notify();
+                }
+            }))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, _, _window, cx| {
+                    this.toggle_monitor(&click_id);
+                    cx.notify();
+                }),
+            )
+            // The overlay's dimming, to scale.
+            .when(is_live, |el| {
+                el.child(
+                    div()
+                        .absolute()
+                        .top(px(0.0))
+                        .left(px(0.0))
+                        .size_full()
+                        .bg(rgba(effective as u32)),
+                )
+            })
+            .child(
+                div()
+                    .relative()
+                    .flex()
+                    .flex_col()
+                    .items_center()
+                    .justify_center()
+                    .size_full()
+                    .px_1()
+                    .gap(px(2.0))
+                    .child(
+                        div()
+                            .text_sm()
+                            .font_weight(FontWeight::BOLD)
+                            .text_color(rgb(0xffffff))
+                            .child(short_name),
+                    )
+                    .when(!mon.friendly_name.is_empty(), |el| {
+                        el.child(
+                            div()
+                                .max_w_full()
+                                .truncate()
+                                .text_xs()
+                                .text_color(rgb(0x888888))
+                                .child(mon.friendly_name.clone()),
+                        )
+                    })
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(if is_live {
+                                rgb(0x4CAF50)
+                            } else if custom.is_some() {
+                                rgb(0x81C784)
+                            } else {
+                                rgb(0x666666)
+                            })
+                            .child(if is_live {
+                                format!("● {}%", pct)
+                            } else {
+                                format!("{}%", pct)
+                            }),
+                    ),
+            );
+
+        map = map.child(tile);
+    }
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FHD: (i32, i32) = (1920, 1080);
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    fn assert_placed(got: (f32, f32, f32, f32), want: (f32, f32, f32, f32)) {
+        let same = close(got.0, want.0)
+            && close(got.1, want.1)
+            && close(got.2, want.2)
+            && close(got.3, want.3);
+        assert!(same, "placed at {:?}, expected {:?}", got, want);
+    }
+
+    /// Whether `placed` stays inside an `area_w` × `area_h` box.
+    fn inside(placed: &[(f32, f32, f32, f32)], area_w: f32, area_h: f32) -> bool {
+        placed.iter().all(|&(x, y, w, h)| {
+            x >= 0.0 && y >= 0.0 && w >= 0.0 && h >= 0.0 && x + w <= area_w && y + h <= area_h
+        })
+    }
+
+    #[test]
+    fn no_monitors_place_nothing() {
+        assert!(fit_layout(&[], MAP_WIDTH, MAP_HEIGHT, MAP_MARGIN, MAP_GAP).is_empty());
+    }
+
+    #[test]
+    fn single_monitor_fills_the_height_and_is_centred() {
+        let placed = fit_layout(&[(0, 0, FHD.0, FHD.1)], 480.0, 208.0, 12.0, 4.0);
+        // 184 px of height for 1080 rows; 1920 columns become 327.1 px.
+        let scale = 184.0 / 1080.0;
+        let width = 1920.0 * scale;
+        let left = (480.0 - width) / 2.0;
+        assert_placed(placed[0], (left + 2.0, 14.0, width - 4.0, 180.0));
+    }
+
+    #[test]
+    fn side_by_side_pair_fills_the_width_with_a_gap() {
+        let rects = [(0, 0, FHD.0, FHD.1), (FHD.0, 0, FHD.0, FHD.1)];
+        let placed = fit_layout(&rects, 480.0, 208.0, 12.0, 4.0);
+        // 456 px of width for 3840 columns; each monitor gets half.
+        let height = 1080.0 * 456.0 / 3840.0;
+        let top = (208.0 - height) / 2.0;
+        assert_placed(placed[0], (14.0, top + 2.0, 224.0, height - 4.0));
+        assert_placed(placed[1], (242.0, top + 2.0, 224.0, height - 4.0));
+        // Neighbours are `gap` apart.
+        assert!(close(placed[1].0 - (placed[0].0 + placed[0].2), 4.0));
+    }
+
+    #[test]
+    fn negative_origins_place_like_positive_ones() {
+        let left_of_primary = [(-FHD.0, 0, FHD.0, FHD.1), (0, 0, FHD.0, FHD.1)];
+        let right_of_primary = [(0, 0, FHD.0, FHD.1), (FHD.0, 0, FHD.0, FHD.1)];
+        assert_eq!(
+            fit_layout(&left_of_primary, 480.0, 208.0, 12.0, 4.0),
+            fit_layout(&right_of_primary, 480.0, 208.0, 12.0, 4.0)
+        );
+
+        // A monitor above the primary one, offset to the left.
+        let rects = [(0, 0, FHD.0, FHD.1), (-960, -FHD.1, FHD.0, FHD.1)];
+        let placed = fit_layout(&rects, 480.0, 208.0, 12.0, 4.0);
+        assert!(inside(&placed, 480.0, 208.0));
+        assert!(placed[1].1 < placed[0].1);
+        assert!(placed[1].0 < placed[0].0);
+    }
+
+    #[test]
+    fn tall_narrow_monitor_keeps_its_aspect_ratio() {
+        let placed = fit_layout(&[(0, 0, FHD.1, FHD.0)], 480.0, 208.0, 12.0, 4.0);
+        let width = 1080.0 * 184.0 / 1920.0;
+        let left = (480.0 - width) / 2.0;
+        assert_placed(placed[0], (left + 2.0, 14.0, width - 4.0, 180.0));
+
+        // A narrow box limits the width instead.
+        let placed = fit_layout(&[(0, 0, FHD.0, FHD.1)], 100.0, 400.0, 10.0, 4.0);
+        let height = 1080.0 * 80.0 / 1920.0;
+        let top = (400.0 - height) / 2.0;
+        assert_placed(placed[0], (12.0, top + 2.0, 76.0, height - 4.0));
+    }
+
+    #[test]
+    fn box_smaller_than_margin_and_gap_collapses_without_escaping() {
+        let rects = [(0, 0, FHD.0, FHD.1), (FHD.0, 0, FHD.0, FHD.1)];
+        let placed = fit_layout(&rects, 20.0, 20.0, 12.0, 4.0);
+        assert_eq!(placed.len(), 2);
+        for &(x, y, w, h) in &placed {
+            assert!(x.is_finite() && y.is_finite());
+            assert_eq!((w, h), (0.0, 0.0));
+        }
+        assert!(inside(&placed, 20.0, 20.0));
+
+        // Room for the monitors but not the gap: they shrink to nothing
+        // rather than to negative sizes.
+        let placed = fit_layout(&rects, 30.0, 30.0, 12.0, 8.0);
+        assert!(placed.iter().all(|&(_, _, w, h)| w == 0.0 && h >= 0.0));
+        assert!(inside(&placed, 30.0, 30.0));
+    }
+}
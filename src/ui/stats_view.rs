@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+
+use gpui::prelude::*;
+use gpui::{Div, FocusHandle, FontWeight, KeyDownEvent, MouseButton, div, px, rgb};
+
+use crate::monitor::{MonitorId, MonitorInfo};
+use crate::stats::{self, DAY_MS, DayBar, MonitorReport, StatsReport};
+use crate::ui::controller::Controller;
+use crate::ui::keyboard::{FOCUS_RING, SPACE};
+use crate::ui::monitor_list::display_name;
+
+/// Height of a full (24-hour) bar in the daily chart.
+const CHART_HEIGHT: f32 = 96.0;
+/// Height of a fully opaque slot in the 24-hour timeline.
+const TIMELINE_HEIGHT: f32 = 36.0;
+
+const PROTECTED: u32 = 0x4CAF50;
+const UNPROTECTED: u32 = 0x555555;
+/// Background of empty chart space.
+const TRACK: u32 = 0x161616;
+
+/// Build the statistics page: a picker of the monitors with history and, for
+/// the chosen one, a daily bar chart of protected versus unprotected hours,
+/// the overlay opacity over the last 24 hours, and totals since the monitor
+/// was first seen.
+///
+/// `shown` is the monitor the user picked; when it is `None` or has no
+/// history the first connected monitor with history is shown instead.
+/// Picker pills take their handle from `focus`, and Space picks the focused
+/// one.
+pub fn stats_view(
+    report: Option<&StatsReport>,
+    monitors: &[MonitorInfo],
+    shown: Option<&MonitorId>,
+    focus: &BTreeMap<MonitorId, FocusHandle>,
+    cx: &mut gpui::Context<Controller>,
+) -> impl IntoElement + use<> {
+    let page = div().flex().flex_col().gap_3().w_full().max_w(px(500.0));
+
+    let Some(report) = report else {
+        return page.child(note("Loading statistics…"));
+    };
+    let entries = entries(report, monitors);
+    let Some((id, _)) = shown_entry(&entries, shown) else {
+        return page.child(note(
+            "No history yet. Exposure is recorded while OLED Care runs.",
+        ));
+    };
+    let summary = &report.monitors[id];
+
+    page.when(entries.len() > 1, |el| {
+        el.child(picker(&entries, id, focus, cx))
+    })
+    .child(daily_chart(&summary.days))
+    .child(timeline(&summary.timeline))
+    .child(totals(summary))
+}
+
+/// Monitors with history and their labels: connected ones in display order,
+/// then disconnected ones.
+pub fn entries(report: &StatsReport, monitors: &[MonitorInfo]) -> Vec<(MonitorId, String)> {
+    let connected = monitors
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| report.monitors.contains_key(&m.id))
+        .map(|(i, m)| (m.id.clone(), display_name(m, i)));
+    let disconnected = report
+        .monitors
+        .keys()
+        .filter(|id| !monitors.iter().any(|m| &m.id == *id))
+        .enumerate()
+        .map(|(i, id)| (id.clone(), format!("Disconnected {}", i + 1)));
+    connected.chain(disconnected).collect()
+}
+
+/// The entry of `shown`, or the first one if it has none.
+pub fn shown_entry<'a>(
+    entries: &'a [(MonitorId, String)],
+    shown: Option<&MonitorId>,
+) -> Option<&'a (MonitorId, String)> {
+    entries
+        .iter()
+        .find(|(id, _)| Some(id) == shown)
+        .or(entries.first())
+}
+
+/// A row of pills, one per monitor with history.
+fn picker(
+    entries: &[(MonitorId, String)],
+    current: &MonitorId,
+    focus: &BTreeMap<MonitorId, FocusHandle>,
+    cx: &mut gpui::Context<Controller>,
+) -> Div {
+    let mut row = div().flex().flex_wrap().gap_2().w_full();
+    for (id, label) in entries {
+        let is_current = id == current;
+        let pick = id.clone();
+        let key_pick = id.clone();
+        row = row.child(
+            div()
+                .px_3()
+                .py_1()
+                .rounded(px(12.0))
+                .text_sm()
+                .bg(if is_current {
+                    rgb(0x1e3a1e)
+                } else {
+                    rgb(0x1e1e1e)
+                })
+                .border_1()
+                .border_color(if is_current {
+                    rgb(0x4CAF50)
+                } else {
+                    rgb(0x333333)
+                })
+                .text_color(if is_current {
+                    rgb(0xffffff)
+                } else {
+                    rgb(0x888888)
+                })
+                .cursor_pointer()
+                .when_some(focus.get(id), |el, handle| el.track_focus(handle))
+                .focus(|style| style.border_color(rgb(FOCUS_RING)))
+                .on_key_down(cx.listener(move |this, ev: &KeyDownEvent, _window, cx| {
+                    if ev.keystroke.key == SPACE {
+                        cx.stop_propagation();
+                        this.stats_monitor = Some(key_pick.clone());
+                        cx.notify();
+                    }
+                }))
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, _, _window, cx| {
+                        this.stats_monitor = Some(pick.clone());
+                        cx.notify();
+                    }),
+                )
+                .child(format!("🖥️ {}", label)),
+        );
+    }
+    row
+}
+
+/// Daily protected (bottom) and unprotected (top) hours, scaled so a bar
+/// reaching the top is a full day.
+fn daily_chart(days: &[DayBar]) -> Div {
+    let scale = |ms: u64| px(CHART_HEIGHT * (ms as f32 / DAY_MS as f32).min(1.0));
+
+    let mut bars = div()
+        .flex()
+        .items_end()
+        .gap(px(6.0))
+        .w_full()
+        .h(px(CHART_HEIGHT));
+    let mut labels = div().flex().gap(px(6.0)).w_full();
+    for bar in days {
+        bars = bars.child(
+            div()
+                .flex()
+                .flex_col()
+                .justify_end()
+                .flex_1()
+                .h_full()
+                .rounded(px(3.0))
+                .bg(rgb(TRACK))
+                .child(
+                    div()
+                        .w_full()
+                        .h(scale(bar.unprotected_ms))
+                        .bg(rgb(UNPROTECTED)),
+                )
+                .child(div().w_full().h(scale(bar.protected_ms)).bg(rgb(PROTECTED))),
+        );
+        let (_, _, day) = stats::civil_from_days(bar.day as i64);
+        labels = labels.child(
+            div()
+                .flex()
+                .justify_center()
+                .flex_1()
+                .text_xs()
+                .text_color(rgb(0x666666))
+                .child(day.to_string()),
+        );
+    }
+
+    let legend = div()
+        .flex()
+        .gap_3()
+        .child(swatch(PROTECTED, "Protected"))
+        .child(swatch(UNPROTECTED, "Unprotected"));
+
+    card(format!("Last {} days", days.len()), legend)
+        .child(bars)
+        .child(labels)
+}
+
+/// Mean overlay opacity per slot over the last 24 hours.  Slots without
+/// history stay empty; unprotected ones show a grey baseline.
+fn timeline(opacities: &[Option<u8>]) -> Div {
+    let mut slots = div()
+        .flex()
+        .items_end()
+        .gap(px(1.0))
+        .w_full()
+        .h(px(TIMELINE_HEIGHT))
+        .bg(rgb(TRACK))
+        .rounded(px(3.0));
+    for &opacity in opacities {
+        let mut slot = div().flex_1();
+        if let Some(opacity) = opacity {
+            let height = (TIMELINE_HEIGHT * opacity as f32 / 255.0).max(2.0);
+            slot = slot.h(px(height)).bg(if opacity > 0 {
+                rgb(PROTECTED)
+            } else {
+                rgb(UNPROTECTED)
+            });
+        }
+        slots = slots.child(slot);
+    }
+
+    let axis = div()
+        .flex()
+        .justify_between()
+        .w_full()
+        .text_xs()
+        .text_color(rgb(0x666666))
+        .child("24 h ago")
+        .child("12 h ago")
+        .child("now");
+
+    card(
+        "Last 24 hours",
+        div().text_xs().text_color(rgb(0x666666)).child("opacity"),
+    )
+    .child(slots)
+    .child(axis)
+}
+
+/// Totals since the monitor was first seen.
+fn totals(summary: &MonitorReport) -> Div {
+    let (year, month, day) = stats::civil_from_days((summary.since_ms / DAY_MS) as i64);
+    let totals = &summary.totals;
+    let mean = totals
+        .mean_protected_opacity()
+        .map(|o| format!("{}%", ((o as f32 / 255.0) * 100.0).round() as u8))
+        .unwrap_or_else(|| "—".to_string());
+
+    card(format!("Since {:04}-{:02}-{:02}", year, month, day), div()).child(
+        div()
+            .flex()
+            .justify_between()
+            .w_full()
+            .child(figure(
+                "Protected",
+                hours(totals.protected_total_ms()),
+                PROTECTED,
+            ))
+            .child(figure(
+                "Unprotected",
+                hours(totals.unprotected_ms()),
+                0xcccccc,
+            ))
+            .child(figure("Faded on hover", hours(totals.faded_ms), 0xcccccc))
+            .child(figure("Mean opacity", mean, 0x81C784)),
+    )
+}
+
+// ── Building blocks ──────────────────────────────────────────────────────────
+
+/// A section with a title on the left and `aside` on the right.
+fn card(title: impl IntoElement, aside: impl IntoElement) -> Div {
+    div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .w_full()
+        .px_4()
+        .py_3()
+        .rounded(px(12.0))
+        .bg(rgb(0x1e1e1e))
+        .border_1()
+        .border_color(rgb(0x333333))
+        .child(
+            div()
+                .flex()
+                .items_center()
+                .justify_between()
+                .child(
+                    div()
+                        .text_sm()
+                        .font_weight(FontWeight::MEDIUM)
+                        .text_color(rgb(0xcccccc))
+                        .child(title),
+                )
+                .child(aside),
+        )
+}
+
+/// A coloured square followed by a label.
+fn swatch(color: u32, label: &'static str) -> Div {
+    div()
+        .flex()
+        .items_center()
+        .gap_1()
+        .text_xs()
+        .text_color(rgb(0x888888))
+        .child(div().w(px(8.0)).h(px(8.0)).rounded(px(2.0)).bg(rgb(color)))
+        .child(label)
+}
+
+/// A large value above a small caption.
+fn figure(caption: &'static str, value: String, color: u32) -> Div {
+    div()
+        .flex()
+        .flex_col()
+        .gap(px(2.0))
+        .child(
+            div()
+                .text_lg()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(color))
+                .child(value),
+        )
+        .child(div().text_xs().text_color(rgb(0x666666)).child(caption))
+}
+
+/// A muted line of text in place of the charts.
+fn note(text: &'static str) -> Div {
+    div().text_sm().text_color(rgb(0x888888)).child(text)
+}
+
+/// `ms` as hours, with a decimal below ten.
+fn hours(ms: u64) -> String {
+    let h = ms as f64 / 3_600_000.0;
+    if h < 10.0 {
+        format!("{:.1} h", h)
+    } else {
+        format!("{:.0} h", h)
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use gpui::prelude::*;
+use gpui::{FocusHandle, FontWeight, KeyDownEvent, MouseButton, div, px, rgb};
+
+use crate::heatmap;
+use crate::monitor::{MonitorId, MonitorInfo};
+use crate::overlay::MonitorSettings;
+use crate::overlay::region::{Region, RegionRect};
+use crate::ui::controller::Controller;
+use crate::ui::keyboard::{FOCUS_RING, SPACE};
+use crate::ui::monitor_list::display_name;
+
+/// A suggested region that is not added yet.
+pub struct Pending {
+    pub monitor: MonitorId,
+    /// Display name of the monitor.
+    pub monitor_name: String,
+    pub region: Region,
+}
+
+/// The `suggestions` from content analysis that are not among the monitors'
+/// regions yet, in monitor order.  Suggestions for disconnected monitors are
+/// left out.
+pub fn pending(
+    suggestions: &BTreeMap<MonitorId, Vec<Region>>,
+    monitors: &[MonitorInfo],
+    monitor_settings: &BTreeMap<MonitorId, MonitorSettings>,
+) -> Vec<Pending> {
+    let mut pending = Vec::new();
+    for (i, mon) in monitors.iter().enumerate() {
+        let Some(suggested) = suggestions.get(&mon.id) else {
+            continue;
+        };
+        let existing = monitor_settings
+            .get(&mon.id)
+            .map(|ms| ms.regions.as_slice())
+            .unwrap_or_default();
+        for region in heatmap::unclaimed(suggested, existing) {
+            pending.push(Pending {
+                monitor: mon.id.clone(),
+                monitor_name: display_name(mon, i),
+                region,
+            });
+        }
+    }
+    pending
+}
+
+/// Size and place of a suggested region, e.g. `1920 × 40 px at 0, 1040`.
+pub fn area_text(rect: &RegionRect) -> String {
+    format!(
+        "{} × {} px at {}, {}",
+        rect.width, rect.height, rect.x, rect.y
+    )
+}
+
+/// Build the suggestions panel: one row per pending suggestion with an Add
+/// button, which takes its handle from `focus` (in the same order) and adds
+/// the region on click or Space.
+pub fn suggestions_panel(
+    pending: &[Pending],
+    focus: &[FocusHandle],
+    cx: &mut gpui::Context<Controller>,
+) -> impl IntoElement + use<> {
+    let mut panel = div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .w_full()
+        .max_w(px(500.0))
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .gap(px(2.0))
+                .child(
+                    div()
+                        .text_lg()
+                        .font_weight(FontWeight::MEDIUM)
+                        .text_color(rgb(0xcccccc))
+                        .child("Suggested regions"),
+                )
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(rgb(0x888888))
+                        .child("Areas that stayed bright and unchanged most of the time"),
+                ),
+        );
+
+    for (p, handle) in pending.iter().zip(focus) {
+        let (click_id, click_region) = (p.monitor.clone(), p.region.clone());
+        let (key_id, key_region) = (p.monitor.clone(), p.region.clone());
+        panel = panel.child(
+            div()
+                .flex()
+                .items_center()
+                .justify_between()
+                .px_3()
+                .py_2()
+                .rounded(px(8.0))
+                .bg(rgb(0x1e1e1e))
+                .border_1()
+                .border_color(rgb(0x333333))
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .child(
+                            div()
+                                .text_sm()
+                                .text_color(rgb(0xffffff))
+                                .child(format!("🖥️ {}", p.monitor_name)),
+                        )
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(rgb(0x888888))
+                                .child(area_text(&p.region.rect)),
+                        ),
+                )
+                .child(
+                    div()
+                        .px_3()
+                        .py_1()
+                        .rounded(px(12.0))
+                        .text_sm()
+                        .bg(rgb(0x1e3a1e))
+                        .border_1()
+                        .border_color(rgb(0x4CAF50))
+                        .text_color(rgb(0xffffff))
+                        .cursor_pointer()
+                        .track_focus(handle)
+                        .focus(|style| style.border_color(rgb(FOCUS_RING)))
+                        .on_key_down(cx.listener(move |this, ev: &KeyDownEvent, _window, cx| {
+                            if ev.keystroke.key == SPACE {
+                                cx.stop_propagation();
+                                this.add_region(&key_id, key_region.clone());
+                                cx.notify();
+                            }
+                        }))
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |this, _, _window, cx| {
+                                this.add_region(&click_id, click_region.clone());
+                                cx.notify();
+                            }),
+                        )
+                        .child("Add"),
+                ),
+        );
+    }
+    panel
+}